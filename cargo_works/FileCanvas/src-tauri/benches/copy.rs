@@ -0,0 +1,128 @@
+// src-tauri/benches/copy.rs
+//
+// Folder copies on the local disk: `ops::copy_dir_recursive` (one file at a
+// time through `DiskFs`) against `copier::copy_tree` with growing worker
+// pools. Two trees are built in a temp folder, one of many small files and
+// one of a few large ones, and each copy is run a few times.
+//
+//   cargo bench --bench copy
+//
+// COPY_BENCH_DIR puts the trees somewhere else, e.g. on the SSD or the
+// btrfs volume you want to measure (the temp folder is often tmpfs).
+// COPY_BENCH_RUNS changes how often each copy runs (default 3).
+
+use filecanvas_lib::copier::{self, CopyOptions, Progress};
+use filecanvas_lib::{ops, DiskFs, FsError};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+struct Tree {
+    name: &'static str,
+    folders: usize,
+    files_per_folder: usize,
+    file_size: usize,
+}
+
+const TREES: [Tree; 2] = [
+    Tree {
+        name: "small files",
+        folders: 50,
+        files_per_folder: 100,
+        file_size: 4 * 1024,
+    },
+    Tree {
+        name: "large files",
+        folders: 2,
+        files_per_folder: 8,
+        file_size: 32 * 1024 * 1024,
+    },
+];
+
+fn build(tree: &Tree, root: &Path) -> std::io::Result<u64> {
+    // Varying bytes so nothing along the way can shortcut zeroes
+    let data: Vec<u8> = (0..tree.file_size).map(|i| (i % 251) as u8).collect();
+    for folder in 0..tree.folders {
+        let dir = root.join(format!("folder-{:03}", folder));
+        fs::create_dir_all(&dir)?;
+        for file in 0..tree.files_per_folder {
+            fs::write(dir.join(format!("file-{:04}.bin", file)), &data)?;
+        }
+    }
+    Ok((tree.folders * tree.files_per_folder * tree.file_size) as u64)
+}
+
+/// Best and mean of `runs` copies of `src` to a fresh `dest`
+fn time(runs: usize, src: &Path, dest: &Path, copy: impl Fn(&Path, &Path) -> Result<(), FsError>) -> (Duration, Duration) {
+    let mut times = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let _ = fs::remove_dir_all(dest);
+        let start = Instant::now();
+        copy(src, dest).expect("copy failed");
+        times.push(start.elapsed());
+    }
+    let _ = fs::remove_dir_all(dest);
+    let best = times.iter().min().copied().unwrap_or_default();
+    let mean = times.iter().sum::<Duration>() / runs as u32;
+    (best, mean)
+}
+
+fn report(label: &str, bytes: u64, (best, mean): (Duration, Duration)) {
+    let mb_per_s = bytes as f64 / 1024.0 / 1024.0 / best.as_secs_f64();
+    println!(
+        "  {:<22} best {:>8.1} ms   mean {:>8.1} ms   {:>8.1} MB/s",
+        label,
+        best.as_secs_f64() * 1000.0,
+        mean.as_secs_f64() * 1000.0,
+        mb_per_s
+    );
+}
+
+fn main() -> std::io::Result<()> {
+    let base = std::env::var_os("COPY_BENCH_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join(format!("filecanvas-copy-bench-{}", std::process::id()));
+    let runs: usize = std::env::var("COPY_BENCH_RUNS")
+        .ok()
+        .and_then(|r| r.parse().ok())
+        .unwrap_or(3)
+        .max(1);
+    let mut pools = vec![1, 2, 4, 8];
+    let default_workers = CopyOptions::default().workers;
+    if !pools.contains(&default_workers) {
+        pools.push(default_workers);
+    }
+
+    for tree in &TREES {
+        let src = base.join("src");
+        let dest = base.join("dest");
+        let _ = fs::remove_dir_all(&base);
+        let bytes = build(tree, &src)?;
+        println!(
+            "{}: {} files of {} KiB in {} folders",
+            tree.name,
+            tree.folders * tree.files_per_folder,
+            tree.file_size / 1024,
+            tree.folders
+        );
+
+        let sequential = time(runs, &src, &dest, |src, dest| ops::copy_dir_recursive(&DiskFs, src, dest));
+        report("copy_dir_recursive", bytes, sequential);
+        for &workers in &pools {
+            let options = CopyOptions { workers };
+            let parallel = time(runs, &src, &dest, |src, dest| {
+                let progress = Progress::default();
+                copier::copy_tree(src, dest, &options, &progress)?;
+                let done = progress.snapshot();
+                assert_eq!(done.bytes_done, bytes, "progress lost bytes");
+                assert_eq!(done.files_done, done.files_total, "progress lost files");
+                Ok(())
+            });
+            report(&format!("copy_tree, {} workers", workers), bytes, parallel);
+        }
+        println!();
+    }
+
+    fs::remove_dir_all(&base)
+}
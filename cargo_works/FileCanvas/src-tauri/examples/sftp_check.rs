@@ -0,0 +1,63 @@
+// src-tauri/examples/sftp_check.rs
+//
+// Round trip against a real SFTP server: list, write, read back, rename and
+// delete a scratch file. With the container in dev/sftp:
+//
+//   SFTP_PASSWORD=canvas cargo run --example sftp_check
+//
+// SFTP_URL picks another folder (default sftp://canvas@localhost:2222/config/data).
+// The host key is trusted on first use and kept in a temp known_hosts file.
+
+use filecanvas_lib::sftp::{self, Auth, HostKeys, SftpFs};
+use filecanvas_lib::{ErrorCode, FileSystem, FsError};
+use std::io::{Read, Write};
+
+fn main() -> Result<(), FsError> {
+    let url = std::env::var("SFTP_URL").unwrap_or_else(|_| "sftp://canvas@localhost:2222/config/data".into());
+    let auth = match std::env::var("SFTP_PASSWORD") {
+        Ok(password) => Auth::Password(password),
+        Err(_) => Auth::Agent,
+    };
+    let (target, folder) = sftp::parse_url(&url)?;
+    let known_hosts = std::env::temp_dir().join("filecanvas_sftp_check_known_hosts");
+
+    let untrusted = HostKeys { file: &known_hosts, trust: None };
+    let fs = match SftpFs::connect(&target, &auth, &untrusted) {
+        Err(e) if e.code == ErrorCode::UnknownHost => {
+            println!("trusting {}", e.detail.as_deref().unwrap_or_default());
+            let trusted = HostKeys { file: &known_hosts, trust: e.detail.as_deref() };
+            SftpFs::connect(&target, &auth, &trusted)?
+        }
+        other => other?,
+    };
+    println!("connected to {}", target.key());
+
+    for entry in fs.read_dir(&folder)? {
+        println!("  {}{}", entry.name, if entry.metadata.is_dir { "/" } else { "" });
+    }
+
+    let file = folder.join("filecanvas_check.txt");
+    let renamed = folder.join("filecanvas_check_renamed.txt");
+    let payload = b"hello from filecanvas\n";
+
+    let mut writer = fs.open_write(&file)?;
+    writer.write_all(payload).map_err(|e| FsError::io(&e, &file))?;
+    writer.flush().map_err(|e| FsError::io(&e, &file))?;
+    drop(writer);
+
+    let mut read_back = Vec::new();
+    fs.open_read(&file)?
+        .read_to_end(&mut read_back)
+        .map_err(|e| FsError::io(&e, &file))?;
+    assert_eq!(read_back, payload, "read back different contents");
+    println!("wrote and read {} bytes", read_back.len());
+
+    fs.rename(&file, &renamed)?;
+    assert!(fs.exists(&renamed) && !fs.exists(&file), "rename did not take effect");
+    println!("renamed to {}", renamed.display());
+
+    fs.remove_file(&renamed)?;
+    assert!(!fs.exists(&renamed), "file still there after delete");
+    println!("deleted, all good");
+    Ok(())
+}
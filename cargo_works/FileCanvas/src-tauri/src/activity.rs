@@ -0,0 +1,336 @@
+// src-tauri/src/activity.rs
+//
+// Audit trail of file operations. Every create, copy, move, delete and rename
+// (from panels, syncs, filtered transfers, the organizer and hot folders),
+// and every "always use" app that failed to open a file, is appended as one
+// JSON line to `activity.jsonl` in the app data dir, with the user, the
+// paths, the bytes involved and how it ended. The log is only ever
+// appended to; `query_activity` filters it and `export_activity` writes the
+// same selection as CSV.
+
+use crate::store::now_ms;
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::fs;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tauri::async_runtime::spawn_blocking;
+use tauri::{Manager, Runtime};
+use tauri_plugin_dialog::DialogExt;
+
+pub const LOG_FILE: &str = "activity.jsonl";
+const DEFAULT_LIMIT: usize = 500;
+const CSV_HEADER: &str = "timestamp,user,operation,via,source,destination,bytes,result,error";
+
+// --- Data Structures ---
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    CreateFolder,
+    CreateFile,
+    Copy,
+    Move,
+    Delete,
+    Rename,
+    /// A hot folder rule started a program on the file
+    Command,
+    /// A file was handed to an app from the panels
+    Open,
+}
+
+/// What started an operation
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Via {
+    Panel,
+    Sync,
+    FilteredTransfer,
+    Organizer,
+    OrganizerUndo,
+    HotFolder,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityEntry {
+    /// Unix milliseconds
+    pub timestamp: u64,
+    /// Login name of whoever ran FileCanvas
+    pub user: String,
+    pub operation: Operation,
+    pub via: Via,
+    /// Paths, or URLs for items on a server
+    pub source: Option<String>,
+    pub destination: Option<String>,
+    pub bytes: Option<u64>,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Which entries `query_activity` and `export_activity` return
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ActivityQuery {
+    /// Unix milliseconds, inclusive
+    pub since: Option<u64>,
+    /// Unix milliseconds, exclusive
+    pub until: Option<u64>,
+    /// Source or destination is this path or inside it
+    pub path_prefix: Option<String>,
+    /// Empty for all operations
+    pub operations: Vec<Operation>,
+    pub failed_only: bool,
+    /// Newest entries first; `query_activity` defaults to 500, export to all
+    pub limit: Option<usize>,
+}
+
+/// Where entries are appended; cheap to clone into blocking tasks
+#[derive(Debug, Clone)]
+pub struct ActivityLog {
+    path: PathBuf,
+}
+
+// --- Helpers ---
+
+fn current_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".into())
+}
+
+/// `path` is `prefix` or below it, on a path boundary
+fn is_under(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches(['/', '\\']);
+    match path.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with(['/', '\\']),
+        None => false,
+    }
+}
+
+/// Quoted where needed; a value a spreadsheet would run as a formula (a
+/// file named `=HYPERLINK(...)`) gets a leading `'` so it stays text
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// `Operation` and `Via` as they're spelled in the JSON
+fn label<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+fn write_csv(out: &mut impl Write, entries: impl Iterator<Item = ActivityEntry>) -> std::io::Result<()> {
+    writeln!(out, "{}", CSV_HEADER)?;
+    for e in entries {
+        let timestamp = Local
+            .timestamp_millis_opt(e.timestamp as i64)
+            .single()
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_default();
+        let row = [
+            timestamp,
+            e.user.clone(),
+            label(&e.operation),
+            label(&e.via),
+            e.source.clone().unwrap_or_default(),
+            e.destination.clone().unwrap_or_default(),
+            e.bytes.map(|b| b.to_string()).unwrap_or_default(),
+            if e.success { "ok" } else { "failed" }.to_string(),
+            e.error.clone().unwrap_or_default(),
+        ];
+        let fields: Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+        writeln!(out, "{}", fields.join(","))?;
+    }
+    Ok(())
+}
+
+impl ActivityEntry {
+    pub fn new(operation: Operation, via: Via) -> Self {
+        ActivityEntry {
+            timestamp: now_ms(),
+            user: current_user(),
+            operation,
+            via,
+            source: None,
+            destination: None,
+            bytes: None,
+            success: false,
+            error: None,
+        }
+    }
+
+    pub fn from(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    pub fn to(mut self, destination: impl Into<String>) -> Self {
+        self.destination = Some(destination.into());
+        self
+    }
+
+    pub fn bytes(mut self, bytes: u64) -> Self {
+        self.bytes = Some(bytes);
+        self
+    }
+
+    fn matches(&self, query: &ActivityQuery) -> bool {
+        query.since.is_none_or(|since| self.timestamp >= since)
+            && query.until.is_none_or(|until| self.timestamp < until)
+            && (query.operations.is_empty() || query.operations.contains(&self.operation))
+            && (!query.failed_only || !self.success)
+            && query.path_prefix.as_deref().is_none_or(|prefix| {
+                [&self.source, &self.destination]
+                    .into_iter()
+                    .flatten()
+                    .any(|path| is_under(path, prefix))
+            })
+    }
+}
+
+impl ActivityLog {
+    pub fn from_app<R: Runtime>(app_handle: &tauri::AppHandle<R>) -> Result<Self, String> {
+        let dir = app_handle.path().app_data_dir().map_err(|e| e.to_string())?;
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        Ok(Self::at(dir.join(LOG_FILE)))
+    }
+
+    /// For the headless hot folder service, which has no app handle
+    pub fn at(path: PathBuf) -> Self {
+        ActivityLog { path }
+    }
+
+    /// Appends one line. The operation already happened, so a log that
+    /// can't be written is reported but doesn't fail it.
+    pub fn append(&self, entry: &ActivityEntry) {
+        let Ok(mut line) = serde_json::to_string(entry) else { return };
+        line.push('\n');
+        // One write per line keeps lines from concurrent commands whole
+        let written = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()));
+        if let Err(e) = written {
+            eprintln!("activity log {}: {}", self.path.display(), e);
+        }
+    }
+
+    /// Logs `entry` with the outcome of `result` and hands `result` back
+    pub fn record<T, E: Display>(&self, mut entry: ActivityEntry, result: Result<T, E>) -> Result<T, E> {
+        entry.success = result.is_ok();
+        entry.error = result.as_ref().err().map(|e| e.to_string());
+        self.append(&entry);
+        result
+    }
+
+    /// Matching entries, newest first
+    fn query(&self, query: &ActivityQuery, default_limit: usize) -> Vec<ActivityEntry> {
+        // Only the newest matches are kept while reading, not the whole log
+        let limit = query.limit.unwrap_or(default_limit);
+        if limit == 0 {
+            return Vec::new();
+        }
+        let mut newest = VecDeque::new();
+        for entry in read_entries(&self.path).filter(|e| e.matches(query)) {
+            if newest.len() == limit {
+                newest.pop_front();
+            }
+            newest.push_back(entry);
+        }
+        newest.into_iter().rev().collect()
+    }
+}
+
+/// `ActivityLog::record` on the app's log
+pub fn record<R: Runtime, T, E: Display>(
+    app_handle: &tauri::AppHandle<R>,
+    entry: ActivityEntry,
+    result: Result<T, E>,
+) -> Result<T, E> {
+    match ActivityLog::from_app(app_handle) {
+        Ok(log) => log.record(entry, result),
+        Err(e) => {
+            eprintln!("activity log: {}", e);
+            result
+        }
+    }
+}
+
+/// The entries oldest first, read a line at a time
+fn read_entries(path: &Path) -> impl Iterator<Item = ActivityEntry> {
+    let lines = fs::File::open(path).map(|f| BufReader::new(f).split(b'\n'));
+    // A line cut short by a crash is skipped, not fatal
+    lines
+        .into_iter()
+        .flatten()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_slice(&line).ok())
+}
+
+// --- Commands ---
+
+#[tauri::command]
+pub async fn query_activity<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    query: Option<ActivityQuery>,
+) -> Result<Vec<ActivityEntry>, String> {
+    let log = ActivityLog::from_app(&app_handle)?;
+    spawn_blocking(move || log.query(&query.unwrap_or_default(), DEFAULT_LIMIT))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Ask where to save and write the matching entries there as CSV, oldest
+/// first. Returns the file written, or `None` when the dialog was cancelled.
+#[tauri::command]
+pub async fn export_activity<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    query: Option<ActivityQuery>,
+) -> Result<Option<String>, String> {
+    let log = ActivityLog::from_app(&app_handle)?;
+    spawn_blocking(move || {
+        let file_name = format!("filecanvas-activity-{}.csv", Local::now().format("%Y-%m-%d"));
+        let picked = app_handle
+            .dialog()
+            .file()
+            .add_filter("CSV", &["csv"])
+            .set_file_name(file_name)
+            .blocking_save_file();
+        let Some(target) = picked.and_then(|p| p.into_path().ok()) else {
+            return Ok(None);
+        };
+
+        // Rows go out as the log is read; only a limit needs the newest held back
+        let query = query.unwrap_or_default();
+        let write = || -> std::io::Result<()> {
+            let mut out = BufWriter::new(fs::File::create(&target)?);
+            match query.limit {
+                Some(_) => {
+                    let mut entries = log.query(&query, usize::MAX);
+                    entries.reverse();
+                    write_csv(&mut out, entries.into_iter())?;
+                }
+                None => write_csv(&mut out, read_entries(&log.path).filter(|e| e.matches(&query)))?,
+            }
+            out.flush()
+        };
+        write().map_err(|e| format!("{}: {}", target.display(), e))?;
+        Ok(Some(target.to_string_lossy().to_string()))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,208 @@
+// src-tauri/src/app_usage.rs
+//
+// Remembers which apps files are opened with, per file type, so the Open With
+// dialog can offer a "Recent" group, and stores the optional "always use this
+// app for this type" choice that `open_file` honours.
+//
+// A file type is its MIME type, or `*.ext` when the type is unknown.
+// Apps are ranked by frecency: each of the last few uses is weighted by its
+// age (as in Firefox's history ranking) and scaled by the total use count.
+// Stored in `app_usage.json` in the app data dir.
+
+use crate::store;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{Manager, Runtime};
+
+const USAGE_FILE: &str = "app_usage.json";
+/// Timestamps kept per app for the recency part of the score
+const MAX_VISITS: usize = 10;
+/// Apps shown in the recent group
+pub const MAX_RECENT: usize = 5;
+/// Apps remembered per type; the lowest ranked are forgotten first
+const MAX_APPS_PER_TYPE: usize = 20;
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+// --- Data Structures ---
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppUse {
+    pub app_id: String,
+    /// Exec line last used, so `open_file` can launch without listing apps
+    pub exec: String,
+    pub count: u32,
+    /// Milliseconds since the epoch of the most recent uses, newest last
+    pub visits: Vec<u64>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TypeUsage {
+    pub apps: Vec<AppUse>,
+    /// `app_id` to open this type with from `open_file`
+    pub always: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct UsageStore {
+    types: HashMap<String, TypeUsage>,
+}
+
+// --- Helpers ---
+
+fn store_path<R: Runtime>(app_handle: &tauri::AppHandle<R>) -> Result<PathBuf, String> {
+    let dir = app_handle.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(USAGE_FILE))
+}
+
+fn load_store(path: &Path) -> UsageStore {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// The key usage is stored under: the MIME type, or `*.ext` when unknown
+pub fn type_key(file_path: &str) -> String {
+    let path = Path::new(file_path);
+    let mime = crate::mime::detect(path);
+    if mime != crate::mime::OCTET_STREAM {
+        return mime;
+    }
+    match path.extension() {
+        Some(ext) => format!("*.{}", ext.to_string_lossy().to_lowercase()),
+        None => mime,
+    }
+}
+
+fn visit_weight(age_days: u64) -> f64 {
+    match age_days {
+        d if d < 4 => 100.0,
+        d if d < 14 => 70.0,
+        d if d < 31 => 50.0,
+        d if d < 90 => 30.0,
+        _ => 10.0,
+    }
+}
+
+impl AppUse {
+    pub fn frecency(&self, now: u64) -> f64 {
+        if self.visits.is_empty() {
+            return 0.0;
+        }
+        let recency: f64 = self
+            .visits
+            .iter()
+            .map(|t| visit_weight(now.saturating_sub(*t) / DAY_MS))
+            .sum();
+        self.count as f64 * recency / self.visits.len() as f64
+    }
+}
+
+/// Usage for the type of `file_path`, apps ranked best first
+pub fn for_file<R: Runtime>(app_handle: &tauri::AppHandle<R>, file_path: &str) -> Result<TypeUsage, String> {
+    let mut store = load_store(&store_path(app_handle)?);
+    let mut usage = store.types.remove(&type_key(file_path)).unwrap_or_default();
+    let now = store::now_ms();
+    usage
+        .apps
+        .sort_by(|a, b| b.frecency(now).total_cmp(&a.frecency(now)));
+    Ok(usage)
+}
+
+/// Note that `app_id` was just used to open `file_path`
+pub fn record<R: Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    file_path: &str,
+    app_id: &str,
+    exec: &str,
+) -> Result<(), String> {
+    let path = store_path(app_handle)?;
+    let mut store = load_store(&path);
+    let usage = store.types.entry(type_key(file_path)).or_default();
+    let now = store::now_ms();
+
+    match usage.apps.iter_mut().find(|a| a.app_id == app_id) {
+        Some(app) => {
+            app.count += 1;
+            app.exec = exec.to_string();
+            app.visits.push(now);
+            if app.visits.len() > MAX_VISITS {
+                app.visits.remove(0);
+            }
+        }
+        None => usage.apps.push(AppUse {
+            app_id: app_id.to_string(),
+            exec: exec.to_string(),
+            count: 1,
+            visits: vec![now],
+        }),
+    }
+
+    if usage.apps.len() > MAX_APPS_PER_TYPE {
+        usage
+            .apps
+            .sort_by(|a, b| b.frecency(now).total_cmp(&a.frecency(now)));
+        let always = usage.always.clone();
+        let mut kept = 0;
+        // Never forget the app picked as "always use"
+        usage.apps.retain(|a| {
+            kept += 1;
+            kept <= MAX_APPS_PER_TYPE || always.as_deref() == Some(a.app_id.as_str())
+        });
+    }
+    store::save_json_atomic(&path, &store)
+}
+
+/// The app (id, exec) to use for `file_path` instead of the system default
+pub fn always_app<R: Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    file_path: &str,
+) -> Result<Option<(String, String)>, String> {
+    let usage = for_file(app_handle, file_path)?;
+    let Some(id) = usage.always else {
+        return Ok(None);
+    };
+    Ok(usage
+        .apps
+        .into_iter()
+        .find(|a| a.app_id == id)
+        .map(|a| (a.app_id, a.exec)))
+}
+
+// --- Commands ---
+
+/// Open files of this type with `app_id` from `open_file`, or clear the choice
+/// with `None`. Returns the file type the choice applies to.
+#[tauri::command]
+pub fn set_always_use_app<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    file_path: String,
+    app_id: Option<String>,
+    exec: Option<String>,
+) -> Result<String, String> {
+    let path = store_path(&app_handle)?;
+    let mut store = load_store(&path);
+    let key = type_key(&file_path);
+    let usage = store.types.entry(key.clone()).or_default();
+
+    if let Some(id) = &app_id {
+        let exec = exec.ok_or("exec is required")?;
+        match usage.apps.iter_mut().find(|a| &a.app_id == id) {
+            Some(app) => app.exec = exec,
+            None => usage.apps.push(AppUse {
+                app_id: id.clone(),
+                exec,
+                count: 0,
+                visits: Vec::new(),
+            }),
+        }
+    }
+    usage.always = app_id;
+    store::save_json_atomic(&path, &store)?;
+    Ok(key)
+}
@@ -0,0 +1,550 @@
+// src-tauri/src/connections.rs
+//
+// Checks the connections drawn on the canvas before a copy or move runs.
+// Each connection puts one item (`from`) into a folder (`to`). Together they
+// form a graph: a connection that fills folder B has to run before the one
+// that moves B elsewhere, a move out of A/x has to run before A itself moves,
+// and some combinations can't work in any order (A into B and B into A).
+//
+// `validate_connections` reports errors (the run should not start) and
+// warnings (it can, but something will be skipped or renamed), and returns
+// the order the remaining connections are safe to run in. It also adds up
+// what the run writes to each local drive, so one that won't fit is stopped
+// before the first file is copied.
+
+use crate::filters::{self, FilterRules, TransferMode};
+use crate::ops;
+use crate::remote::{self, Location};
+use filecanvas_lib::space::{self, human_bytes};
+use filecanvas_lib::{DiskFs, FileSystem};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+use tauri::async_runtime::spawn_blocking;
+use tauri::Runtime;
+
+// --- Data Structures ---
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Connection {
+    pub id: u64,
+    pub from: String,
+    pub to: String,
+    /// Only what passes these is transferred (and counted for space)
+    #[serde(default)]
+    pub filters: Option<FilterRules>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    MissingSource,
+    MissingDestination,
+    NotAFolder,
+    /// The destination is the item itself or inside it
+    SelfNesting,
+    Cycle,
+    /// One item moved to several places
+    ConflictingMoves,
+    Duplicate,
+    /// Moving an item into the folder it's already in
+    AlreadyThere,
+    /// Two items with the same name end up in the same folder
+    SameName,
+    /// More to write to a drive than it has free
+    NoSpace,
+    /// It fits, but leaves the drive almost full
+    LowSpace,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Issue {
+    pub kind: IssueKind,
+    /// Connection ids involved
+    pub connections: Vec<u64>,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ConnectionCheck {
+    pub errors: Vec<Issue>,
+    pub warnings: Vec<Issue>,
+    /// Connection ids in a safe order; connections left out have an issue
+    pub order: Vec<u64>,
+}
+
+/// What's at a path before anything runs. An existing item comes with its
+/// real path, links and `..` resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Probe {
+    Folder(PathBuf),
+    File(PathBuf),
+    /// Doesn't exist or can't be used; why
+    Missing(String),
+}
+
+/// A connection with its paths resolved
+struct Edge {
+    id: u64,
+    from: PathBuf,
+    to: PathBuf,
+    /// Where the item ends up: `to/<name of from>`
+    result: PathBuf,
+    from_probe: Probe,
+    to_probe: Probe,
+}
+
+// --- Helpers ---
+
+/// `path` is `folder` or inside it. Compares components, so `/a/bc` is not
+/// inside `/a/b`; URLs work the same way (`sftp:`, `user@host`, …).
+fn within(path: &Path, folder: &Path) -> bool {
+    path.starts_with(folder)
+}
+
+/// `.` and `..` worked out without asking the disk, for paths that don't
+/// exist yet. Paths without them are kept as written.
+fn normalize(path: &Path) -> PathBuf {
+    let dotted = path.components().any(|c| matches!(c, Component::CurDir | Component::ParentDir));
+    if !dotted {
+        return path.to_path_buf();
+    }
+    let mut normal = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normal.pop();
+            }
+            other => normal.push(other),
+        }
+    }
+    normal
+}
+
+/// Where `path` really is: the probe's answer when it exists, otherwise the
+/// path with `.` and `..` worked out
+fn resolve(path: &str, probe: &impl Fn(&str) -> Probe) -> (PathBuf, Probe) {
+    let probed = probe(path);
+    let resolved = match &probed {
+        Probe::Folder(real) | Probe::File(real) => real.clone(),
+        Probe::Missing(_) => normalize(Path::new(path.trim_end_matches('/'))),
+    };
+    (resolved, probed)
+}
+
+fn issue(kind: IssueKind, connections: &[u64], message: String) -> Issue {
+    Issue {
+        kind,
+        connections: connections.to_vec(),
+        message,
+    }
+}
+
+/// `i` has to run before `j`
+fn runs_before(i: &Edge, j: &Edge, mode: TransferMode) -> bool {
+    // Fill a folder before it's taken elsewhere
+    within(&i.to, &j.from)
+        // The item `i` brings is what `j` takes, or where `j` puts things
+        || within(&j.from, &i.result)
+        || within(&j.to, &i.result)
+        // Something inside is moved out before its parent moves
+        || (mode == TransferMode::Move && i.from != j.from && within(&i.from, &j.from))
+}
+
+/// Kahn's algorithm, taking the earliest drawn connection whenever there's a
+/// choice. Returns the order and the connections stuck in cycles.
+fn order_edges(edges: &[&Edge], mode: TransferMode) -> (Vec<usize>, Vec<usize>) {
+    let n = edges.len();
+    let mut incoming = vec![0usize; n];
+    let mut outgoing: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for i in 0..n {
+        for j in 0..n {
+            if i != j && runs_before(edges[i], edges[j], mode) {
+                outgoing[i].push(j);
+                incoming[j] += 1;
+            }
+        }
+    }
+
+    let mut ready: BTreeSet<usize> = (0..n).filter(|&i| incoming[i] == 0).collect();
+    let mut order = Vec::with_capacity(n);
+    while let Some(i) = ready.pop_first() {
+        order.push(i);
+        for &j in &outgoing[i] {
+            incoming[j] -= 1;
+            if incoming[j] == 0 {
+                ready.insert(j);
+            }
+        }
+    }
+    let stuck = (0..n).filter(|&i| incoming[i] > 0).collect();
+    (order, stuck)
+}
+
+/// Checks `connections` with `probe` telling what exists. Kept apart from the
+/// command so the rules don't depend on the disk.
+pub fn validate(connections: &[Connection], mode: TransferMode, probe: impl Fn(&str) -> Probe) -> ConnectionCheck {
+    let mut check = ConnectionCheck::default();
+    let mut edges: Vec<Edge> = Vec::new();
+
+    for c in connections {
+        // Compared as resolved, so neither `..` nor a link hides that the
+        // destination is inside the item
+        let (from, from_probe) = resolve(&c.from, &probe);
+        let (to, to_probe) = resolve(&c.to, &probe);
+        let Some(name) = from.file_name() else {
+            check.errors.push(issue(
+                IssueKind::SelfNesting,
+                &[c.id],
+                format!("{} is a root and can't be put into another folder", c.from),
+            ));
+            continue;
+        };
+        let result = to.join(name);
+
+        if within(&to, &from) {
+            check.errors.push(issue(
+                IssueKind::SelfNesting,
+                &[c.id],
+                format!("{} can't go into {}, which is itself or inside it", c.from, c.to),
+            ));
+            continue;
+        }
+        if mode == TransferMode::Move && from.parent() == Some(to.as_path()) {
+            check.warnings.push(issue(
+                IssueKind::AlreadyThere,
+                &[c.id],
+                format!("{} is already in {}; skipped", c.from, c.to),
+            ));
+            continue;
+        }
+        if let Some(first) = edges.iter().find(|e| e.from == from && e.to == to) {
+            check.warnings.push(issue(
+                IssueKind::Duplicate,
+                &[first.id, c.id],
+                format!("{} → {} is drawn more than once; it runs once", c.from, c.to),
+            ));
+            continue;
+        }
+        edges.push(Edge {
+            id: c.id,
+            from,
+            to,
+            result,
+            from_probe,
+            to_probe,
+        });
+    }
+
+    // The same item can be copied to several folders, but moved only to one
+    let mut conflicted: BTreeSet<u64> = BTreeSet::new();
+    if mode == TransferMode::Move {
+        for e in &edges {
+            if conflicted.contains(&e.id) {
+                continue;
+            }
+            let ids: Vec<u64> = edges.iter().filter(|o| o.from == e.from).map(|o| o.id).collect();
+            if ids.len() > 1 {
+                let targets: Vec<String> = edges
+                    .iter()
+                    .filter(|o| o.from == e.from)
+                    .map(|o| o.to.to_string_lossy().to_string())
+                    .collect();
+                check.errors.push(issue(
+                    IssueKind::ConflictingMoves,
+                    &ids,
+                    format!("{} is moved to {} places: {}", e.from.display(), ids.len(), targets.join(", ")),
+                ));
+                conflicted.extend(ids);
+            }
+        }
+    }
+    edges.retain(|e| !conflicted.contains(&e.id));
+
+    for (i, e) in edges.iter().enumerate() {
+        let others: Vec<u64> = edges[i + 1..].iter().filter(|o| o.result == e.result).map(|o| o.id).collect();
+        if !others.is_empty() {
+            let ids: Vec<u64> = std::iter::once(e.id).chain(others).collect();
+            check.warnings.push(issue(
+                IssueKind::SameName,
+                &ids,
+                format!("{} items would be named {}", ids.len(), e.result.display()),
+            ));
+        }
+    }
+
+    // Paths that only appear once an earlier connection ran aren't missing
+    let produced = |path: &Path, own: u64| edges.iter().any(|o| o.id != own && within(path, &o.result));
+    let mut unusable: BTreeSet<u64> = BTreeSet::new();
+    for e in &edges {
+        if !produced(&e.from, e.id) {
+            if let Probe::Missing(why) = &e.from_probe {
+                check.errors.push(issue(
+                    IssueKind::MissingSource,
+                    &[e.id],
+                    format!("{}: {}", e.from.display(), why),
+                ));
+                unusable.insert(e.id);
+            }
+        }
+        if !produced(&e.to, e.id) {
+            match &e.to_probe {
+                Probe::Folder(_) => {}
+                Probe::File(_) => {
+                    check.errors.push(issue(
+                        IssueKind::NotAFolder,
+                        &[e.id],
+                        format!("{} is a file, not a folder", e.to.display()),
+                    ));
+                    unusable.insert(e.id);
+                }
+                Probe::Missing(why) => {
+                    check.errors.push(issue(
+                        IssueKind::MissingDestination,
+                        &[e.id],
+                        format!("{}: {}", e.to.display(), why),
+                    ));
+                    unusable.insert(e.id);
+                }
+            }
+        }
+    }
+
+    let runnable: Vec<&Edge> = edges.iter().filter(|e| !unusable.contains(&e.id)).collect();
+    let (order, stuck) = order_edges(&runnable, mode);
+    check.order = order.iter().map(|&i| runnable[i].id).collect();
+    if !stuck.is_empty() {
+        let ids: Vec<u64> = stuck.iter().map(|&i| runnable[i].id).collect();
+        let paths: Vec<String> = stuck
+            .iter()
+            .map(|&i| format!("{} → {}", runnable[i].from.display(), runnable[i].to.display()))
+            .collect();
+        let message = format!("These connections depend on each other: {}", paths.join(", "));
+        match mode {
+            // Each copy reads what's there when it runs, so drawing order decides
+            TransferMode::Copy => {
+                check.warnings.push(issue(IssueKind::Cycle, &ids, format!("{}; they run in the order drawn", message)));
+                check.order.extend(&ids);
+            }
+            TransferMode::Move => check.errors.push(issue(IssueKind::Cycle, &ids, message)),
+        }
+    }
+
+    check
+}
+
+/// Bytes connection `c` writes to its destination drive, if both ends are
+/// local and there now
+fn bytes_written<R: Runtime>(app_handle: &tauri::AppHandle<R>, c: &Connection, mode: TransferMode) -> Option<(PathBuf, u64)> {
+    let ends = (remote::existing(app_handle, &c.from), remote::existing(app_handle, &c.to));
+    let (Ok(Location::Local(from)), Ok(Location::Local(to))) = ends else { return None };
+    let size = match c.filters.as_ref().filter(|rules| !rules.is_empty()) {
+        Some(rules) => filters::planned_bytes(&Location::Local(from.clone()), rules).ok()?,
+        None => ops::item_size(&DiskFs, &from, &DiskFs.metadata(&from).ok()?),
+    };
+    let needed = space::bytes_needed(&from, &to, size, mode == TransferMode::Move);
+    Some((to, needed))
+}
+
+/// Adds `no_space` errors and `low_space` warnings for the drives the
+/// runnable connections write to, with all of them counted together
+fn check_space<R: Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    connections: &[Connection],
+    mode: TransferMode,
+    check: &mut ConnectionCheck,
+) {
+    // Drive → (a destination on it, bytes, connection ids)
+    let mut drives: BTreeMap<u64, (PathBuf, u64, Vec<u64>)> = BTreeMap::new();
+    for c in check.order.iter().filter_map(|id| connections.iter().find(|c| c.id == *id)) {
+        let Some((to, needed)) = bytes_written(app_handle, c, mode) else { continue };
+        let Some(drive) = space::volume_id(&to).filter(|_| needed > 0) else { continue };
+        let (_, total, ids) = drives.entry(drive).or_insert_with(|| (to, 0, Vec::new()));
+        *total += needed;
+        ids.push(c.id);
+    }
+
+    for (folder, needed, ids) in drives.into_values() {
+        let Some(space) = space::check(&folder, needed) else { continue };
+        if !space.enough {
+            check.errors.push(issue(
+                IssueKind::NoSpace,
+                &ids,
+                format!(
+                    "{} needed on the drive of {}, only {} free",
+                    human_bytes(needed),
+                    folder.display(),
+                    human_bytes(space.free)
+                ),
+            ));
+        } else if space.low {
+            check.warnings.push(issue(
+                IssueKind::LowSpace,
+                &ids,
+                format!(
+                    "The drive of {} will have only {} of {} left",
+                    folder.display(),
+                    human_bytes(space.free - needed),
+                    human_bytes(space.total)
+                ),
+            ));
+        }
+    }
+}
+
+// --- Commands ---
+
+/// Check the canvas connections for a copy or move run (see `validate`)
+#[tauri::command]
+pub async fn validate_connections<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    connections: Vec<Connection>,
+    mode: TransferMode,
+) -> Result<ConnectionCheck, String> {
+    spawn_blocking(move || {
+        let probe = |path: &str| match remote::existing(&app_handle, path) {
+            Ok(item) => match item.fs().metadata(item.path()) {
+                Ok(metadata) if metadata.is_dir => Probe::Folder(PathBuf::from(item.url(item.path()))),
+                Ok(_) => Probe::File(PathBuf::from(item.url(item.path()))),
+                Err(e) => Probe::Missing(e.message),
+            },
+            Err(e) => Probe::Missing(e.message),
+        };
+        let mut check = validate(&connections, mode, probe);
+        check_space(&app_handle, &connections, mode, &mut check);
+        check
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A disk with these folders and files, where `links` point a path at
+    /// another one, like the real probe after canonicalizing
+    fn fake_probe<'a>(
+        folders: &'a [&'a str],
+        files: &'a [&'a str],
+        links: &'a [(&'a str, &'a str)],
+    ) -> impl Fn(&str) -> Probe + 'a {
+        move |path: &str| {
+            let mut real = normalize(Path::new(path.trim_end_matches('/')));
+            for (link, target) in links {
+                if let Ok(rest) = real.strip_prefix(link) {
+                    real = Path::new(target).components().chain(rest.components()).collect();
+                }
+            }
+            let real_str = real.to_string_lossy();
+            if folders.contains(&real_str.as_ref()) {
+                Probe::Folder(real)
+            } else if files.contains(&real_str.as_ref()) {
+                Probe::File(real)
+            } else {
+                Probe::Missing("not found".into())
+            }
+        }
+    }
+
+    fn connection(id: u64, from: &str, to: &str) -> Connection {
+        Connection {
+            id,
+            from: from.into(),
+            to: to.into(),
+            filters: None,
+        }
+    }
+
+    fn kinds(issues: &[Issue]) -> Vec<IssueKind> {
+        issues.iter().map(|i| i.kind).collect()
+    }
+
+    const FOLDERS: &[&str] = &["/", "/a", "/a/b", "/b", "/c", "/d", "/x"];
+    const FILES: &[&str] = &["/a/f.txt"];
+
+    #[test]
+    fn refuses_putting_a_folder_into_itself() {
+        let probe = fake_probe(FOLDERS, FILES, &[("/link", "/a")]);
+        for to in ["/a", "/a/b", "/a/b/", "/x/../a/b", "/link/b", "/link"] {
+            let check = validate(&[connection(1, "/a", to)], TransferMode::Copy, &probe);
+            assert_eq!(kinds(&check.errors), [IssueKind::SelfNesting], "into {}", to);
+            assert!(check.order.is_empty(), "into {}", to);
+        }
+        let check = validate(&[connection(1, "/", "/a")], TransferMode::Copy, &probe);
+        assert_eq!(kinds(&check.errors), [IssueKind::SelfNesting]);
+
+        // Only a path boundary counts: /a/bc is not inside /a/b
+        let probe = fake_probe(&["/a/b", "/a/bc"], &[], &[]);
+        let check = validate(&[connection(1, "/a/b", "/a/bc")], TransferMode::Copy, probe);
+        assert!(check.errors.is_empty());
+    }
+
+    #[test]
+    fn orders_chained_moves() {
+        // Drawn last to first: /c goes into /d, /b into /c, a file into /b
+        let connections = [connection(3, "/c", "/d"), connection(2, "/b", "/c"), connection(1, "/a/f.txt", "/b")];
+        let check = validate(&connections, TransferMode::Move, fake_probe(FOLDERS, FILES, &[]));
+        assert!(check.errors.is_empty(), "{:?}", check.errors);
+        assert_eq!(check.order, [1, 2, 3]);
+    }
+
+    #[test]
+    fn moves_out_of_a_folder_run_before_it_moves() {
+        let connections = [connection(1, "/a", "/c"), connection(2, "/a/b", "/d")];
+        let check = validate(&connections, TransferMode::Move, fake_probe(FOLDERS, FILES, &[]));
+        assert_eq!(check.order, [2, 1]);
+    }
+
+    #[test]
+    fn an_item_moves_to_one_place_only() {
+        let connections = [connection(1, "/a/f.txt", "/b"), connection(2, "/a/f.txt", "/c"), connection(3, "/x", "/d")];
+        let check = validate(&connections, TransferMode::Move, fake_probe(FOLDERS, FILES, &[]));
+        assert_eq!(kinds(&check.errors), [IssueKind::ConflictingMoves]);
+        assert_eq!(check.errors[0].connections, [1, 2]);
+        assert_eq!(check.order, [3]);
+
+        // Copies to several folders are fine
+        let check = validate(&connections, TransferMode::Copy, fake_probe(FOLDERS, FILES, &[]));
+        assert!(check.errors.is_empty());
+        assert_eq!(check.order, [1, 2, 3]);
+    }
+
+    #[test]
+    fn duplicates_run_once() {
+        let connections = [connection(1, "/a", "/b"), connection(2, "/a/", "/x/../b"), connection(3, "/c", "/d")];
+        let check = validate(&connections, TransferMode::Copy, fake_probe(FOLDERS, FILES, &[]));
+        assert_eq!(kinds(&check.warnings), [IssueKind::Duplicate]);
+        assert_eq!(check.warnings[0].connections, [1, 2]);
+        assert_eq!(check.order, [1, 3]);
+    }
+
+    #[test]
+    fn cycles_stop_a_move_but_not_a_copy() {
+        let connections = [connection(1, "/b", "/c"), connection(2, "/c", "/b")];
+        let check = validate(&connections, TransferMode::Move, fake_probe(FOLDERS, FILES, &[]));
+        assert_eq!(kinds(&check.errors), [IssueKind::Cycle]);
+        assert_eq!(check.errors[0].connections, [1, 2]);
+        assert!(check.order.is_empty());
+
+        let check = validate(&connections, TransferMode::Copy, fake_probe(FOLDERS, FILES, &[]));
+        assert!(check.errors.is_empty());
+        assert_eq!(kinds(&check.warnings), [IssueKind::Cycle]);
+        assert_eq!(check.order, [1, 2]);
+    }
+
+    #[test]
+    fn missing_ends_are_errors_unless_an_earlier_connection_makes_them() {
+        let connections = [connection(1, "/a/f.txt", "/nowhere"), connection(2, "/gone", "/b"), connection(3, "/a/f.txt", "/d")];
+        let check = validate(&connections, TransferMode::Copy, fake_probe(FOLDERS, FILES, &[]));
+        assert_eq!(kinds(&check.errors), [IssueKind::MissingDestination, IssueKind::MissingSource]);
+        assert_eq!(check.order, [3]);
+
+        // /b/a only exists once /a has been copied into /b
+        let connections = [connection(1, "/a", "/b"), connection(2, "/b/a", "/c")];
+        let check = validate(&connections, TransferMode::Copy, fake_probe(FOLDERS, FILES, &[]));
+        assert!(check.errors.is_empty(), "{:?}", check.errors);
+        assert_eq!(check.order, [1, 2]);
+    }
+}
@@ -0,0 +1,244 @@
+// src-tauri/src/custom_apps.rs
+//
+// User-defined "Open with" commands for scripts that have no desktop entry.
+// Stored in `custom_apps.json` in the app config dir.
+//
+// Command templates are split like a shell would split words (quotes and
+// backslashes), then placeholders are filled in per argument, so a path
+// with spaces stays a single argument. Nothing runs through a shell.
+//   {path}  full path          {dir}  parent folder
+//   {name}  file name          {stem} name without extension
+//   {ext}   extension
+// A template without {path} gets the path appended.
+
+use crate::launcher::{self, LaunchError, Launched};
+use crate::store;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::{Manager, Runtime};
+
+const CUSTOM_APPS_FILE: &str = "custom_apps.json";
+
+/// Prefix that marks custom entries in `OpenWithApp.id`
+pub const ID_PREFIX: &str = "custom:";
+
+// --- Data Structures ---
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomApp {
+    /// Generated on first save when left empty
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub command: String,
+    /// Extensions ("pdf") or MIME types ("application/pdf", "image/*").
+    /// Empty means the command is offered for every file.
+    #[serde(default)]
+    pub file_types: Vec<String>,
+    #[serde(default)]
+    pub terminal: bool,
+}
+
+// --- Helpers ---
+
+fn store_path<R: Runtime>(app_handle: &tauri::AppHandle<R>) -> Result<PathBuf, String> {
+    let dir = app_handle.path().app_config_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(CUSTOM_APPS_FILE))
+}
+
+fn load_apps(path: &Path) -> Vec<CustomApp> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+pub fn load<R: Runtime>(app_handle: &tauri::AppHandle<R>) -> Result<Vec<CustomApp>, String> {
+    Ok(load_apps(&store_path(app_handle)?))
+}
+
+/// Split a command line into words: whitespace separates, '…' is literal,
+/// "…" allows \" and \\, and a backslash outside quotes escapes one char
+fn split_words(command: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current: Option<String> = None;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if let Some(word) = current.take() {
+                    words.push(word);
+                }
+            }
+            '\'' => {
+                let word = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(ch) => word.push(ch),
+                        None => return Err("Unterminated ' in command".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                let word = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(ch @ ('"' | '\\')) => word.push(ch),
+                            Some(ch) => {
+                                word.push('\\');
+                                word.push(ch);
+                            }
+                            None => return Err("Unterminated \" in command".to_string()),
+                        },
+                        Some(ch) => word.push(ch),
+                        None => return Err("Unterminated \" in command".to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                if let Some(ch) = chars.next() {
+                    current.get_or_insert_with(String::new).push(ch);
+                }
+            }
+            ch => current.get_or_insert_with(String::new).push(ch),
+        }
+    }
+    if let Some(word) = current {
+        words.push(word);
+    }
+    if words.is_empty() {
+        return Err("Empty command".to_string());
+    }
+    Ok(words)
+}
+
+/// Build the argv for running `app` on `file_path`
+pub fn expand(app: &CustomApp, file_path: &str) -> Result<Vec<String>, String> {
+    let path = Path::new(file_path);
+    let part = |s: Option<&std::ffi::OsStr>| s.map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let dir = path.parent().map(|p| p.display().to_string()).unwrap_or_default();
+    let name = part(path.file_name());
+    let stem = part(path.file_stem());
+    let ext = part(path.extension());
+
+    let mut words = split_words(&app.command)?;
+    let has_path = words.iter().any(|w| w.contains("{path}"));
+    for word in words.iter_mut() {
+        *word = word
+            .replace("{path}", file_path)
+            .replace("{dir}", &dir)
+            .replace("{name}", &name)
+            .replace("{stem}", &stem)
+            .replace("{ext}", &ext);
+    }
+    if !has_path {
+        words.push(file_path.to_string());
+    }
+    Ok(words)
+}
+
+/// How `app` relates to a file: None when it isn't offered, Some(true) when
+/// one of its file types matched, Some(false) when it applies to every file
+pub fn match_file(app: &CustomApp, file_path: &str, file_mimes: &[String]) -> Option<bool> {
+    if app.file_types.is_empty() {
+        return Some(false);
+    }
+    let ext = Path::new(file_path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let media = file_mimes.first().and_then(|m| m.split('/').next()).unwrap_or("");
+
+    app.file_types
+        .iter()
+        .any(|t| {
+            let t = t.trim().to_lowercase();
+            if let Some(wildcard) = t.strip_suffix("/*") {
+                wildcard == media
+            } else if t.contains('/') {
+                file_mimes.contains(&crate::mime::canonical(&t))
+            } else {
+                !ext.is_empty() && t.trim_start_matches('.') == ext
+            }
+        })
+        .then_some(true)
+}
+
+pub fn find<R: Runtime>(app_handle: &tauri::AppHandle<R>, id: &str) -> Result<CustomApp, String> {
+    load(app_handle)?
+        .into_iter()
+        .find(|a| a.id == id)
+        .ok_or_else(|| format!("No custom command with id {}", id))
+}
+
+/// Start `app` on `file_path`, from the file's folder
+pub fn launch<R: Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    app: &CustomApp,
+    file_path: &str,
+) -> Result<Launched, LaunchError> {
+    let mut argv = expand(app, file_path)?;
+    if app.terminal {
+        argv = crate::terminal::wrap(app_handle, argv)?;
+    }
+    let mut cmd = Command::new(&argv[0]);
+    cmd.args(&argv[1..]);
+    if let Some(dir) = Path::new(file_path).parent().filter(|d| d.is_dir()) {
+        cmd.current_dir(dir);
+    }
+    launcher::spawn(cmd)
+}
+
+// --- Commands ---
+
+#[tauri::command]
+pub fn list_custom_apps<R: Runtime>(app_handle: tauri::AppHandle<R>) -> Result<Vec<CustomApp>, String> {
+    load(&app_handle)
+}
+
+/// Create (empty id) or update a custom command. Returns the saved entry.
+#[tauri::command]
+pub fn save_custom_app<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    app: CustomApp,
+) -> Result<CustomApp, String> {
+    let mut app = app;
+    app.name = app.name.trim().to_string();
+    if app.name.is_empty() {
+        return Err("Name is required".to_string());
+    }
+    split_words(&app.command)?;
+    app.file_types.retain(|t| !t.trim().is_empty());
+
+    let path = store_path(&app_handle)?;
+    let mut apps = load_apps(&path);
+    if app.id.is_empty() {
+        app.id = format!("app-{}", store::now_ms());
+    }
+
+    match apps.iter_mut().find(|a| a.id == app.id) {
+        Some(existing) => *existing = app.clone(),
+        None => apps.push(app.clone()),
+    }
+    store::save_json_atomic(&path, &apps)?;
+    Ok(app)
+}
+
+#[tauri::command]
+pub fn delete_custom_app<R: Runtime>(app_handle: tauri::AppHandle<R>, id: String) -> Result<(), String> {
+    let path = store_path(&app_handle)?;
+    let mut apps = load_apps(&path);
+    let before = apps.len();
+    apps.retain(|a| a.id != id);
+    if apps.len() == before {
+        return Err(format!("No custom command with id {}", id));
+    }
+    store::save_json_atomic(&path, &apps)
+}
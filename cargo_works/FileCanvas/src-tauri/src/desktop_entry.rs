@@ -0,0 +1,404 @@
+// src-tauri/src/desktop_entry.rs
+//
+// Parser for freedesktop .desktop files. Only the `[Desktop Entry]` group
+// and the `[Desktop Action <id>]` groups named in `Actions=` are read;
+// everything else (e.g. vendor extension groups) is ignored.
+//
+// Localized keys (`Name[de_DE]`) are resolved against LC_ALL / LC_MESSAGES /
+// LANG using the spec's lang_COUNTRY@MODIFIER fallback order.
+//
+// The group parser is also used for other INI-style files (icon theme
+// index.theme, GTK settings), which is why this module builds everywhere.
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const MAIN_GROUP: &str = "Desktop Entry";
+const ACTION_PREFIX: &str = "Desktop Action ";
+
+// --- Data Structures ---
+
+#[derive(Debug, Clone)]
+pub struct DesktopAction {
+    pub id: String,
+    pub name: String,
+    pub exec: String,
+    pub icon: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DesktopEntry {
+    pub path: PathBuf,
+    /// `Type=`: Application, Link or Directory
+    pub entry_type: String,
+    pub name: String,
+    /// Raw `Exec=` value; see desktop_exec for expansion
+    pub exec: Option<String>,
+    pub try_exec: Option<String>,
+    pub icon: Option<String>,
+    /// `Path=`: working directory to launch in
+    pub working_dir: Option<String>,
+    pub mime_types: Vec<String>,
+    pub categories: Vec<String>,
+    pub terminal: bool,
+    pub no_display: bool,
+    pub hidden: bool,
+    pub only_show_in: Vec<String>,
+    pub not_show_in: Vec<String>,
+    pub actions: Vec<DesktopAction>,
+}
+
+/// Group name → key (including any `[locale]` suffix) → raw value
+pub(crate) type Groups = HashMap<String, HashMap<String, String>>;
+
+// --- Helpers ---
+
+pub(crate) fn parse_groups(content: &str) -> Groups {
+    let mut groups: Groups = HashMap::new();
+    let mut current: Option<String> = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            let name = line[1..line.len() - 1].to_string();
+            groups.entry(name.clone()).or_default();
+            current = Some(name);
+            continue;
+        }
+        let (Some(group), Some((key, value))) = (&current, line.split_once('=')) else {
+            continue;
+        };
+        // The first occurrence of a key within a group wins
+        groups
+            .get_mut(group)
+            .expect("group inserted above")
+            .entry(key.trim().to_string())
+            .or_insert_with(|| value.trim().to_string());
+    }
+    groups
+}
+
+/// Undo the escapes every string value may contain (\s \n \t \r \\).
+/// Anything else is left as written: `Exec=` has quoting rules of its own
+/// (see desktop_exec) that need their backslashes.
+pub fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => out.push(' '),
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('\\') => out.push('\\'),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// `a;b\;c;` → ["a", "b;c"]
+fn split_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    if next == ';' {
+                        current.push(';');
+                    } else {
+                        current.push('\\');
+                        current.push(next);
+                    }
+                }
+            }
+            ';' => items.push(unescape(&std::mem::take(&mut current))),
+            _ => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        items.push(unescape(&current));
+    }
+    items.retain(|s| !s.is_empty());
+    items
+}
+
+fn is_true(value: Option<&String>) -> bool {
+    value.is_some_and(|v| v == "true")
+}
+
+/// Locale suffixes to try for localized keys, most specific first.
+/// `de_DE.UTF-8@euro` → ["de_DE@euro", "de_DE", "de@euro", "de"]
+fn locale_candidates() -> Vec<String> {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|v| std::env::var(v).ok())
+        .find(|v| !v.is_empty())
+        .unwrap_or_default();
+    candidates_for(&locale)
+}
+
+fn candidates_for(locale: &str) -> Vec<String> {
+    if locale.is_empty() || locale == "C" || locale == "POSIX" {
+        return Vec::new();
+    }
+
+    let (rest, modifier) = match locale.split_once('@') {
+        Some((r, m)) => (r, Some(m)),
+        None => (locale, None),
+    };
+    let rest = rest.split('.').next().unwrap_or(rest);
+    let (lang, country) = match rest.split_once('_') {
+        Some((l, c)) => (l, Some(c)),
+        None => (rest, None),
+    };
+
+    let mut out = Vec::new();
+    if let (Some(c), Some(m)) = (country, modifier) {
+        out.push(format!("{}_{}@{}", lang, c, m));
+    }
+    if let Some(c) = country {
+        out.push(format!("{}_{}", lang, c));
+    }
+    if let Some(m) = modifier {
+        out.push(format!("{}@{}", lang, m));
+    }
+    out.push(lang.to_string());
+    out
+}
+
+fn localized(group: &HashMap<String, String>, key: &str, locales: &[String]) -> Option<String> {
+    locales
+        .iter()
+        .find_map(|l| group.get(&format!("{}[{}]", key, l)))
+        .or_else(|| group.get(key))
+        .map(|v| unescape(v))
+}
+
+/// TryExec: an absolute path must exist, a bare name must be on $PATH;
+/// both must be executable
+pub(crate) fn program_exists(program: &str) -> bool {
+    let is_executable = |p: &Path| {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            p.metadata()
+                .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+                .unwrap_or(false)
+        }
+        #[cfg(not(unix))]
+        {
+            p.is_file()
+        }
+    };
+
+    if program.contains('/') {
+        return is_executable(Path::new(program));
+    }
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| is_executable(&dir.join(program))))
+        .unwrap_or(false)
+}
+
+/// Lower-cased entries of $XDG_CURRENT_DESKTOP ("GNOME" → "gnome")
+pub fn current_desktops() -> Vec<String> {
+    std::env::var("XDG_CURRENT_DESKTOP")
+        .unwrap_or_default()
+        .split(':')
+        .filter(|d| !d.is_empty())
+        .map(|d| d.to_lowercase())
+        .collect()
+}
+
+// --- Parsing ---
+
+impl DesktopEntry {
+    pub fn parse(content: &str, path: &Path) -> Option<Self> {
+        let groups = parse_groups(content);
+        let main = groups.get(MAIN_GROUP)?;
+        let locales = locale_candidates();
+        let list = |key: &str| main.get(key).map(|v| split_list(v)).unwrap_or_default();
+
+        let actions = list("Actions")
+            .into_iter()
+            .filter_map(|id| {
+                let group = groups.get(&format!("{}{}", ACTION_PREFIX, id))?;
+                Some(DesktopAction {
+                    name: localized(group, "Name", &locales)?,
+                    exec: group.get("Exec")?.clone(),
+                    icon: localized(group, "Icon", &locales),
+                    id,
+                })
+            })
+            .collect();
+
+        Some(DesktopEntry {
+            path: path.to_path_buf(),
+            entry_type: main.get("Type").cloned().unwrap_or_default(),
+            name: localized(main, "Name", &locales)?,
+            exec: main.get("Exec").cloned(),
+            try_exec: main.get("TryExec").map(|v| unescape(v)),
+            icon: localized(main, "Icon", &locales),
+            working_dir: main.get("Path").map(|v| unescape(v)).filter(|p| !p.is_empty()),
+            mime_types: list("MimeType"),
+            categories: list("Categories"),
+            terminal: is_true(main.get("Terminal")),
+            no_display: is_true(main.get("NoDisplay")),
+            hidden: is_true(main.get("Hidden")),
+            only_show_in: list("OnlyShowIn"),
+            not_show_in: list("NotShowIn"),
+            actions,
+        })
+    }
+
+    pub fn load(path: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(path).ok()?;
+        let entry = Self::parse(&content, path)?;
+        let icons = entry.actions.iter().map(|a| &a.icon).chain([&entry.icon]);
+        icons.flatten().for_each(|icon| crate::icons::allow_entry_icon(icon));
+        Some(entry)
+    }
+
+    /// Launchable applications only: right Type, not Hidden, allowed in the
+    /// current desktop and with its TryExec program installed.
+    /// NoDisplay entries are still launchable, so they are checked separately.
+    pub fn is_available(&self) -> bool {
+        self.available_in(&current_desktops())
+    }
+
+    /// `is_available` for the given (lower-cased) desktop names
+    fn available_in(&self, desktops: &[String]) -> bool {
+        if self.entry_type != "Application" || self.hidden || self.exec.is_none() {
+            return false;
+        }
+
+        let listed = |list: &[String]| list.iter().any(|d| desktops.contains(&d.to_lowercase()));
+        if !self.only_show_in.is_empty() && !listed(&self.only_show_in) {
+            return false;
+        }
+        if listed(&self.not_show_in) {
+            return false;
+        }
+
+        self.try_exec.as_deref().is_none_or(program_exists)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIEWER: &str = "\
+# A comment
+[Desktop Entry]
+Type=Application
+Name=Viewer
+Name[de]=Betrachter
+Name[de_DE]=Bildbetrachter
+Exec=viewer %F
+Exec=ignored
+MimeType=image/png;image/jpeg;
+Actions=new-window;missing;
+
+[Desktop Action new-window]
+Name=New Window
+Exec=viewer --new-window
+
+[X-Vendor Extension]
+Exec=vendor
+";
+
+    fn entry() -> DesktopEntry {
+        DesktopEntry::parse(VIEWER, Path::new("/usr/share/applications/viewer.desktop")).unwrap()
+    }
+
+    fn desktops(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn groups_keep_the_first_value_of_each_key() {
+        let groups = parse_groups(VIEWER);
+        assert_eq!(groups.len(), 3);
+        let main = &groups[MAIN_GROUP];
+        assert_eq!(main["Exec"], "viewer %F");
+        assert_eq!(main["Name[de_DE]"], "Bildbetrachter");
+        assert_eq!(groups["X-Vendor Extension"]["Exec"], "vendor");
+        // Keys before the first group belong to none
+        assert!(parse_groups("Name=stray\n[Empty]").get("Empty").unwrap().is_empty());
+    }
+
+    #[test]
+    fn lists_split_on_unescaped_semicolons() {
+        assert_eq!(split_list(r"a;b\;c;"), ["a", "b;c"]);
+        assert_eq!(split_list(r"one\stwo;;three"), ["one two", "three"]);
+        assert!(split_list(";;").is_empty());
+    }
+
+    #[test]
+    fn locales_fall_back_from_most_specific() {
+        assert_eq!(candidates_for("de_DE.UTF-8@euro"), ["de_DE@euro", "de_DE", "de@euro", "de"]);
+        assert_eq!(candidates_for("pt_BR.UTF-8"), ["pt_BR", "pt"]);
+        assert_eq!(candidates_for("sr@latin"), ["sr@latin", "sr"]);
+        assert!(candidates_for("C").is_empty());
+        assert!(candidates_for("").is_empty());
+
+        let main = &parse_groups(VIEWER)[MAIN_GROUP];
+        assert_eq!(localized(main, "Name", &candidates_for("de_AT")).unwrap(), "Betrachter");
+        assert_eq!(localized(main, "Name", &candidates_for("de_DE")).unwrap(), "Bildbetrachter");
+        assert_eq!(localized(main, "Name", &candidates_for("fr_FR")).unwrap(), "Viewer");
+    }
+
+    #[test]
+    fn entries_read_lists_and_named_actions() {
+        let entry = entry();
+        assert_eq!(entry.mime_types, ["image/png", "image/jpeg"]);
+        // Actions without a group of their own are skipped
+        assert_eq!(entry.actions.len(), 1);
+        assert_eq!(entry.actions[0].exec, "viewer --new-window");
+        assert!(DesktopEntry::parse("[Desktop Entry]\nType=Application", Path::new("/x")).is_none());
+    }
+
+    #[test]
+    fn availability_follows_type_hidden_and_desktop_lists() {
+        let gnome = desktops(&["gnome"]);
+        assert!(entry().available_in(&gnome));
+
+        let link = DesktopEntry { entry_type: "Link".into(), ..entry() };
+        assert!(!link.available_in(&gnome));
+        let hidden = DesktopEntry { hidden: true, ..entry() };
+        assert!(!hidden.available_in(&gnome));
+
+        let kde_only = DesktopEntry { only_show_in: desktops(&["KDE"]), ..entry() };
+        assert!(!kde_only.available_in(&gnome));
+        assert!(kde_only.available_in(&desktops(&["kde"])));
+        let not_gnome = DesktopEntry { not_show_in: desktops(&["GNOME"]), ..entry() };
+        assert!(!not_gnome.available_in(&desktops(&["ubuntu", "gnome"])));
+        assert!(not_gnome.available_in(&[]));
+    }
+
+    #[test]
+    fn availability_needs_the_try_exec_program() {
+        let missing = DesktopEntry { try_exec: Some("/nonexistent/filecanvas-viewer".into()), ..entry() };
+        assert!(!missing.available_in(&[]));
+        #[cfg(unix)]
+        {
+            let installed = DesktopEntry { try_exec: Some("/bin/sh".into()), ..entry() };
+            assert!(installed.available_in(&[]));
+        }
+    }
+}
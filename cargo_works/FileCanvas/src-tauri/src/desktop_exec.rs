@@ -0,0 +1,332 @@
+// src-tauri/src/desktop_exec.rs
+//
+// Desktop Entry `Exec=` handling per the freedesktop Desktop Entry spec:
+//   - value-level escapes (\s \n \t \r \\) are undone first
+//   - arguments are split on spaces; double-quoted arguments may contain
+//     spaces and use \" \` \$ \\ escapes
+//   - field codes are substituted: %f %F %u %U %i %c %k and %%;
+//     deprecated codes (%d %D %n %N %v %m) are dropped
+//
+// The result is a list of argv vectors that are spawned directly, never
+// through a shell, so file names with quotes, `$` or spaces are passed as-is.
+
+use crate::desktop_entry::unescape;
+use std::path::Path;
+
+// --- Data Structures ---
+
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Literal(String),
+    Field(char),
+}
+
+/// One argument of the Exec line before field codes are expanded
+#[derive(Debug, Clone, PartialEq)]
+struct Arg(Vec<Piece>);
+
+/// Values for the field codes that don't come from the files being opened
+#[derive(Debug, Default, Clone)]
+pub struct ExecContext {
+    /// `%c`: the (translated) Name of the entry
+    pub name: String,
+    /// `%i`: expands to `--icon <Icon>` when the entry has an icon
+    pub icon: Option<String>,
+    /// `%k`: location of the .desktop file
+    pub desktop_file: Option<String>,
+}
+
+/// One process to start and the files it was given
+#[derive(Debug, Clone, PartialEq)]
+pub struct Launch {
+    pub argv: Vec<String>,
+    pub files: Vec<String>,
+}
+
+// --- Parsing ---
+
+fn push_literal(pieces: &mut Vec<Piece>, c: char) {
+    match pieces.last_mut() {
+        Some(Piece::Literal(s)) => s.push(c),
+        _ => pieces.push(Piece::Literal(c.to_string())),
+    }
+}
+
+/// Split a raw `Exec=` value into arguments
+fn tokenize(exec: &str) -> Result<Vec<Arg>, String> {
+    let exec = unescape(exec);
+    let mut args = Vec::new();
+    let mut current: Option<Vec<Piece>> = None;
+    let mut chars = exec.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\n' => {
+                if let Some(pieces) = current.take() {
+                    args.push(Arg(pieces));
+                }
+            }
+            '"' => {
+                let pieces = current.get_or_insert_with(Vec::new);
+                // An empty quoted argument ("") is still an argument
+                if pieces.is_empty() {
+                    pieces.push(Piece::Literal(String::new()));
+                }
+                loop {
+                    match chars.next() {
+                        None => return Err(format!("Unterminated quote in Exec: {}", exec)),
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(e @ ('"' | '`' | '$' | '\\')) => push_literal(pieces, e),
+                            Some(other) => {
+                                push_literal(pieces, '\\');
+                                push_literal(pieces, other);
+                            }
+                            None => return Err(format!("Unterminated quote in Exec: {}", exec)),
+                        },
+                        // Field codes are not allowed inside quotes; only %% is honored
+                        Some('%') if chars.peek() == Some(&'%') => {
+                            chars.next();
+                            push_literal(pieces, '%');
+                        }
+                        Some(other) => push_literal(pieces, other),
+                    }
+                }
+            }
+            '%' => {
+                let pieces = current.get_or_insert_with(Vec::new);
+                match chars.next() {
+                    Some('%') => push_literal(pieces, '%'),
+                    Some(code @ ('f' | 'F' | 'u' | 'U' | 'i' | 'c' | 'k')) => {
+                        pieces.push(Piece::Field(code))
+                    }
+                    Some('d' | 'D' | 'n' | 'N' | 'v' | 'm') => {}
+                    Some(other) => {
+                        return Err(format!("Unknown field code %{} in Exec: {}", other, exec))
+                    }
+                    None => return Err(format!("Dangling % in Exec: {}", exec)),
+                }
+            }
+            other => push_literal(current.get_or_insert_with(Vec::new), other),
+        }
+    }
+    if let Some(pieces) = current {
+        args.push(Arg(pieces));
+    }
+
+    // Arguments that only held deprecated codes vanish entirely
+    args.retain(|a| !a.0.is_empty());
+    if args.is_empty() {
+        return Err("Empty Exec command".to_string());
+    }
+    Ok(args)
+}
+
+// --- Expansion ---
+
+/// `file://` URI for a local path, percent-encoding everything outside the
+/// unreserved set. Strings that already look like URIs are passed through.
+pub fn to_uri(file: &str) -> String {
+    if file.contains("://") {
+        return file.to_string();
+    }
+    let path = Path::new(file);
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    };
+
+    let mut out = String::from("file://");
+    for byte in absolute.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+fn uses(args: &[Arg], codes: &[char]) -> bool {
+    args.iter()
+        .flat_map(|a| a.0.iter())
+        .any(|p| matches!(p, Piece::Field(c) if codes.contains(c)))
+}
+
+/// Expand one launch. `files` are the files handed to this invocation:
+/// all of them for %F/%U, at most one for %f/%u.
+fn expand_once(args: &[Arg], files: &[String], ctx: &ExecContext) -> Vec<String> {
+    let mut argv = Vec::new();
+
+    for Arg(pieces) in args {
+        // List codes standing alone become zero or more arguments
+        if let [Piece::Field(code)] = pieces.as_slice() {
+            match code {
+                'F' => {
+                    argv.extend(files.iter().cloned());
+                    continue;
+                }
+                'U' => {
+                    argv.extend(files.iter().map(|f| to_uri(f)));
+                    continue;
+                }
+                'f' | 'u' if files.is_empty() => continue,
+                'i' => {
+                    if let Some(icon) = ctx.icon.as_ref().filter(|i| !i.is_empty()) {
+                        argv.push("--icon".to_string());
+                        argv.push(icon.clone());
+                    }
+                    continue;
+                }
+                'k' if ctx.desktop_file.is_none() => continue,
+                _ => {}
+            }
+        }
+
+        let mut value = String::new();
+        for piece in pieces {
+            match piece {
+                Piece::Literal(s) => value.push_str(s),
+                Piece::Field('f' | 'F') => value.push_str(files.first().map(|s| s.as_str()).unwrap_or("")),
+                Piece::Field('u' | 'U') => {
+                    value.push_str(&files.first().map(|f| to_uri(f)).unwrap_or_default())
+                }
+                Piece::Field('i') => value.push_str(ctx.icon.as_deref().unwrap_or("")),
+                Piece::Field('c') => value.push_str(&ctx.name),
+                Piece::Field('k') => value.push_str(ctx.desktop_file.as_deref().unwrap_or("")),
+                Piece::Field(_) => {}
+            }
+        }
+        argv.push(value);
+    }
+    argv
+}
+
+/// Turn an `Exec=` value and the files to open into the processes to spawn.
+///
+/// Exec lines with %F/%U get every file in one launch. With %f/%u the
+/// program is launched once per file. Lines without any file code get the
+/// files appended, one launch per file, the same way GLib handles them.
+pub fn expand(exec: &str, files: &[String], ctx: &ExecContext) -> Result<Vec<Launch>, String> {
+    let mut args = tokenize(exec)?;
+
+    let launches = if uses(&args, &['F', 'U']) || files.is_empty() {
+        vec![Launch {
+            argv: expand_once(&args, files, ctx),
+            files: files.to_vec(),
+        }]
+    } else {
+        if !uses(&args, &['f', 'u']) {
+            args.push(Arg(vec![Piece::Field('f')]));
+        }
+        files
+            .iter()
+            .map(|f| Launch {
+                argv: expand_once(&args, std::slice::from_ref(f), ctx),
+                files: vec![f.clone()],
+            })
+            .collect()
+    };
+
+    // `Exec=%F` without files, or `Exec=%i` without an icon, leaves nothing to run
+    if launches.iter().any(|l| l.argv.first().is_none_or(|program| program.is_empty())) {
+        return Err(format!("No program to run in Exec: {}", exec));
+    }
+    Ok(launches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn argvs(exec: &str, names: &[&str], ctx: &ExecContext) -> Vec<Vec<String>> {
+        expand(exec, &files(names), ctx).unwrap().into_iter().map(|l| l.argv).collect()
+    }
+
+    fn ctx() -> ExecContext {
+        ExecContext {
+            name: "Image Viewer".into(),
+            icon: Some("viewer".into()),
+            desktop_file: Some("/usr/share/applications/viewer.desktop".into()),
+        }
+    }
+
+    #[test]
+    fn quoted_arguments_keep_spaces_and_escapes() {
+        // A backslash in a quoted argument is written \\\\: once for the value, once for the quotes
+        let argv = &argvs(r#"app "two words" "say \"hi\"" "\$HOME" "back\\\\slash" """#, &[], &ctx())[0];
+        assert_eq!(argv, &["app", "two words", r#"say "hi""#, "$HOME", r"back\slash", ""]);
+        assert!(expand(r#"app "open"#, &[], &ctx()).is_err());
+    }
+
+    #[test]
+    fn value_escapes_come_first() {
+        // \s is a space at the value level, so it splits like one
+        assert_eq!(argvs(r"app\s--flag", &[], &ctx())[0], ["app", "--flag"]);
+    }
+
+    #[test]
+    fn percent_percent_is_a_literal() {
+        assert_eq!(argvs("app 100%% \"50%%\"", &[], &ctx())[0], ["app", "100%", "50%"]);
+    }
+
+    #[test]
+    fn list_codes_take_every_file_in_one_launch() {
+        let launches = expand("app %F", &files(&["/a b.jpg", "/c.jpg"]), &ctx()).unwrap();
+        assert_eq!(launches.len(), 1);
+        assert_eq!(launches[0].argv, ["app", "/a b.jpg", "/c.jpg"]);
+        assert_eq!(launches[0].files, ["/a b.jpg", "/c.jpg"]);
+
+        assert_eq!(
+            argvs("app %U", &["/a b.jpg"], &ctx()),
+            [["app", "file:///a%20b.jpg"]]
+        );
+    }
+
+    #[test]
+    fn single_codes_launch_once_per_file() {
+        assert_eq!(
+            argvs("app --open=%f", &["/a.txt", "/b.txt"], &ctx()),
+            [["app", "--open=/a.txt"], ["app", "--open=/b.txt"]]
+        );
+        assert_eq!(argvs("app %u", &["/a.txt"], &ctx()), [["app", "file:///a.txt"]]);
+        // Without files the argument is dropped
+        assert_eq!(argvs("app %f", &[], &ctx()), [["app"]]);
+    }
+
+    #[test]
+    fn files_are_appended_without_a_file_code() {
+        assert_eq!(argvs("app --new", &["/a", "/b"], &ctx()), [["app", "--new", "/a"], ["app", "--new", "/b"]]);
+    }
+
+    #[test]
+    fn entry_codes_come_from_the_context() {
+        let argv = &argvs("app %i --name=%c %k", &[], &ctx())[0];
+        assert_eq!(argv, &["app", "--icon", "viewer", "--name=Image Viewer", "/usr/share/applications/viewer.desktop"]);
+
+        let bare = ExecContext::default();
+        assert_eq!(argvs("app %i %k", &[], &bare)[0], ["app"]);
+    }
+
+    #[test]
+    fn deprecated_codes_are_dropped() {
+        assert_eq!(argvs("app %d %D %n %N %v %m -x%d", &[], &ctx())[0], ["app", "-x"]);
+        assert!(expand("app %z", &[], &ctx()).is_err());
+        assert!(expand("app %", &[], &ctx()).is_err());
+    }
+
+    #[test]
+    fn an_exec_line_without_a_program_is_an_error() {
+        assert!(expand("", &[], &ctx()).is_err());
+        assert!(expand("%F", &[], &ctx()).is_err());
+        assert!(expand("%i", &[], &ExecContext::default()).is_err());
+        assert!(expand("%f", &[], &ctx()).is_err());
+        assert!(expand("%F", &files(&["/a"]), &ctx()).is_ok());
+    }
+}
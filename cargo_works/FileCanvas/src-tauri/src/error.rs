@@ -0,0 +1,191 @@
+// src-tauri/src/error.rs
+//
+// The error type returned by the file and open-with commands. It serializes
+// to `{ code, path, errno, message }` so the UI can react to the kind of
+// failure ("already_exists" → offer a new name, "permission_denied" → choose
+// another folder) instead of matching on English text.
+//
+// Modules that still use `Result<_, String>` can `?` an FsError (it converts
+// to its message), and a String converts back as `ErrorCode::Other`. Launch
+// failures convert in launcher.rs.
+
+use serde::Serialize;
+use std::fmt;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+// --- Data Structures ---
+
+/// Stable identifiers the frontend can switch on
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    NotFound,
+    PermissionDenied,
+    AlreadyExists,
+    NotADirectory,
+    IsADirectory,
+    DirectoryNotEmpty,
+    /// Rename/move across filesystems
+    CrossesDevices,
+    ReadOnly,
+    NoSpace,
+    Busy,
+    InvalidName,
+    InvalidPath,
+    /// Outside every folder approved through the picker
+    OutsideRoot,
+    /// An approved root can't be deleted, renamed or moved
+    ProtectedRoot,
+    /// The program to launch isn't installed or isn't executable
+    AppNotFound,
+    /// The program started and exited with an error right away
+    AppExited,
+    LaunchFailed,
+    /// A remote server refused the credentials
+    AuthFailed,
+    /// A remote server couldn't be reached or the connection dropped
+    ConnectionFailed,
+    /// The server's host key isn't known yet; `detail` has its fingerprint
+    UnknownHost,
+    /// The server's host key differs from the one saved earlier
+    HostKeyChanged,
+    Unsupported,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FsError {
+    pub code: ErrorCode,
+    /// The file or folder the error is about, when there is one
+    pub path: Option<String>,
+    /// Raw OS error number (errno / GetLastError)
+    pub errno: Option<i32>,
+    pub message: String,
+    /// Exit code of an app that failed to start
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// Tail of that app's stderr
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stderr: Option<String>,
+    /// Extra data for the UI to act on, e.g. the host key fingerprint to
+    /// confirm for `unknown_host`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+// --- Helpers ---
+
+fn code_for(kind: ErrorKind) -> ErrorCode {
+    match kind {
+        ErrorKind::NotFound => ErrorCode::NotFound,
+        ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
+        ErrorKind::AlreadyExists => ErrorCode::AlreadyExists,
+        ErrorKind::NotADirectory => ErrorCode::NotADirectory,
+        ErrorKind::IsADirectory => ErrorCode::IsADirectory,
+        ErrorKind::DirectoryNotEmpty => ErrorCode::DirectoryNotEmpty,
+        ErrorKind::CrossesDevices => ErrorCode::CrossesDevices,
+        ErrorKind::ReadOnlyFilesystem => ErrorCode::ReadOnly,
+        ErrorKind::StorageFull | ErrorKind::QuotaExceeded => ErrorCode::NoSpace,
+        ErrorKind::ResourceBusy | ErrorKind::ExecutableFileBusy => ErrorCode::Busy,
+        ErrorKind::InvalidFilename => ErrorCode::InvalidName,
+        ErrorKind::Unsupported => ErrorCode::Unsupported,
+        ErrorKind::ConnectionRefused
+        | ErrorKind::ConnectionReset
+        | ErrorKind::ConnectionAborted
+        | ErrorKind::NotConnected
+        | ErrorKind::HostUnreachable
+        | ErrorKind::NetworkUnreachable
+        | ErrorKind::TimedOut => ErrorCode::ConnectionFailed,
+        _ => ErrorCode::Other,
+    }
+}
+
+fn describe(code: ErrorCode) -> Option<&'static str> {
+    Some(match code {
+        ErrorCode::NotFound => "No such file or folder",
+        ErrorCode::PermissionDenied => "Permission denied",
+        ErrorCode::AlreadyExists => "Already exists",
+        ErrorCode::NotADirectory => "Not a folder",
+        ErrorCode::IsADirectory => "Is a folder",
+        ErrorCode::DirectoryNotEmpty => "Folder is not empty",
+        ErrorCode::CrossesDevices => "Can't move across drives",
+        ErrorCode::ReadOnly => "The drive is read-only",
+        ErrorCode::NoSpace => "Not enough space",
+        ErrorCode::Busy => "In use by another program",
+        ErrorCode::InvalidName => "Invalid name",
+        ErrorCode::ConnectionFailed => "Can't reach",
+        _ => return None,
+    })
+}
+
+impl FsError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        FsError {
+            code,
+            path: None,
+            errno: None,
+            message: message.into(),
+            exit_code: None,
+            stderr: None,
+            detail: None,
+        }
+    }
+
+    pub fn with_path(mut self, path: impl AsRef<Path>) -> Self {
+        self.path = Some(path.as_ref().to_string_lossy().to_string());
+        self
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// An I/O error while working on `path`
+    pub fn io(err: &io::Error, path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let code = code_for(err.kind());
+        let message = match describe(code) {
+            Some(text) => format!("{}: {}", text, path.display()),
+            None => format!("{}: {}", path.display(), err),
+        };
+        FsError {
+            errno: err.raw_os_error(),
+            ..FsError::new(code, message).with_path(path)
+        }
+    }
+}
+
+/// `.at(path)` turns an `io::Result` into a `Result<_, FsError>` about `path`
+pub trait IoContext<T> {
+    fn at(self, path: impl AsRef<Path>) -> Result<T, FsError>;
+}
+
+impl<T> IoContext<T> for io::Result<T> {
+    fn at(self, path: impl AsRef<Path>) -> Result<T, FsError> {
+        self.map_err(|e| FsError::io(&e, path))
+    }
+}
+
+impl fmt::Display for FsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(line) = self.stderr.as_deref().and_then(|s| s.lines().rev().find(|l| !l.trim().is_empty())) {
+            write!(f, ": {}", line.trim())?;
+        }
+        Ok(())
+    }
+}
+
+impl From<String> for FsError {
+    fn from(message: String) -> Self {
+        FsError::new(ErrorCode::Other, message)
+    }
+}
+
+impl From<FsError> for String {
+    fn from(e: FsError) -> Self {
+        e.to_string()
+    }
+}
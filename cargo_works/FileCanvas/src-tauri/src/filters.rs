@@ -0,0 +1,673 @@
+use crate::activity::{ActivityEntry, ActivityLog, Operation, Via};
+use crate::error::{ErrorCode, FsError};
+use crate::ops;
+use crate::remote::{self, Location};
+use filecanvas_lib::space::{self, SpaceCheck};
+use filecanvas_lib::vfs::Metadata;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::async_runtime::spawn_blocking;
+use tauri::Runtime;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+// --- Data Structures ---
+
+/// Filter rules attached to a connection. Empty lists and `None` values mean
+/// "no restriction", so the default value lets everything through.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterRules {
+    /// Globs a file must match (by name or relative path), e.g. `*.pdf`.
+    pub include: Vec<String>,
+    /// Globs that skip a file or a whole folder, e.g. `*.tmp`, `node_modules`.
+    pub exclude: Vec<String>,
+    /// Allowed extensions without the dot, case-insensitive.
+    pub extensions: Vec<String>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    /// Only files last modified more than this many days ago.
+    pub older_than_days: Option<u64>,
+    /// Only files last modified within this many days.
+    pub newer_than_days: Option<u64>,
+    /// Regular expression applied to the file name.
+    pub name_regex: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FilteredEntry {
+    pub path: String,
+    pub is_directory: bool,
+    /// The rule that filtered this entry out, e.g. `exclude: *.tmp`.
+    pub rule: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedFile {
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransferPlan {
+    pub source: String,
+    pub destination: String,
+    pub files: Vec<PlannedFile>,
+    /// Empty folders that pass the filter; they're created at the destination
+    pub empty_folders: Vec<String>,
+    pub filtered: Vec<FilteredEntry>,
+    pub total_bytes: u64,
+    /// Room for the transfer at the destination; `None` when the free space
+    /// can't be read
+    pub space: Option<SpaceCheck>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferMode {
+    Copy,
+    Move,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransferResult {
+    pub destination: String,
+    pub transferred: usize,
+    pub filtered: usize,
+    pub bytes: u64,
+    /// Files left alone because the destination already had one by that name
+    pub conflicts: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// `FilterRules` with the globs and regex compiled once per transfer.
+pub struct CompiledFilter {
+    include: Option<(GlobSet, Vec<String>)>,
+    exclude: Option<(GlobSet, Vec<String>)>,
+    extensions: Vec<String>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    older_than: Option<Duration>,
+    newer_than: Option<Duration>,
+    name_regex: Option<Regex>,
+    now: SystemTime,
+}
+
+// --- Compilation & Matching ---
+
+fn build_globset(patterns: &[String]) -> Result<Option<(GlobSet, Vec<String>)>, String> {
+    let patterns: Vec<String> = patterns
+        .iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect();
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in &patterns {
+        let glob = Glob::new(pattern).map_err(|e| format!("Invalid glob '{}': {}", pattern, e))?;
+        builder.add(glob);
+    }
+    let set = builder.build().map_err(|e| e.to_string())?;
+    Ok(Some((set, patterns)))
+}
+
+fn matching_pattern<'a>(globs: &'a (GlobSet, Vec<String>), name: &str, rel_path: &str) -> Option<&'a str> {
+    let (set, patterns) = globs;
+    set.matches(name)
+        .into_iter()
+        .chain(set.matches(rel_path))
+        .next()
+        .map(|i| patterns[i].as_str())
+}
+
+impl FilterRules {
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty()
+            && self.exclude.is_empty()
+            && self.extensions.is_empty()
+            && self.min_size.is_none()
+            && self.max_size.is_none()
+            && self.older_than_days.is_none()
+            && self.newer_than_days.is_none()
+            && self.name_regex.as_deref().is_none_or(|r| r.trim().is_empty())
+    }
+
+    pub fn compile(&self) -> Result<CompiledFilter, String> {
+        let name_regex = match self.name_regex.as_deref().map(str::trim) {
+            Some(r) if !r.is_empty() => {
+                Some(Regex::new(r).map_err(|e| format!("Invalid name regex: {}", e))?)
+            }
+            _ => None,
+        };
+
+        Ok(CompiledFilter {
+            include: build_globset(&self.include)?,
+            exclude: build_globset(&self.exclude)?,
+            extensions: self
+                .extensions
+                .iter()
+                .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+                .filter(|e| !e.is_empty())
+                .collect(),
+            min_size: self.min_size,
+            max_size: self.max_size,
+            older_than: self.older_than_days.map(|d| Duration::from_secs(d * DAY.as_secs())),
+            newer_than: self.newer_than_days.map(|d| Duration::from_secs(d * DAY.as_secs())),
+            name_regex,
+            now: SystemTime::now(),
+        })
+    }
+}
+
+impl CompiledFilter {
+    /// Folders are only checked against the exclude globs, so `node_modules`
+    /// or `.git` can prune a whole subtree. Returns the rule that rejected it.
+    pub fn check_dir(&self, name: &str, rel_path: &str) -> Option<String> {
+        self.exclude
+            .as_ref()
+            .and_then(|ex| matching_pattern(ex, name, rel_path))
+            .map(|p| format!("exclude: {}", p))
+    }
+
+    /// Returns the rule that rejected the file, or `None` if it passes.
+    pub fn check_file(&self, name: &str, rel_path: &str, metadata: &fs::Metadata) -> Option<String> {
+        self.check_entry(name, rel_path, metadata.len(), metadata.modified().ok())
+    }
+
+    /// `check_file` for an entry of any `FileSystem`
+    pub fn check_metadata(&self, name: &str, rel_path: &str, metadata: &Metadata) -> Option<String> {
+        let modified = (metadata.mtime > 0).then(|| UNIX_EPOCH + Duration::from_millis(metadata.mtime));
+        self.check_entry(name, rel_path, metadata.len, modified)
+    }
+
+    /// `check_file` for a file known only by size and modification time,
+    /// e.g. one listed on a remote server.
+    pub fn check_entry(&self, name: &str, rel_path: &str, size: u64, modified: Option<SystemTime>) -> Option<String> {
+        if let Some(p) = self.exclude.as_ref().and_then(|ex| matching_pattern(ex, name, rel_path)) {
+            return Some(format!("exclude: {}", p));
+        }
+
+        if let Some(include) = &self.include {
+            if matching_pattern(include, name, rel_path).is_none() {
+                return Some(format!("include: {}", include.1.join(", ")));
+            }
+        }
+
+        if !self.extensions.is_empty() {
+            let ext = Path::new(name)
+                .extension()
+                .map(|e| e.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            if !self.extensions.contains(&ext) {
+                return Some(format!("extensions: {}", self.extensions.join(", ")));
+            }
+        }
+
+        if let Some(min) = self.min_size {
+            if size < min {
+                return Some(format!("min size: {} bytes", min));
+            }
+        }
+        if let Some(max) = self.max_size {
+            if size > max {
+                return Some(format!("max size: {} bytes", max));
+            }
+        }
+
+        if self.older_than.is_some() || self.newer_than.is_some() {
+            let age = modified
+                .and_then(|m| self.now.duration_since(m).ok())
+                .unwrap_or_default();
+            if let Some(older) = self.older_than {
+                if age < older {
+                    return Some(format!("older than: {} days", older.as_secs() / DAY.as_secs()));
+                }
+            }
+            if let Some(newer) = self.newer_than {
+                if age > newer {
+                    return Some(format!("newer than: {} days", newer.as_secs() / DAY.as_secs()));
+                }
+            }
+        }
+
+        if let Some(re) = &self.name_regex {
+            if !re.is_match(name) {
+                return Some(format!("name regex: {}", re.as_str()));
+            }
+        }
+
+        None
+    }
+}
+
+// --- Planning ---
+
+/// What passes the filter below a source
+#[derive(Default)]
+struct Collected {
+    files: Vec<PlannedFile>,
+    empty_folders: Vec<String>,
+    filtered: Vec<FilteredEntry>,
+}
+
+/// Walks `src` (a folder or a single file) and splits it into the files that
+/// pass the filter and the entries that were filtered out.
+fn collect(src: &Location, filter: &CompiledFilter) -> Result<Collected, FsError> {
+    let mut collected = Collected::default();
+    let metadata = src.fs().metadata(src.path())?;
+    if !metadata.is_dir {
+        let name = ops::display_name(src.path());
+        match filter.check_metadata(&name, &name, &metadata) {
+            Some(rule) => collected.filtered.push(FilteredEntry { path: name, is_directory: false, rule }),
+            None => collected.files.push(PlannedFile { path: name, size: metadata.len }),
+        }
+        return Ok(collected);
+    }
+    collect_dir(src, src.path(), "", filter, &mut collected);
+    Ok(collected)
+}
+
+/// Adds what's below `dir` to `collected`, keyed relative to the source
+/// (`prefix` is the key of `dir`). Unreadable folders are skipped.
+fn collect_dir(src: &Location, dir: &Path, prefix: &str, filter: &CompiledFilter, collected: &mut Collected) {
+    let Ok(entries) = src.fs().read_dir(dir) else { return };
+    if entries.is_empty() && !prefix.is_empty() {
+        collected.empty_folders.push(prefix.to_string());
+    }
+    for entry in entries {
+        let rel = if prefix.is_empty() {
+            entry.name.clone()
+        } else {
+            format!("{}/{}", prefix, entry.name)
+        };
+        if entry.metadata.is_dir {
+            match filter.check_dir(&entry.name, &rel) {
+                Some(rule) => collected.filtered.push(FilteredEntry { path: rel, is_directory: true, rule }),
+                None => collect_dir(src, &entry.path, &rel, filter, collected),
+            }
+            continue;
+        }
+        match filter.check_metadata(&entry.name, &rel, &entry.metadata) {
+            Some(rule) => collected.filtered.push(FilteredEntry { path: rel, is_directory: false, rule }),
+            None => collected.files.push(PlannedFile { path: rel, size: entry.metadata.len }),
+        }
+    }
+}
+
+/// `root` joined with a `/` separated key
+fn join_key(root: &Path, key: &str) -> std::path::PathBuf {
+    key.split('/').fold(root.to_path_buf(), |p, part| p.join(part))
+}
+
+fn destination_root(src: &Location, dest_folder: &Location) -> Result<Location, FsError> {
+    if !dest_folder.fs().metadata(dest_folder.path())?.is_dir {
+        return Err(FsError::new(ErrorCode::NotADirectory, "Destination is not a folder").with_path(dest_folder.url(dest_folder.path())));
+    }
+    let name = src
+        .path()
+        .file_name()
+        .ok_or_else(|| FsError::new(ErrorCode::InvalidPath, "Invalid source path"))?;
+    let dest = dest_folder.with_path(dest_folder.path().join(name));
+    let src_is_dir = src.fs().metadata(src.path()).is_ok_and(|m| m.is_dir);
+    if src_is_dir && src.same_fs(&dest) && dest.path().starts_with(src.path()) {
+        return Err(FsError::new(ErrorCode::InvalidPath, "Cannot transfer a folder into its own subfolder")
+            .with_path(dest_folder.url(dest_folder.path())));
+    }
+    Ok(dest)
+}
+
+/// Bytes the destination has to take: none for a move within one volume or
+/// one server
+fn bytes_needed(src: &Location, dest_folder: &Location, bytes: u64, mode: TransferMode) -> u64 {
+    let moving = mode == TransferMode::Move;
+    match (src, dest_folder) {
+        (Location::Local(from), Location::Local(into)) => space::bytes_needed(from, into, bytes, moving),
+        _ if moving && src.same_fs(dest_folder) => 0,
+        _ => bytes,
+    }
+}
+
+fn build_plan(src: &Location, dest_folder: &Location, rules: &FilterRules, mode: TransferMode) -> Result<TransferPlan, FsError> {
+    let dest = destination_root(src, dest_folder)?;
+    let filter = rules.compile()?;
+    let collected = collect(src, &filter)?;
+    let total_bytes = collected.files.iter().map(|f| f.size).sum();
+    let space = match dest_folder {
+        Location::Local(into) => space::check(into, bytes_needed(src, dest_folder, total_bytes, mode)),
+        Location::Remote { .. } => None,
+    };
+
+    Ok(TransferPlan {
+        source: src.url(src.path()),
+        destination: dest.url(dest.path()),
+        files: collected.files,
+        empty_folders: collected.empty_folders,
+        filtered: collected.filtered,
+        total_bytes,
+        space,
+    })
+}
+
+/// Bytes a transfer of `src` with `rules` would carry, without listing
+/// what's filtered out
+pub fn planned_bytes(src: &Location, rules: &FilterRules) -> Result<u64, FsError> {
+    let filter = rules.compile()?;
+    Ok(collect(src, &filter)?.files.iter().map(|f| f.size).sum())
+}
+
+// --- Execution ---
+
+/// Copy or move one file; an existing file at `to` is never replaced
+fn transfer_one(from: &Location, to: &Location, mode: TransferMode) -> Result<u64, FsError> {
+    if let Some(parent) = to.path().parent() {
+        to.fs().create_dir_all(parent)?;
+    }
+    ops::ensure_free(to.fs(), to.path())?;
+    let size = from.fs().metadata(from.path())?.len;
+    let copy = || {
+        if from.same_fs(to) {
+            from.fs().copy_file(from.path(), to.path())
+        } else {
+            ops::stream_file(from.fs(), from.path(), to.fs(), to.path())
+        }
+    };
+    match mode {
+        TransferMode::Copy => copy(),
+        // A rename where it works; across drives or servers a copy and a delete
+        TransferMode::Move if from.same_fs(to) => match from.fs().rename(from.path(), to.path()) {
+            Err(e) if e.code == ErrorCode::CrossesDevices => {
+                copy()?;
+                from.fs().remove_file(from.path()).map(|_| size)
+            }
+            result => result.map(|_| size),
+        },
+        TransferMode::Move => {
+            copy()?;
+            from.fs().remove_file(from.path()).map(|_| size)
+        }
+    }
+}
+
+/// After a filtered move, drop the source folders that were emptied by it.
+/// Folders still holding filtered-out files are left alone.
+fn remove_emptied_dirs(src: &Location, plan: &TransferPlan) {
+    let mut keys: Vec<&str> = plan
+        .files
+        .iter()
+        .filter_map(|f| f.path.rsplit_once('/').map(|(dir, _)| dir))
+        .chain(plan.empty_folders.iter().map(String::as_str))
+        .collect();
+    keys.sort();
+    keys.dedup();
+
+    // deepest first so parents can become empty too
+    for key in keys.iter().rev() {
+        let mut current = Some(*key);
+        while let Some(dir) = current {
+            let path = join_key(src.path(), dir);
+            let empty = src.fs().read_dir(&path).is_ok_and(|entries| entries.is_empty());
+            if !empty || src.fs().remove_dir_all(&path).is_err() {
+                break;
+            }
+            current = dir.rsplit_once('/').map(|(parent, _)| parent);
+        }
+    }
+}
+
+/// `transfer_one`, logged to the activity log
+fn transfer_logged(from: &Location, to: &Location, mode: TransferMode, log: &ActivityLog) -> Result<u64, FsError> {
+    let operation = match mode {
+        TransferMode::Copy => Operation::Copy,
+        TransferMode::Move => Operation::Move,
+    };
+    let entry = ActivityEntry::new(operation, Via::FilteredTransfer)
+        .from(from.url(from.path()))
+        .to(to.url(to.path()));
+    let result = transfer_one(from, to, mode);
+    let entry = match &result {
+        Ok(bytes) => entry.bytes(*bytes),
+        Err(_) => entry,
+    };
+    log.record(entry, result)
+}
+
+fn execute(src: &Location, dest_root: &Location, plan: &TransferPlan, mode: TransferMode, log: &ActivityLog) -> TransferResult {
+    let mut result = TransferResult {
+        destination: plan.destination.clone(),
+        transferred: 0,
+        filtered: plan.filtered.len(),
+        bytes: 0,
+        conflicts: Vec::new(),
+        errors: Vec::new(),
+    };
+    let mut record = |path: &str, outcome: Result<u64, FsError>| match outcome {
+        Ok(bytes) => {
+            result.transferred += 1;
+            result.bytes += bytes;
+        }
+        Err(e) if e.code == ErrorCode::AlreadyExists => result.conflicts.push(path.to_string()),
+        Err(e) => result.errors.push(format!("{}: {}", path, e)),
+    };
+
+    if !src.fs().metadata(src.path()).is_ok_and(|m| m.is_dir) {
+        record(&plan.source, transfer_logged(src, dest_root, mode, log));
+        return result;
+    }
+
+    if let Err(e) = dest_root.fs().create_dir_all(dest_root.path()) {
+        result.errors.push(format!("{}: {}", plan.destination, e));
+        return result;
+    }
+
+    for file in &plan.files {
+        let from = src.with_path(join_key(src.path(), &file.path));
+        let to = dest_root.with_path(join_key(dest_root.path(), &file.path));
+        record(&file.path, transfer_logged(&from, &to, mode, log));
+    }
+
+    for folder in &plan.empty_folders {
+        let to = join_key(dest_root.path(), folder);
+        if let Err(e) = dest_root.fs().create_dir_all(&to) {
+            result.errors.push(format!("{}: {}", folder, e));
+        }
+    }
+
+    if mode == TransferMode::Move {
+        remove_emptied_dirs(src, plan);
+    }
+
+    result
+}
+
+// --- Commands ---
+
+/// What a filtered transfer would do; `mode` (default copy) decides how much
+/// space it needs
+#[tauri::command]
+pub async fn plan_transfer<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    src: String,
+    dest_folder: String,
+    filters: Option<FilterRules>,
+    mode: Option<TransferMode>,
+) -> Result<TransferPlan, FsError> {
+    spawn_blocking(move || {
+        let src = remote::existing(&app_handle, &src)?;
+        let dest_folder = remote::existing(&app_handle, &dest_folder)?;
+        let rules = filters.unwrap_or_default();
+        build_plan(&src, &dest_folder, &rules, mode.unwrap_or(TransferMode::Copy))
+    })
+    .await
+    .map_err(|e| FsError::from(e.to_string()))?
+}
+
+#[tauri::command]
+pub async fn transfer_filtered<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    src: String,
+    dest_folder: String,
+    mode: TransferMode,
+    filters: Option<FilterRules>,
+) -> Result<TransferResult, FsError> {
+    let log = ActivityLog::from_app(&app_handle)?;
+    spawn_blocking(move || {
+        // Moving takes the source away, so it can't be an approved root itself
+        let src = match mode {
+            TransferMode::Copy => remote::existing(&app_handle, &src)?,
+            TransferMode::Move => remote::entry(&app_handle, &src)?,
+        };
+        let dest_folder = remote::existing(&app_handle, &dest_folder)?;
+        let rules = filters.unwrap_or_default();
+        let plan = build_plan(&src, &dest_folder, &rules, mode)?;
+        if let Some(space) = plan.space.as_ref().filter(|s| !s.enough) {
+            return Err(space.error());
+        }
+        let dest_root = destination_root(&src, &dest_folder)?;
+
+        // A single file that was filtered out is simply left where it is.
+        let is_file = !src.fs().metadata(src.path())?.is_dir;
+        if is_file && plan.files.is_empty() {
+            return Ok(TransferResult {
+                destination: plan.destination,
+                transferred: 0,
+                filtered: plan.filtered.len(),
+                bytes: 0,
+                conflicts: Vec::new(),
+                errors: Vec::new(),
+            });
+        }
+
+        Ok(execute(&src, &dest_root, &plan, mode, &log))
+    })
+    .await
+    .map_err(|e| FsError::from(e.to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use filecanvas_lib::vfs::{FileSystem, MemoryFs, RemoteFs};
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    fn compile(rules: FilterRules) -> CompiledFilter {
+        rules.compile().unwrap()
+    }
+
+    /// Rule that rejects a file of `size` bytes modified `days` ago
+    fn check(filter: &CompiledFilter, rel: &str, size: u64, days: u64) -> Option<String> {
+        let name = rel.rsplit('/').next().unwrap();
+        let modified = filter.now - Duration::from_secs(days * DAY.as_secs());
+        filter.check_entry(name, rel, size, Some(modified))
+    }
+
+    #[test]
+    fn globs_match_names_and_relative_paths() {
+        let filter = compile(FilterRules {
+            include: vec!["*.pdf".into(), "reports/**".into()],
+            exclude: vec!["*.tmp".into(), "drafts/*".into()],
+            ..FilterRules::default()
+        });
+        assert_eq!(check(&filter, "a.pdf", 1, 0), None);
+        assert_eq!(check(&filter, "reports/q1.xlsx", 1, 0), None);
+        assert_eq!(check(&filter, "notes.txt", 1, 0).unwrap(), "include: *.pdf, reports/**");
+        // Exclude wins over include
+        assert_eq!(check(&filter, "reports/q1.tmp", 1, 0).unwrap(), "exclude: *.tmp");
+        assert_eq!(check(&filter, "drafts/b.pdf", 1, 0).unwrap(), "exclude: drafts/*");
+
+        assert_eq!(filter.check_dir("drafts", "drafts"), None);
+        let prune = compile(FilterRules { exclude: vec!["node_modules".into()], ..FilterRules::default() });
+        assert_eq!(prune.check_dir("node_modules", "app/node_modules").unwrap(), "exclude: node_modules");
+        assert!(FilterRules { include: vec!["[".into()], ..FilterRules::default() }.compile().is_err());
+    }
+
+    #[test]
+    fn extensions_ignore_case_and_dots() {
+        let filter = compile(FilterRules {
+            extensions: vec![".JPG".into(), " png ".into(), "".into()],
+            ..FilterRules::default()
+        });
+        assert_eq!(check(&filter, "photo.jpg", 1, 0), None);
+        assert_eq!(check(&filter, "photo.PNG", 1, 0), None);
+        assert_eq!(check(&filter, "photo.gif", 1, 0).unwrap(), "extensions: jpg, png");
+        assert!(check(&filter, "README", 1, 0).is_some());
+    }
+
+    #[test]
+    fn size_and_age_limits_are_inclusive() {
+        let sized = compile(FilterRules { min_size: Some(10), max_size: Some(20), ..FilterRules::default() });
+        assert_eq!(check(&sized, "a", 10, 0), None);
+        assert_eq!(check(&sized, "a", 20, 0), None);
+        assert_eq!(check(&sized, "a", 9, 0).unwrap(), "min size: 10 bytes");
+        assert_eq!(check(&sized, "a", 21, 0).unwrap(), "max size: 20 bytes");
+
+        let aged = compile(FilterRules {
+            older_than_days: Some(7),
+            newer_than_days: Some(30),
+            ..FilterRules::default()
+        });
+        assert_eq!(check(&aged, "a", 1, 7), None);
+        assert_eq!(check(&aged, "a", 1, 30), None);
+        assert_eq!(check(&aged, "a", 1, 6).unwrap(), "older than: 7 days");
+        assert_eq!(check(&aged, "a", 1, 31).unwrap(), "newer than: 30 days");
+        // Without a time the file counts as brand new
+        assert!(aged.check_entry("a", "a", 1, None).is_some());
+    }
+
+    #[test]
+    fn the_name_regex_only_sees_the_name() {
+        let filter = compile(FilterRules { name_regex: Some(r"^IMG_\d+".into()), ..FilterRules::default() });
+        assert_eq!(check(&filter, "2024/IMG_0001.jpg", 1, 0), None);
+        assert_eq!(check(&filter, "IMG_0001/notes.txt", 1, 0).unwrap(), r"name regex: ^IMG_\d+");
+
+        let blank = FilterRules { name_regex: Some("  ".into()), ..FilterRules::default() };
+        assert!(blank.is_empty());
+        assert!(FilterRules { name_regex: Some("(".into()), ..FilterRules::default() }.compile().is_err());
+    }
+
+    #[test]
+    fn plans_report_what_was_filtered_out_and_why() {
+        let fs = MemoryFs::new();
+        for (path, size) in [("/src/a.pdf", 5), ("/src/big.pdf", 500), ("/src/b.txt", 5), ("/src/cache/x.pdf", 5)] {
+            fs.add_file(path, vec![0u8; size]).unwrap();
+        }
+        fs.create_dir_all(Path::new("/src/empty")).unwrap();
+        fs.create_dir(Path::new("/dest")).unwrap();
+        let fs: Arc<dyn RemoteFs> = Arc::new(fs);
+        let src = Location::Remote { fs, path: PathBuf::from("/src") };
+        let dest = src.with_path(PathBuf::from("/dest"));
+
+        let rules = FilterRules {
+            exclude: vec!["cache".into()],
+            extensions: vec!["pdf".into()],
+            max_size: Some(100),
+            ..FilterRules::default()
+        };
+        let plan = build_plan(&src, &dest, &rules, TransferMode::Copy).unwrap();
+        assert_eq!(plan.destination, "memory:///dest/src");
+        let files: Vec<&str> = plan.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(files, ["a.pdf"]);
+        assert_eq!(plan.total_bytes, 5);
+        assert_eq!(plan.empty_folders, ["empty"]);
+
+        let mut filtered: Vec<(&str, bool, &str)> =
+            plan.filtered.iter().map(|f| (f.path.as_str(), f.is_directory, f.rule.as_str())).collect();
+        filtered.sort();
+        assert_eq!(
+            filtered,
+            [
+                ("b.txt", false, "extensions: pdf"),
+                ("big.pdf", false, "max size: 100 bytes"),
+                ("cache", true, "exclude: cache"),
+            ]
+        );
+        assert_eq!(planned_bytes(&src, &rules).unwrap(), 5);
+    }
+}
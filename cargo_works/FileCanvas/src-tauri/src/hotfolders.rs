@@ -0,0 +1,871 @@
+use crate::activity::{self, ActivityEntry, ActivityLog, Operation, Via};
+use crate::filters::{CompiledFilter, FilterRules};
+use crate::store::{self, now_ms};
+use chrono::Local;
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{Manager, Runtime, State};
+
+const RULES_FILE: &str = "hot_folders.json";
+const ACTIVITY_FILE: &str = "hot_folders_activity.jsonl";
+const LOCK_FILE: &str = "hot_folders.lock";
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+// Events for files we just wrote ourselves are ignored for this long, so a
+// rename or a copy into another watched folder doesn't trigger again.
+const PRODUCED_GRACE: Duration = Duration::from_secs(30);
+// The running watcher rewrites its lock this often. Where the owner can't be
+// asked directly, a lock left alone for `STALE_LOCK` belongs to a dead process.
+const LOCK_REFRESH: Duration = Duration::from_secs(30);
+#[cfg(not(unix))]
+const STALE_LOCK: Duration = Duration::from_secs(120);
+// A lock without a pid yet is being written by the process that created it.
+const LOCK_WRITE_GRACE: Duration = Duration::from_secs(5);
+// Same identifier as tauri.conf.json, used when running without the GUI.
+const APP_IDENTIFIER: &str = "com.butterman.filecanvas";
+
+// --- Data Structures ---
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HotAction {
+    Move { destination: String },
+    Copy { destination: String },
+    /// New name built from `{stem}`, `{ext}`, `{name}`, `{date}` and `{time}`.
+    Rename { template: String },
+    /// Program started directly (no shell). Arguments may use `{path}`,
+    /// `{dir}`, `{name}` and `{stem}`.
+    Command { program: String, args: Vec<String> },
+}
+
+fn default_settle_secs() -> u64 {
+    3
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotFolderRule {
+    pub id: String,
+    pub name: String,
+    pub folder: String,
+    #[serde(default)]
+    pub recursive: bool,
+    #[serde(default)]
+    pub filters: FilterRules,
+    pub action: HotAction,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// How long a file's size must stay unchanged before it is handled.
+    #[serde(default = "default_settle_secs")]
+    pub settle_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotFolderActivity {
+    pub timestamp: u64,
+    pub rule_id: String,
+    pub rule_name: String,
+    pub path: String,
+    pub action: String,
+    pub success: bool,
+    pub destination: Option<String>,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HotFolderStatus {
+    pub running: bool,
+    /// Another FileCanvas process (usually `--hot-folders`) owns the watchers.
+    pub running_elsewhere: bool,
+    pub rule_count: usize,
+}
+
+/// Where rules, the activity log and the lock live. The GUI takes these from
+/// the Tauri path resolver; the headless service derives the same locations.
+#[derive(Debug, Clone)]
+pub struct HotFolderPaths {
+    pub rules: PathBuf,
+    pub activity: PathBuf,
+    /// The shared activity log (see activity.rs), next to `activity`
+    pub activity_log: PathBuf,
+    pub lock: PathBuf,
+}
+
+struct RunningService {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+/// Managed Tauri state holding the watcher thread while it runs in the GUI.
+#[derive(Default)]
+pub struct HotFolderService {
+    running: Mutex<Option<RunningService>>,
+}
+
+struct ActiveRule {
+    rule: HotFolderRule,
+    folder: PathBuf,
+    filter: CompiledFilter,
+}
+
+struct Pending {
+    size: u64,
+    modified: Option<SystemTime>,
+    stable_since: Instant,
+}
+
+// --- Paths & persistence ---
+
+impl HotFolderPaths {
+    pub fn from_app<R: Runtime>(app_handle: &tauri::AppHandle<R>) -> Result<Self, String> {
+        let config = app_handle.path().app_config_dir().map_err(|e| e.to_string())?;
+        let data = app_handle.path().app_data_dir().map_err(|e| e.to_string())?;
+        Self::in_dirs(config, data)
+    }
+
+    /// Mirrors Tauri's `app_config_dir` / `app_data_dir` for the headless mode.
+    pub fn headless() -> Result<Self, String> {
+        let home = std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .map(PathBuf::from)
+            .ok_or("Could not determine the home directory")?;
+
+        let (config, data) = if cfg!(target_os = "macos") {
+            let dir = home.join("Library/Application Support");
+            (dir.clone(), dir)
+        } else if cfg!(windows) {
+            let dir = std::env::var_os("APPDATA")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| home.join("AppData\\Roaming"));
+            (dir.clone(), dir)
+        } else {
+            let config = std::env::var_os("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| home.join(".config"));
+            let data = std::env::var_os("XDG_DATA_HOME")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| home.join(".local/share"));
+            (config, data)
+        };
+
+        Self::in_dirs(config.join(APP_IDENTIFIER), data.join(APP_IDENTIFIER))
+    }
+
+    fn in_dirs(config: PathBuf, data: PathBuf) -> Result<Self, String> {
+        fs::create_dir_all(&config).map_err(|e| e.to_string())?;
+        fs::create_dir_all(&data).map_err(|e| e.to_string())?;
+        Ok(HotFolderPaths {
+            rules: config.join(RULES_FILE),
+            activity: data.join(ACTIVITY_FILE),
+            activity_log: data.join(activity::LOG_FILE),
+            lock: data.join(LOCK_FILE),
+        })
+    }
+}
+
+fn load_rules(path: &Path) -> Vec<HotFolderRule> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn append_activity(path: &Path, entry: &HotFolderActivity) {
+    let Ok(line) = serde_json::to_string(entry) else { return };
+    if let Ok(mut file) = fs::OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(file, "{}", line);
+    }
+}
+
+fn read_activity(path: &Path, limit: usize) -> Vec<HotFolderActivity> {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    content
+        .lines()
+        .rev()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .take(limit)
+        .collect()
+}
+
+fn mtime_of(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+// --- Single-instance lock ---
+
+#[cfg(unix)]
+fn process_alive(pid: u32, _lock: &Path) -> bool {
+    // pid 0 would address our own process group
+    let Ok(pid) = libc::pid_t::try_from(pid) else { return false };
+    if pid <= 0 {
+        return false;
+    }
+    // Signal 0 only checks the process exists; EPERM means it belongs to
+    // another user but is still there
+    // SAFETY: kill with signal 0 sends nothing
+    let sent = unsafe { libc::kill(pid, 0) } == 0;
+    sent || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: u32, lock: &Path) -> bool {
+    // No process API to ask; the owner keeps the lock fresh while it runs
+    mtime_of(lock)
+        .and_then(|t| t.elapsed().ok())
+        .is_some_and(|age| age < STALE_LOCK)
+}
+
+fn lock_owner(lock: &Path) -> Option<u32> {
+    let pid: u32 = fs::read_to_string(lock).ok()?.trim().parse().ok()?;
+    process_alive(pid, lock).then_some(pid)
+}
+
+/// Nobody holds `lock`: its process is gone, or it never got a pid written
+fn lock_is_stale(lock: &Path, content: &str) -> bool {
+    if content.trim().parse::<u32>().is_ok() {
+        return lock_owner(lock).is_none();
+    }
+    mtime_of(lock)
+        .and_then(|t| t.elapsed().ok())
+        .is_some_and(|age| age >= LOCK_WRITE_GRACE)
+}
+
+/// Take the lock by creating the file, so two processes starting at once
+/// can't both win. A stale lock is removed and creating it is tried again.
+fn acquire_lock(lock: &Path) -> Result<(), String> {
+    let me = std::process::id();
+    for _ in 0..3 {
+        match fs::OpenOptions::new().write(true).create_new(true).open(lock) {
+            Ok(mut file) => return write!(file, "{}", me).map_err(|e| e.to_string()),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e.to_string()),
+        }
+
+        let content = fs::read_to_string(lock).unwrap_or_default();
+        match lock_owner(lock) {
+            Some(pid) if pid == me => return Ok(()),
+            Some(pid) => return Err(format!("Hot folders are already running in process {}", pid)),
+            None if !lock_is_stale(lock, &content) => {
+                return Err("Hot folders are being started by another process".to_string())
+            }
+            None => {
+                // Only remove the lock that was judged stale, not one another
+                // process has written since
+                if fs::read_to_string(lock).unwrap_or_default() == content {
+                    let _ = fs::remove_file(lock);
+                }
+            }
+        }
+    }
+    Err(format!("Could not take the hot folder lock {}", lock.display()))
+}
+
+fn release_lock(lock: &Path) {
+    if lock_owner(lock) == Some(std::process::id()) {
+        let _ = fs::remove_file(lock);
+    }
+}
+
+// --- Actions ---
+
+fn split_name(path: &Path) -> (String, String, String) {
+    let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let ext = path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+    (name, stem, ext)
+}
+
+/// `report.pdf` → `report (1).pdf`, `report (2).pdf`, … until a free name is found.
+fn free_destination(dir: &Path, name: &str) -> PathBuf {
+    let candidate = dir.join(name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    let (_, stem, ext) = split_name(Path::new(name));
+    let ext = if ext.is_empty() { String::new() } else { format!(".{}", ext) };
+    (1..)
+        .map(|n| dir.join(format!("{} ({}){}", stem, n, ext)))
+        .find(|p| !p.exists())
+        .unwrap_or(candidate)
+}
+
+fn expand_placeholders(template: &str, path: &Path) -> String {
+    let (name, stem, ext) = split_name(path);
+    let now = Local::now();
+    template
+        .replace("{path}", &path.to_string_lossy())
+        .replace("{dir}", &path.parent().unwrap_or(Path::new("")).to_string_lossy())
+        .replace("{name}", &name)
+        .replace("{stem}", &stem)
+        .replace("{ext}", &ext)
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{time}", &now.format("%H%M%S").to_string())
+}
+
+fn action_operation(action: &HotAction) -> Operation {
+    match action {
+        HotAction::Move { .. } => Operation::Move,
+        HotAction::Copy { .. } => Operation::Copy,
+        HotAction::Rename { .. } => Operation::Rename,
+        HotAction::Command { .. } => Operation::Command,
+    }
+}
+
+fn action_label(action: &HotAction) -> &'static str {
+    match action {
+        HotAction::Move { .. } => "move",
+        HotAction::Copy { .. } => "copy",
+        HotAction::Rename { .. } => "rename",
+        HotAction::Command { .. } => "command",
+    }
+}
+
+/// Runs the rule's action on a settled file. Returns the destination (if the
+/// action produced one) or a description of what went wrong.
+fn run_action(action: &HotAction, path: &Path) -> Result<Option<PathBuf>, String> {
+    let name = path.file_name().ok_or("Invalid path")?.to_string_lossy().to_string();
+
+    match action {
+        HotAction::Move { destination } => {
+            let dir = PathBuf::from(destination);
+            fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+            let dest = free_destination(&dir, &name);
+            match fs::rename(path, &dest) {
+                // rename can't cross filesystems, copy + delete instead
+                Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+                    fs::copy(path, &dest).map_err(|e| e.to_string())?;
+                    fs::remove_file(path).map_err(|e| e.to_string())?;
+                }
+                result => result.map_err(|e| e.to_string())?,
+            }
+            Ok(Some(dest))
+        }
+        HotAction::Copy { destination } => {
+            let dir = PathBuf::from(destination);
+            fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+            let dest = free_destination(&dir, &name);
+            fs::copy(path, &dest).map_err(|e| e.to_string())?;
+            Ok(Some(dest))
+        }
+        HotAction::Rename { template } => {
+            let new_name = expand_placeholders(template, path);
+            if new_name.is_empty() || new_name.contains(['/', '\\']) {
+                return Err(format!("Invalid name from template: {}", new_name));
+            }
+            if new_name == name {
+                return Ok(None);
+            }
+            let dir = path.parent().ok_or("Invalid path")?;
+            let dest = free_destination(dir, &new_name);
+            fs::rename(path, &dest).map_err(|e| e.to_string())?;
+            Ok(Some(dest))
+        }
+        HotAction::Command { program, args } => {
+            let output = Command::new(program)
+                .args(args.iter().map(|a| expand_placeholders(a, path)))
+                .output()
+                .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+            if output.status.success() {
+                Ok(None)
+            } else {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let tail: String = stderr.lines().rev().take(3).collect::<Vec<_>>().join(" | ");
+                Err(format!("{} exited with {}: {}", program, output.status, tail))
+            }
+        }
+    }
+}
+
+// --- Watcher loop ---
+
+impl Pending {
+    fn new() -> Self {
+        Pending {
+            size: u64::MAX,
+            modified: None,
+            stable_since: Instant::now(),
+        }
+    }
+
+    /// Note the file's size and mtime; true when they differ from last time
+    fn changed(&mut self, size: u64, modified: Option<SystemTime>, now: Instant) -> bool {
+        if size == self.size && modified == self.modified {
+            return false;
+        }
+        self.size = size;
+        self.modified = modified;
+        self.stable_since = now;
+        true
+    }
+
+    fn settled(&self, settle: Duration, now: Instant) -> bool {
+        now.saturating_duration_since(self.stable_since) >= settle
+    }
+}
+
+fn compile_rules(rules: Vec<HotFolderRule>) -> Vec<ActiveRule> {
+    rules
+        .into_iter()
+        .filter(|r| r.enabled)
+        .filter_map(|rule| {
+            let filter = rule.filters.compile().ok()?;
+            let folder = PathBuf::from(&rule.folder);
+            folder.is_dir().then_some(ActiveRule { rule, folder, filter })
+        })
+        .collect()
+}
+
+fn matching_rule<'a>(rules: &'a [ActiveRule], path: &Path) -> Option<&'a ActiveRule> {
+    let name = path.file_name()?.to_string_lossy().to_string();
+    if name.starts_with('.') {
+        return None;
+    }
+    let metadata = fs::metadata(path).ok()?;
+    if !metadata.is_file() {
+        return None;
+    }
+
+    rules.iter().find(|r| {
+        let in_folder = if r.rule.recursive {
+            path.starts_with(&r.folder)
+        } else {
+            path.parent() == Some(r.folder.as_path())
+        };
+        let rel = path.strip_prefix(&r.folder).unwrap_or(path).to_string_lossy().replace('\\', "/");
+        in_folder && r.filter.check_file(&name, &rel, &metadata).is_none()
+    })
+}
+
+/// Watches every enabled rule's folder until `stop` is set. Files are only
+/// handled once their size and mtime stopped changing for `settle_secs`.
+/// The rules file is re-read whenever it changes on disk.
+fn rewatch(watcher: &mut notify::RecommendedWatcher, rules: &[ActiveRule], watched: &mut Vec<PathBuf>) {
+    for folder in watched.drain(..) {
+        let _ = watcher.unwatch(&folder);
+    }
+    for r in rules {
+        let mode = if r.rule.recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        if watcher.watch(&r.folder, mode).is_ok() {
+            watched.push(r.folder.clone());
+        }
+    }
+}
+
+fn watch_loop(paths: HotFolderPaths, stop: Arc<AtomicBool>) -> Result<(), String> {
+    let (tx, rx) = channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(|e| e.to_string())?;
+
+    let audit = ActivityLog::at(paths.activity_log.clone());
+    let mut rules_mtime = mtime_of(&paths.rules);
+    let mut rules = compile_rules(load_rules(&paths.rules));
+    let mut watched: Vec<PathBuf> = Vec::new();
+    let mut pending: HashMap<PathBuf, Pending> = HashMap::new();
+    // Files we produced ourselves (renames, copies into watched folders).
+    let mut produced: HashMap<PathBuf, Instant> = HashMap::new();
+
+    rewatch(&mut watcher, &rules, &mut watched);
+    let mut lock_refreshed = Instant::now();
+
+    while !stop.load(Ordering::Relaxed) {
+        match rx.recv_timeout(POLL_INTERVAL) {
+            Ok(Ok(event)) => {
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    for path in event.paths {
+                        if produced.contains_key(&path) {
+                            continue;
+                        }
+                        pending.entry(path).or_insert_with(Pending::new);
+                    }
+                }
+            }
+            Ok(Err(_)) | Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        produced.retain(|_, at| at.elapsed() < PRODUCED_GRACE);
+
+        if lock_refreshed.elapsed() >= LOCK_REFRESH {
+            let _ = fs::write(&paths.lock, std::process::id().to_string());
+            lock_refreshed = Instant::now();
+        }
+
+        let current_mtime = mtime_of(&paths.rules);
+        if current_mtime != rules_mtime {
+            rules_mtime = current_mtime;
+            rules = compile_rules(load_rules(&paths.rules));
+            rewatch(&mut watcher, &rules, &mut watched);
+        }
+
+        let mut ready = Vec::new();
+        pending.retain(|path, p| {
+            let Ok(metadata) = fs::metadata(path) else {
+                return false; // gone again (temp file, moved away)
+            };
+            if p.changed(metadata.len(), metadata.modified().ok(), Instant::now()) {
+                return true;
+            }
+            let settle = matching_rule(&rules, path)
+                .map(|r| Duration::from_secs(r.rule.settle_secs))
+                .unwrap_or_default();
+            if p.settled(settle, Instant::now()) {
+                ready.push(path.clone());
+                return false;
+            }
+            true
+        });
+
+        for path in ready {
+            let Some(active) = matching_rule(&rules, &path) else { continue };
+            let bytes = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            let result = run_action(&active.rule.action, &path);
+
+            let (success, destination, detail) = match result {
+                Ok(dest) => (true, dest, None),
+                Err(e) => (false, None, Some(e)),
+            };
+            if let Some(dest) = &destination {
+                produced.insert(dest.clone(), Instant::now());
+            }
+            // A command may rewrite the file in place; that must not run it again
+            if matches!(active.rule.action, HotAction::Command { .. }) {
+                produced.insert(path.clone(), Instant::now());
+            }
+
+            let operation = action_operation(&active.rule.action);
+            // A rename whose template gives the same name did nothing
+            if !(success && destination.is_none() && operation == Operation::Rename) {
+                let mut entry = ActivityEntry::new(operation, Via::HotFolder)
+                    .from(path.to_string_lossy())
+                    .bytes(bytes);
+                if let Some(dest) = &destination {
+                    entry = entry.to(dest.to_string_lossy());
+                }
+                entry.success = success;
+                entry.error = detail.clone();
+                audit.append(&entry);
+            }
+
+            append_activity(
+                &paths.activity,
+                &HotFolderActivity {
+                    timestamp: now_ms(),
+                    rule_id: active.rule.id.clone(),
+                    rule_name: active.rule.name.clone(),
+                    path: path.to_string_lossy().to_string(),
+                    action: action_label(&active.rule.action).to_string(),
+                    success,
+                    destination: destination.map(|d| d.to_string_lossy().to_string()),
+                    detail,
+                },
+            );
+        }
+    }
+
+    Ok(())
+}
+
+impl HotFolderService {
+    pub fn start(&self, paths: HotFolderPaths) -> Result<(), String> {
+        let mut running = self.running.lock().map_err(|e| e.to_string())?;
+        if running.as_ref().is_some_and(|r| !r.handle.is_finished()) {
+            return Ok(());
+        }
+        acquire_lock(&paths.lock)?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let handle = std::thread::spawn(move || {
+            let lock = paths.lock.clone();
+            if let Err(e) = watch_loop(paths, thread_stop) {
+                eprintln!("hot folders stopped: {}", e);
+            }
+            release_lock(&lock);
+        });
+
+        *running = Some(RunningService { stop, handle });
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        let running = self.running.lock().ok().and_then(|mut r| r.take());
+        if let Some(service) = running {
+            service.stop.store(true, Ordering::Relaxed);
+            let _ = service.handle.join();
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.running
+            .lock()
+            .map(|r| r.as_ref().is_some_and(|s| !s.handle.is_finished()))
+            .unwrap_or(false)
+    }
+}
+
+/// Entry point for `filecanvas --hot-folders`: runs the watchers without the
+/// GUI until the process is terminated.
+pub fn run_headless() -> Result<(), String> {
+    let paths = HotFolderPaths::headless()?;
+    let rules = load_rules(&paths.rules);
+    println!(
+        "Watching {} hot folder rule(s) from {}",
+        rules.iter().filter(|r| r.enabled).count(),
+        paths.rules.display()
+    );
+
+    acquire_lock(&paths.lock)?;
+    let lock = paths.lock.clone();
+    let result = watch_loop(paths, Arc::new(AtomicBool::new(false)));
+    release_lock(&lock);
+    result
+}
+
+// --- Commands ---
+
+#[tauri::command]
+pub async fn list_hot_folder_rules<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+) -> Result<Vec<HotFolderRule>, String> {
+    let paths = HotFolderPaths::from_app(&app_handle)?;
+    Ok(load_rules(&paths.rules))
+}
+
+/// Replaces the whole rule list. A running watcher (here or in the headless
+/// process) picks the change up from the rules file on its next poll.
+#[tauri::command]
+pub async fn save_hot_folder_rules<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    rules: Vec<HotFolderRule>,
+) -> Result<(), String> {
+    for rule in &rules {
+        if !Path::new(&rule.folder).is_dir() {
+            return Err(format!("Rule \"{}\": {} is not a folder", rule.name, rule.folder));
+        }
+        // The headless watcher has no UI to ask, so rules may only touch
+        // folders that were approved here
+        crate::path_policy::existing(&app_handle, &rule.folder)
+            .map_err(|e| format!("Rule \"{}\": {}", rule.name, e))?;
+        if let HotAction::Move { destination } | HotAction::Copy { destination } = &rule.action {
+            crate::path_policy::target(&app_handle, destination)
+                .map_err(|e| format!("Rule \"{}\": {}", rule.name, e))?;
+        }
+        rule.filters
+            .compile()
+            .map_err(|e| format!("Rule \"{}\": {}", rule.name, e))?;
+    }
+    let paths = HotFolderPaths::from_app(&app_handle)?;
+    store::save_json_atomic(&paths.rules, &rules)
+}
+
+#[tauri::command]
+pub async fn start_hot_folders<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    service: State<'_, HotFolderService>,
+) -> Result<HotFolderStatus, String> {
+    let paths = HotFolderPaths::from_app(&app_handle)?;
+    service.start(paths.clone())?;
+    Ok(HotFolderStatus {
+        running: true,
+        running_elsewhere: false,
+        rule_count: load_rules(&paths.rules).len(),
+    })
+}
+
+#[tauri::command]
+pub async fn stop_hot_folders(service: State<'_, HotFolderService>) -> Result<(), String> {
+    service.stop();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn hot_folders_status<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    service: State<'_, HotFolderService>,
+) -> Result<HotFolderStatus, String> {
+    let paths = HotFolderPaths::from_app(&app_handle)?;
+    let running = service.is_running();
+    Ok(HotFolderStatus {
+        running,
+        running_elsewhere: !running && lock_owner(&paths.lock).is_some(),
+        rule_count: load_rules(&paths.rules).len(),
+    })
+}
+
+#[tauri::command]
+pub async fn hot_folder_activity<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    limit: Option<usize>,
+) -> Result<Vec<HotFolderActivity>, String> {
+    let paths = HotFolderPaths::from_app(&app_handle)?;
+    Ok(read_activity(&paths.activity, limit.unwrap_or(200)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("filecanvas-hot-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn rule(folder: &Path, recursive: bool, filters: FilterRules) -> HotFolderRule {
+        HotFolderRule {
+            id: "r1".into(),
+            name: "Rule".into(),
+            folder: folder.to_string_lossy().to_string(),
+            recursive,
+            filters,
+            action: HotAction::Rename { template: "{name}".into() },
+            enabled: true,
+            settle_secs: 3,
+        }
+    }
+
+    #[test]
+    fn free_destination_numbers_taken_names() {
+        let dir = scratch("free");
+        assert_eq!(free_destination(&dir, "report.pdf"), dir.join("report.pdf"));
+        fs::write(dir.join("report.pdf"), "").unwrap();
+        fs::write(dir.join("report (1).pdf"), "").unwrap();
+        assert_eq!(free_destination(&dir, "report.pdf"), dir.join("report (2).pdf"));
+        fs::write(dir.join("README"), "").unwrap();
+        assert_eq!(free_destination(&dir, "README"), dir.join("README (1)"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rename_templates_fill_in_the_name_parts() {
+        let dir = scratch("rename");
+        let file = dir.join("scan.PDF");
+        fs::write(&file, "scan").unwrap();
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        assert_eq!(
+            expand_placeholders("{date} {stem}.{ext} in {dir}", &file),
+            format!("{} scan.PDF in {}", today, dir.display())
+        );
+
+        let template = |t: &str| HotAction::Rename { template: t.into() };
+        assert!(run_action(&template("../{name}"), &file).is_err());
+        assert!(run_action(&template(""), &file).is_err());
+        assert_eq!(run_action(&template("{name}"), &file).unwrap(), None);
+
+        let renamed = run_action(&template("{date}-{name}"), &file).unwrap().unwrap();
+        assert_eq!(renamed, dir.join(format!("{}-scan.PDF", today)));
+        assert_eq!(fs::read_to_string(&renamed).unwrap(), "scan");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn moves_never_overwrite_and_report_errors() {
+        let dir = scratch("move");
+        let dest = dir.join("out");
+        fs::create_dir_all(&dest).unwrap();
+        fs::write(dest.join("a.txt"), "old").unwrap();
+        fs::write(dir.join("a.txt"), "new").unwrap();
+        let action = HotAction::Move { destination: dest.to_string_lossy().to_string() };
+
+        let moved = run_action(&action, &dir.join("a.txt")).unwrap().unwrap();
+        assert_eq!(moved, dest.join("a (1).txt"));
+        assert_eq!(fs::read_to_string(dest.join("a.txt")).unwrap(), "old");
+        assert!(!dir.join("a.txt").exists());
+
+        assert!(run_action(&action, &dir.join("missing.txt")).is_err());
+        assert!(!dest.join("missing.txt").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rules_match_files_in_their_folder_that_pass_the_filters() {
+        let dir = scratch("match");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        for name in ["a.pdf", "b.txt", ".hidden.pdf", "sub/c.pdf"] {
+            fs::write(dir.join(name), name).unwrap();
+        }
+        let pdfs = FilterRules { extensions: vec!["pdf".into()], ..FilterRules::default() };
+
+        let flat = compile_rules(vec![rule(&dir, false, pdfs.clone())]);
+        assert!(matching_rule(&flat, &dir.join("a.pdf")).is_some());
+        assert!(matching_rule(&flat, &dir.join("b.txt")).is_none());
+        assert!(matching_rule(&flat, &dir.join(".hidden.pdf")).is_none());
+        assert!(matching_rule(&flat, &dir.join("sub/c.pdf")).is_none());
+        assert!(matching_rule(&flat, &dir.join("sub")).is_none());
+
+        let deep = compile_rules(vec![rule(&dir, true, pdfs)]);
+        assert!(matching_rule(&deep, &dir.join("sub/c.pdf")).is_some());
+
+        // Disabled rules and missing folders are dropped
+        let disabled = HotFolderRule { enabled: false, ..rule(&dir, false, FilterRules::default()) };
+        let missing = rule(&dir.join("gone"), false, FilterRules::default());
+        assert!(compile_rules(vec![disabled, missing]).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn files_settle_once_size_and_mtime_stop_changing() {
+        let start = Instant::now();
+        let settle = Duration::from_secs(3);
+        let modified = Some(SystemTime::now());
+        let mut pending = Pending::new();
+
+        // The first look always counts as a change
+        assert!(pending.changed(10, modified, start));
+        assert!(!pending.changed(10, modified, start + Duration::from_secs(1)));
+        assert!(!pending.settled(settle, start + Duration::from_secs(2)));
+        assert!(pending.settled(settle, start + settle));
+
+        // Still growing: the wait starts over
+        assert!(pending.changed(20, modified, start + Duration::from_secs(4)));
+        assert!(!pending.settled(settle, start + Duration::from_secs(6)));
+        assert!(pending.settled(settle, start + Duration::from_secs(7)));
+        assert!(pending.changed(20, None, start + Duration::from_secs(8)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn the_lock_is_only_taken_over_when_stale() {
+        let dir = scratch("lock");
+        let lock = dir.join(LOCK_FILE);
+        let me = std::process::id().to_string();
+
+        acquire_lock(&lock).unwrap();
+        assert_eq!(fs::read_to_string(&lock).unwrap(), me);
+        acquire_lock(&lock).unwrap();
+
+        // pid 1 is always running
+        fs::write(&lock, "1").unwrap();
+        assert!(acquire_lock(&lock).is_err());
+        assert_eq!(fs::read_to_string(&lock).unwrap(), "1");
+
+        // No process has this pid
+        fs::write(&lock, i32::MAX.to_string()).unwrap();
+        acquire_lock(&lock).unwrap();
+        assert_eq!(fs::read_to_string(&lock).unwrap(), me);
+
+        // Just created and not written yet
+        fs::write(&lock, "").unwrap();
+        assert!(acquire_lock(&lock).is_err());
+
+        release_lock(&lock);
+        assert!(lock.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
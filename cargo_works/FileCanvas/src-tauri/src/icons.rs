@@ -0,0 +1,381 @@
+// src-tauri/src/icons.rs
+//
+// Resolves `Icon=` names from desktop entries to image data the webview can
+// show, following the freedesktop Icon Theme spec:
+//   current theme → its Inherits chain → hicolor → /usr/share/pixmaps
+// Within a theme the directory matching the requested size wins, otherwise
+// the one with the closest size.
+//
+// Results are returned as `data:` URLs (PNG or SVG) and cached per name/size.
+// Absolute `Icon=` paths are only read inside the icon folders or when a
+// loaded desktop entry names them; the webview can't pick arbitrary files.
+
+use base64::Engine;
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tauri::async_runtime::spawn_blocking;
+
+const EXTENSIONS: [&str; 2] = ["png", "svg"];
+/// Icons bigger than this are not worth inlining
+const MAX_ICON_BYTES: u64 = 1024 * 1024;
+
+// --- Data Structures ---
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DirType {
+    Fixed,
+    Scalable,
+    Threshold,
+}
+
+#[derive(Debug, Clone)]
+struct ThemeDir {
+    subdir: String,
+    size: u32,
+    scale: u32,
+    kind: DirType,
+    min_size: u32,
+    max_size: u32,
+    threshold: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Theme {
+    /// Every base directory that contains this theme
+    roots: Vec<PathBuf>,
+    dirs: Vec<ThemeDir>,
+    inherits: Vec<String>,
+}
+
+/// (icon name, size) → data URL, with misses cached as None
+type IconCache = HashMap<(String, u32), Option<String>>;
+
+// --- Helpers ---
+
+fn base_dirs() -> Vec<PathBuf> {
+    let home = PathBuf::from(std::env::var("HOME").unwrap_or_default());
+    let data_home = std::env::var("XDG_DATA_HOME")
+        .ok()
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".local/share"));
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    let mut dirs = vec![home.join(".icons"), data_home.join("icons")];
+    dirs.extend(data_dirs.split(':').map(|d| PathBuf::from(d).join("icons")));
+    dirs
+}
+
+/// The user's icon theme from GTK or KDE settings, if configured
+fn current_theme_name() -> Option<String> {
+    let config = std::env::var("XDG_CONFIG_HOME")
+        .ok()
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(std::env::var("HOME").unwrap_or_default()).join(".config"));
+
+    let sources = [
+        (config.join("gtk-4.0/settings.ini"), "Settings", "gtk-icon-theme-name"),
+        (config.join("gtk-3.0/settings.ini"), "Settings", "gtk-icon-theme-name"),
+        (config.join("kdeglobals"), "Icons", "Theme"),
+    ];
+    sources.iter().find_map(|(path, group, key)| {
+        let content = std::fs::read_to_string(path).ok()?;
+        let groups = crate::desktop_entry::parse_groups(&content);
+        let value = groups.get(*group)?.get(*key)?.trim_matches('"').to_string();
+        (!value.is_empty()).then_some(value)
+    })
+}
+
+fn load_theme(name: &str) -> Option<Theme> {
+    let roots: Vec<PathBuf> = base_dirs()
+        .into_iter()
+        .map(|d| d.join(name))
+        .filter(|d| d.is_dir())
+        .collect();
+    // The first index.theme found describes the theme
+    let index = roots.iter().find_map(|r| std::fs::read_to_string(r.join("index.theme")).ok())?;
+    let groups = crate::desktop_entry::parse_groups(&index);
+    let main = groups.get("Icon Theme")?;
+
+    let list = |key: &str| -> Vec<String> {
+        main.get(key)
+            .map(|v| v.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect())
+            .unwrap_or_default()
+    };
+
+    let mut subdirs = list("Directories");
+    subdirs.extend(list("ScaledDirectories"));
+
+    let dirs = subdirs
+        .into_iter()
+        .filter_map(|subdir| {
+            let group = groups.get(&subdir)?;
+            let num = |key: &str| group.get(key).and_then(|v| v.parse::<u32>().ok());
+            let size = num("Size")?;
+            Some(ThemeDir {
+                kind: match group.get("Type").map(|s| s.as_str()) {
+                    Some("Fixed") => DirType::Fixed,
+                    Some("Scalable") => DirType::Scalable,
+                    _ => DirType::Threshold,
+                },
+                scale: num("Scale").unwrap_or(1),
+                min_size: num("MinSize").unwrap_or(size),
+                max_size: num("MaxSize").unwrap_or(size),
+                threshold: num("Threshold").unwrap_or(2),
+                size,
+                subdir,
+            })
+        })
+        .collect();
+
+    Some(Theme {
+        roots,
+        dirs,
+        inherits: list("Inherits"),
+    })
+}
+
+fn theme(name: &str) -> Option<Theme> {
+    static THEMES: OnceLock<Mutex<HashMap<String, Option<Theme>>>> = OnceLock::new();
+    let themes = THEMES.get_or_init(Default::default);
+    let mut themes = themes.lock().ok()?;
+    themes
+        .entry(name.to_string())
+        .or_insert_with(|| load_theme(name))
+        .clone()
+}
+
+impl ThemeDir {
+    fn matches(&self, size: u32, scale: u32) -> bool {
+        if self.scale != scale {
+            return false;
+        }
+        match self.kind {
+            DirType::Fixed => self.size == size,
+            DirType::Scalable => (self.min_size..=self.max_size).contains(&size),
+            DirType::Threshold => {
+                (self.size.saturating_sub(self.threshold)..=self.size.saturating_add(self.threshold)).contains(&size)
+            }
+        }
+    }
+
+    /// Sizes come from index.theme files, so huge values saturate instead
+    /// of overflowing
+    fn distance(&self, size: u32, scale: u32) -> u32 {
+        let want = size.saturating_mul(scale);
+        let (low, high) = match self.kind {
+            DirType::Fixed => (self.size, self.size),
+            DirType::Scalable => (self.min_size, self.max_size),
+            DirType::Threshold => (
+                self.size.saturating_sub(self.threshold),
+                self.size.saturating_add(self.threshold),
+            ),
+        };
+        let (low, high) = (low.saturating_mul(self.scale), high.saturating_mul(self.scale));
+        if want < low {
+            low - want
+        } else {
+            want.saturating_sub(high)
+        }
+    }
+}
+
+fn lookup_in_theme(theme: &Theme, icon: &str, size: u32, scale: u32) -> Option<PathBuf> {
+    let file_in = |dir: &ThemeDir| {
+        theme.roots.iter().find_map(|root| {
+            EXTENSIONS
+                .iter()
+                .map(|ext| root.join(&dir.subdir).join(format!("{}.{}", icon, ext)))
+                .find(|p| p.is_file())
+        })
+    };
+
+    if let Some(found) = theme.dirs.iter().filter(|d| d.matches(size, scale)).find_map(file_in) {
+        return Some(found);
+    }
+
+    theme
+        .dirs
+        .iter()
+        .filter_map(|d| file_in(d).map(|p| (d.distance(size, scale), p)))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, p)| p)
+}
+
+/// Walk the theme and everything it inherits, depth first
+fn find_in_themes(
+    name: &str,
+    icon: &str,
+    size: u32,
+    visited: &mut HashSet<String>,
+) -> Option<PathBuf> {
+    if !visited.insert(name.to_string()) {
+        return None;
+    }
+    let theme = theme(name)?;
+    if let Some(found) = lookup_in_theme(&theme, icon, size, 1) {
+        return Some(found);
+    }
+    theme
+        .inherits
+        .iter()
+        .find_map(|parent| find_in_themes(parent, icon, size, visited))
+}
+
+/// Unthemed icons directly in the base dirs or in /usr/share/pixmaps
+fn lookup_fallback(icon: &str) -> Option<PathBuf> {
+    let mut dirs = base_dirs();
+    dirs.push(PathBuf::from("/usr/share/pixmaps"));
+    dirs.iter().find_map(|dir| {
+        EXTENSIONS
+            .iter()
+            .map(|ext| dir.join(format!("{}.{}", icon, ext)))
+            .find(|p| p.is_file())
+    })
+}
+
+/// Absolute `Icon=` paths named by desktop entries that were loaded
+fn entry_icons() -> &'static Mutex<HashSet<PathBuf>> {
+    static ENTRY_ICONS: OnceLock<Mutex<HashSet<PathBuf>>> = OnceLock::new();
+    ENTRY_ICONS.get_or_init(Default::default)
+}
+
+/// Allow an absolute `Icon=` path outside the icon folders because a loaded
+/// desktop entry names it. Other values are ignored.
+pub fn allow_entry_icon(icon: &str) {
+    let path = Path::new(icon);
+    if path.is_absolute() {
+        if let Ok(mut allowed) = entry_icons().lock() {
+            allowed.insert(path.to_path_buf());
+        }
+    }
+}
+
+/// The webview asks for icons by name, so an absolute path is only read
+/// from the icon and pixmap folders or when a desktop entry named it
+fn allowed_icon_path(path: &Path) -> bool {
+    if path.components().any(|c| c == Component::ParentDir) {
+        return false;
+    }
+    let mut dirs = base_dirs();
+    dirs.push(PathBuf::from("/usr/share/pixmaps"));
+    dirs.iter().any(|dir| path.starts_with(dir))
+        || entry_icons().lock().is_ok_and(|allowed| allowed.contains(path))
+}
+
+/// Path of the best icon file for an `Icon=` value at `size` pixels
+pub fn find_icon(icon: &str, size: u32) -> Option<PathBuf> {
+    if icon.is_empty() {
+        return None;
+    }
+    let path = Path::new(icon);
+    if path.is_absolute() {
+        return (allowed_icon_path(path) && path.is_file()).then(|| path.to_path_buf());
+    }
+    // A name, never a path into some other folder
+    if icon.contains('/') || icon.contains('\\') || icon == ".." {
+        return None;
+    }
+    // Icon names must not carry an extension, but some entries do anyway
+    let icon = icon
+        .strip_suffix(".png")
+        .or_else(|| icon.strip_suffix(".svg"))
+        .unwrap_or(icon);
+
+    let mut visited = HashSet::new();
+    if let Some(name) = current_theme_name() {
+        if let Some(found) = find_in_themes(&name, icon, size, &mut visited) {
+            return Some(found);
+        }
+    }
+    find_in_themes("hicolor", icon, size, &mut visited).or_else(|| lookup_fallback(icon))
+}
+
+fn to_data_url(path: &Path) -> Option<String> {
+    let mime = match path.extension()?.to_str()?.to_lowercase().as_str() {
+        "png" => "image/png",
+        "svg" => "image/svg+xml",
+        _ => return None,
+    };
+    if path.metadata().ok()?.len() > MAX_ICON_BYTES {
+        return None;
+    }
+    let bytes = std::fs::read(path).ok()?;
+    Some(format!(
+        "data:{};base64,{}",
+        mime,
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    ))
+}
+
+/// `data:` URL for an icon, cached (misses included) per name and size
+pub fn icon_data_url(icon: &str, size: u32) -> Option<String> {
+    static CACHE: OnceLock<Mutex<IconCache>> = OnceLock::new();
+    let cache = CACHE.get_or_init(Default::default);
+
+    let key = (icon.to_string(), size);
+    if let Some(hit) = cache.lock().ok()?.get(&key) {
+        return hit.clone();
+    }
+    let url = find_icon(icon, size).and_then(|p| to_data_url(&p));
+    cache.lock().ok()?.insert(key, url.clone());
+    url
+}
+
+// --- Commands ---
+
+/// Resolve several `Icon=` values at once. Icons that can't be found are
+/// left out of the result.
+#[tauri::command]
+pub async fn resolve_icons(icons: Vec<String>, size: u32) -> Result<HashMap<String, String>, String> {
+    spawn_blocking(move || {
+        icons
+            .into_iter()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter_map(|icon| icon_data_url(&icon, size).map(|url| (icon, url)))
+            .collect()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir(kind: DirType, size: u32, scale: u32) -> ThemeDir {
+        ThemeDir {
+            subdir: String::new(),
+            size,
+            scale,
+            kind,
+            min_size: size,
+            max_size: size,
+            threshold: 2,
+        }
+    }
+
+    #[test]
+    fn distance_is_measured_in_scaled_pixels() {
+        assert_eq!(dir(DirType::Fixed, 48, 1).distance(48, 1), 0);
+        assert_eq!(dir(DirType::Fixed, 32, 1).distance(48, 1), 16);
+        assert_eq!(dir(DirType::Fixed, 24, 2).distance(16, 1), 32);
+        assert_eq!(dir(DirType::Threshold, 48, 1).distance(45, 1), 1);
+        assert!(dir(DirType::Threshold, 48, 1).matches(50, 1));
+    }
+
+    #[test]
+    fn huge_sizes_from_a_theme_saturate() {
+        let huge = dir(DirType::Threshold, u32::MAX, u32::MAX);
+        assert!(huge.matches(u32::MAX, u32::MAX));
+        assert_eq!(huge.distance(u32::MAX, u32::MAX), 0);
+        assert_eq!(dir(DirType::Fixed, u32::MAX, 2).distance(16, 1), u32::MAX - 16);
+        assert_eq!(dir(DirType::Fixed, 16, 1).distance(u32::MAX, 3), u32::MAX - 16);
+    }
+}
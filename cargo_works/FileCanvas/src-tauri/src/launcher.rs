@@ -0,0 +1,230 @@
+// src-tauri/src/launcher.rs
+//
+// Starts external apps and watches them for a moment instead of returning as
+// soon as `spawn()` does. A program that is missing, not executable, or that
+// exits with an error right away (bad arguments, can't open the file, no
+// display) becomes a `LaunchError` with its exit code and stderr.
+//
+// Apps still running after the watch window are left alone; a background
+// thread reaps them when they exit so no zombies pile up. Launchers such as
+// gio or xdg-open exit 0 once they've handed off, which counts as success.
+
+use crate::error::{ErrorCode, FsError};
+use serde::Serialize;
+use std::fmt;
+use std::io::{ErrorKind, Read};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long a fresh process is watched for an early exit
+const WATCH_WINDOW: Duration = Duration::from_millis(600);
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+/// Time allowed for the last stderr bytes to arrive after the process exited
+const STDERR_GRACE: Duration = Duration::from_millis(100);
+/// Only the end of stderr is kept; that's where the error usually is
+const MAX_STDERR_BYTES: usize = 4096;
+
+// --- Data Structures ---
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LaunchErrorKind {
+    /// The program doesn't exist
+    NotFound,
+    PermissionDenied,
+    /// Any other reason `spawn()` failed
+    SpawnFailed,
+    /// Started, then exited unsuccessfully within the watch window
+    ExitedEarly,
+    /// Failed before anything was started (bad Exec line, no terminal, …)
+    Other,
+}
+
+/// What the UI gets when an app couldn't be started
+#[derive(Debug, Clone, Serialize)]
+pub struct LaunchError {
+    pub kind: LaunchErrorKind,
+    pub program: String,
+    pub message: String,
+    pub exit_code: Option<i32>,
+    /// Tail of the program's stderr, if it printed anything
+    pub stderr: Option<String>,
+}
+
+/// A started process that hasn't been checked yet
+pub struct Launched {
+    child: Child,
+    program: String,
+    started: Instant,
+    stderr: Arc<Mutex<Vec<u8>>>,
+    stderr_done: mpsc::Receiver<()>,
+}
+
+// --- Helpers ---
+
+impl fmt::Display for LaunchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(stderr) = self.stderr.as_deref().and_then(|s| s.lines().rev().find(|l| !l.trim().is_empty())) {
+            write!(f, ": {}", stderr.trim())?;
+        }
+        Ok(())
+    }
+}
+
+impl From<String> for LaunchError {
+    fn from(message: String) -> Self {
+        LaunchError {
+            kind: LaunchErrorKind::Other,
+            program: String::new(),
+            message,
+            exit_code: None,
+            stderr: None,
+        }
+    }
+}
+
+impl From<LaunchError> for String {
+    fn from(e: LaunchError) -> Self {
+        e.to_string()
+    }
+}
+
+impl From<LaunchError> for FsError {
+    fn from(e: LaunchError) -> Self {
+        let code = match e.kind {
+            LaunchErrorKind::NotFound | LaunchErrorKind::PermissionDenied => ErrorCode::AppNotFound,
+            LaunchErrorKind::ExitedEarly => ErrorCode::AppExited,
+            LaunchErrorKind::SpawnFailed => ErrorCode::LaunchFailed,
+            LaunchErrorKind::Other => ErrorCode::Other,
+        };
+        FsError {
+            code,
+            path: (!e.program.is_empty()).then_some(e.program),
+            errno: None,
+            message: e.message,
+            exit_code: e.exit_code,
+            stderr: e.stderr,
+            detail: None,
+        }
+    }
+}
+
+fn program_name(cmd: &Command) -> String {
+    cmd.get_program().to_string_lossy().to_string()
+}
+
+/// Read stderr on its own thread so a chatty app never blocks on a full pipe.
+/// Only the last MAX_STDERR_BYTES are kept.
+fn collect_stderr(child: &mut Child) -> (Arc<Mutex<Vec<u8>>>, mpsc::Receiver<()>) {
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let (done_tx, done_rx) = mpsc::channel();
+
+    if let Some(mut pipe) = child.stderr.take() {
+        let buffer = Arc::clone(&buffer);
+        std::thread::spawn(move || {
+            let mut chunk = [0u8; 1024];
+            // Ends at EOF, which may be long after the process exits if it
+            // handed the pipe to a child of its own
+            while let Ok(n) = pipe.read(&mut chunk) {
+                if n == 0 {
+                    break;
+                }
+                if let Ok(mut buf) = buffer.lock() {
+                    buf.extend_from_slice(&chunk[..n]);
+                    let excess = buf.len().saturating_sub(MAX_STDERR_BYTES);
+                    buf.drain(..excess);
+                }
+            }
+            let _ = done_tx.send(());
+        });
+    } else {
+        let _ = done_tx.send(());
+    }
+    (buffer, done_rx)
+}
+
+// --- Launching ---
+
+/// Start `cmd` with stdin/stdout detached and stderr captured
+pub fn spawn(mut cmd: Command) -> Result<Launched, LaunchError> {
+    let program = program_name(&cmd);
+    cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::piped());
+
+    let mut child = cmd.spawn().map_err(|e| {
+        let (kind, message) = match e.kind() {
+            ErrorKind::NotFound => (LaunchErrorKind::NotFound, format!("{} is not installed", program)),
+            ErrorKind::PermissionDenied => (
+                LaunchErrorKind::PermissionDenied,
+                format!("{} is not executable", program),
+            ),
+            _ => (LaunchErrorKind::SpawnFailed, format!("Failed to launch {}: {}", program, e)),
+        };
+        LaunchError {
+            kind,
+            program: program.clone(),
+            message,
+            exit_code: None,
+            stderr: None,
+        }
+    })?;
+
+    let (stderr, stderr_done) = collect_stderr(&mut child);
+    Ok(Launched {
+        child,
+        program,
+        started: Instant::now(),
+        stderr,
+        stderr_done,
+    })
+}
+
+impl Launched {
+    /// Wait out the rest of the watch window. Fails if the process exited
+    /// unsuccessfully; otherwise it is left running and reaped in the background.
+    ///
+    /// Several processes can be spawned first and then supervised one after
+    /// another: the window counts from each spawn, so the total wait stays short.
+    pub fn supervise(mut self) -> Result<(), LaunchError> {
+        loop {
+            match self.child.try_wait() {
+                Ok(Some(status)) if status.success() => return Ok(()),
+                Ok(Some(status)) => {
+                    let _ = self.stderr_done.recv_timeout(STDERR_GRACE);
+                    let stderr = self
+                        .stderr
+                        .lock()
+                        .map(|b| String::from_utf8_lossy(&b).trim().to_string())
+                        .unwrap_or_default();
+                    let message = match status.code() {
+                        Some(code) => format!("{} exited with code {}", self.program, code),
+                        None => format!("{} was terminated by a signal", self.program),
+                    };
+                    return Err(LaunchError {
+                        kind: LaunchErrorKind::ExitedEarly,
+                        program: self.program,
+                        message,
+                        exit_code: status.code(),
+                        stderr: (!stderr.is_empty()).then_some(stderr),
+                    });
+                }
+                Ok(None) if self.started.elapsed() < WATCH_WINDOW => std::thread::sleep(POLL_INTERVAL),
+                // Still running (or can't be polled): treat as started
+                _ => break,
+            }
+        }
+
+        let mut child = self.child;
+        std::thread::spawn(move || {
+            let _ = child.wait();
+        });
+        Ok(())
+    }
+}
+
+/// Spawn and supervise in one go
+pub fn run(cmd: Command) -> Result<(), LaunchError> {
+    spawn(cmd)?.supervise()
+}
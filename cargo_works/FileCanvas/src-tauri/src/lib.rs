@@ -1,14 +1,29 @@
-// Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-#[tauri::command]
-fn greet(name: &str) -> String {
-    format!("Hello, {}! You've been greeted from Rust!", name)
-}
+// src-tauri/src/lib.rs
+//
+// `filecanvas_lib`: the file operations of FileCanvas without the GUI. The
+// Tauri commands in main.rs are thin wrappers that check paths against the
+// approved folders and call into `ops` with the real disk; tests and tools
+// can run the same code against `vfs::MemoryFs`.
+//
+//   error     FsError, the serializable error every operation returns
+//   vfs       the FileSystem trait with DiskFs and MemoryFs
+//   ops       listing, create, move, copy, delete, rename, sizes
+//   copier    parallel folder copies on the local disk
+//   resumable large-file copies that continue after an interruption
+//   space     free space on the volumes a transfer writes to
+//   sftp      FileSystem over SFTP for remote panels
+//   s3        FileSystem over S3-compatible object storage
+//   webdav    FileSystem over WebDAV (Nextcloud, ownCloud, …)
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![greet])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
+pub mod copier;
+pub mod error;
+pub mod ops;
+pub mod resumable;
+pub mod s3;
+pub mod sftp;
+pub mod space;
+pub mod vfs;
+pub mod webdav;
+
+pub use error::{ErrorCode, FsError};
+pub use vfs::{DiskFs, FileSystem, MemoryFs, RemoteFs};
@@ -1,8 +1,47 @@
+mod activity;
+mod app_usage;
+mod connections;
+mod custom_apps;
+mod desktop_entry;
+mod desktop_exec;
+mod filters;
+mod hotfolders;
+mod icons;
+mod launcher;
+mod mime;
+#[cfg(target_os = "linux")]
+mod mimeapps;
 mod openwith;
-use openwith::{list_open_with_apps, open_with_app};
-use std::fs;
-use std::path::PathBuf;
-use std::path::Path;
+mod organizer;
+mod path_policy;
+mod remote;
+mod store;
+mod sync;
+mod terminal;
+use activity::{export_activity, query_activity, ActivityEntry, Operation, Via};
+use app_usage::set_always_use_app;
+use connections::validate_connections;
+use custom_apps::{delete_custom_app, list_custom_apps, save_custom_app};
+use openwith::{
+    list_open_with_apps, open_files_with_app, open_with_app, set_default_app, LaunchResult,
+};
+use icons::resolve_icons;
+use filecanvas_lib::copier::{self, CopyOptions, Progress};
+use filecanvas_lib::{error, ops, space};
+use std::path::{Path, PathBuf};
+use error::{ErrorCode, FsError};
+use ops::FileItem;
+use launcher::LaunchErrorKind;
+use path_policy::{list_approved_roots, revoke_root, PathPolicy};
+use remote::{connect_remote, connect_s3, connect_webdav, disconnect_remote, forget_remote, Location, RemoteSessions};
+use organizer::{list_organize_runs, organize_folder, undo_organize_run};
+use filters::{plan_transfer, transfer_filtered};
+use hotfolders::{
+    hot_folder_activity, hot_folders_status, list_hot_folder_rules, save_hot_folder_rules,
+    start_hot_folders, stop_hot_folders, HotFolderService,
+};
+use sync::{plan_sync, run_sync};
+use terminal::{set_terminal, terminal_status};
 
 
 use serde::{Deserialize, Serialize};
@@ -16,16 +55,6 @@ use tauri::Manager;
 
 // --- Data Structures ---
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct FileItem {
-    pub name: String,
-    pub path: String,
-    pub is_directory: bool,
-    pub children: Option<Vec<FileItem>>,
-    pub mtime: u64,
-    pub ctime: u64,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileProperties {
     pub name: String,
@@ -34,60 +63,67 @@ pub struct FileProperties {
     pub size: u64,      // recursive if directory
     pub mtime: u64,
     pub ctime: u64,
+    pub mime_type: String,
     pub children: Option<Vec<FileItem>>,
+    /// Free and total bytes of the volume, for local folders
+    pub free_space: Option<u64>,
+    pub total_space: Option<u64>,
 }
 
 
 // --- Helpers ---
 
-fn read_dir_shallow(path: &PathBuf) -> Vec<FileItem> {
-    let mut items = Vec::new();
+/// Bytes in a file or folder, for the activity log
+fn logged_size(item: &Location) -> u64 {
+    item.fs()
+        .symlink_metadata(item.path())
+        .map(|m| ops::item_size(item.fs(), item.path(), &m))
+        .unwrap_or(0)
+}
 
-    let entries = match fs::read_dir(path) {
-        Ok(e) => e,
-        Err(_) => return items,
+/// Refuses a transfer into a local folder without room for it, before
+/// anything is written. A move within one volume needs none.
+fn ensure_room(src: &Location, dest_folder: &Location, bytes: u64, moving: bool) -> Result<(), FsError> {
+    let Location::Local(dest) = dest_folder else { return Ok(()) };
+    let needed = match src {
+        Location::Local(from) => space::bytes_needed(from, dest, bytes, moving),
+        Location::Remote { .. } => bytes,
     };
+    space::ensure(dest, needed)
+}
 
-    for entry in entries.flatten() {
-        let name = entry.file_name().to_string_lossy().to_string();
-        if name.starts_with('.') {
-            continue;
-        }
-
-        let entry_path = entry.path();
-
-        let metadata = match fs::metadata(&entry_path) {
-            Ok(m) => m,
-            Err(_) => continue, 
-        };
-
-        let mtime = metadata
-            .modified()
-            .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_millis() as u64)
-            .unwrap_or(0);
-
-        let ctime = metadata
-            .created()
-            .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_millis() as u64)
-            .unwrap_or(mtime); 
-
-        items.push(FileItem {
-            name,
-            path: entry_path.to_string_lossy().to_string(),
-            is_directory: metadata.is_dir(),
-            children: None,
-            mtime,
-            ctime,
-        });
+/// A rename can't leave its drive: a local move onto another volume fails
+/// with `CrossesDevices` and goes the long way, copy then delete, instead
+fn move_across_drives(e: FsError, src: &Location, dest_folder: &Location) -> Result<PathBuf, FsError> {
+    if e.code != ErrorCode::CrossesDevices {
+        return Err(e);
     }
+    ops::move_between(src.fs(), src.path(), dest_folder.fs(), dest_folder.path())
+}
 
-    items.sort_by(|a, b| {
-        b.is_directory
-            .cmp(&a.is_directory)
-            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
-    });
+/// Logs a copy or move of `src` into `dest_folder`, ending at the URL in `result`
+fn record_transfer<R: Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    operation: Operation,
+    src: &Location,
+    dest_folder: &Location,
+    bytes: u64,
+    result: Result<String, FsError>,
+) -> Result<String, FsError> {
+    let entry = ActivityEntry::new(operation, Via::Panel)
+        .from(src.url(src.path()))
+        .bytes(bytes);
+    let entry = match &result {
+        Ok(dest) => entry.to(dest),
+        Err(_) => entry.to(dest_folder.url(dest_folder.path())),
+    };
+    activity::record(app_handle, entry, result)
+}
 
-    items
+/// Run `work` on the blocking pool: commands that touch the disk or a server
+/// must not hold up the async runtime (and with it every other command)
+async fn blocking<T: Send + 'static>(work: impl FnOnce() -> Result<T, FsError> + Send + 'static) -> Result<T, FsError> {
+    spawn_blocking(work).await.map_err(|e| FsError::from(e.to_string()))?
 }
 
 // --- Commands ---
@@ -95,290 +131,403 @@ fn read_dir_shallow(path: &PathBuf) -> Vec<FileItem> {
 #[tauri::command]
 async fn pick_folder<R: Runtime>(
     app_handle: tauri::AppHandle<R>,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, FsError> {
     // Note: use blocking_pick_folders if you aren't in a closure, 
     // but typically for v2 plugins we use pick_folders(callback) or the sync version
     let result = app_handle.dialog().file().blocking_pick_folders();
 
+    // Picking a folder is what grants access to it (see path_policy)
     match result {
-        Some(paths) => Ok(paths
+        Some(paths) => paths
             .into_iter()
             .filter_map(|fp| fp.into_path().ok())
-            .filter_map(|pb| pb.to_str().map(|s| s.to_owned()))
-            .collect()),
+            .map(|pb| path_policy::approve(&app_handle, &pb))
+            .map(|approved| approved.map(|pb| pb.to_string_lossy().to_string()))
+            .collect(),
         None => Ok(vec![]),
     }
 }
 
 #[tauri::command]
-async fn read_folder(folder_path: String) -> Result<Vec<FileItem>, String> {
-    let path = PathBuf::from(folder_path);
-    spawn_blocking(move || Ok(read_dir_shallow(&path)))
-        .await
-        .map_err(|e| e.to_string())?
+async fn read_folder<R: Runtime>(app_handle: tauri::AppHandle<R>, folder_path: String) -> Result<Vec<FileItem>, FsError> {
+    blocking(move || {
+        let folder = remote::existing(&app_handle, &folder_path)?;
+        let mut items = ops::read_dir_shallow(folder.fs(), folder.path());
+        if let Location::Remote { .. } = folder {
+            for item in &mut items {
+                item.path = folder.url(Path::new(&item.path));
+            }
+        }
+        Ok(items)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn create_folder(parent_path: String, folder_name: String) -> Result<FileItem, String> {
-    let new_path = PathBuf::from(&parent_path).join(&folder_name);
-    fs::create_dir(&new_path).map_err(|e| e.to_string())?;
-
-    let metadata = fs::metadata(&new_path).map_err(|e| e.to_string())?;
-    let mtime = metadata
-        .modified()
-        .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_millis() as u64)
-        .unwrap_or(0);
-    let ctime = metadata
-        .created()
-        .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_millis() as u64)
-        .unwrap_or(mtime);
-
-    Ok(FileItem {
-        name: folder_name,
-        path: new_path.to_string_lossy().to_string(),
-        is_directory: true,
-        children: None,
-        mtime,
-        ctime,
+async fn create_folder<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    parent_path: String,
+    folder_name: String,
+) -> Result<FileItem, FsError> {
+    blocking(move || {
+        let new_path = remote::child(&app_handle, &parent_path, &folder_name)?;
+        let entry = ActivityEntry::new(Operation::CreateFolder, Via::Panel).to(new_path.url(new_path.path()));
+        let mut item = activity::record(&app_handle, entry, ops::create_folder(new_path.fs(), new_path.path()))?;
+        item.path = new_path.url(new_path.path());
+        Ok(item)
     })
+    .await
 }
 
 #[tauri::command]
-async fn create_file(parent_path: String, file_name: String) -> Result<FileItem, String> {
-    let new_path = PathBuf::from(&parent_path).join(&file_name);
-    fs::File::create(&new_path).map_err(|e| e.to_string())?;
-
-    let metadata = fs::metadata(&new_path).map_err(|e| e.to_string())?;
-    let mtime = metadata
-        .modified()
-        .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_millis() as u64)
-        .unwrap_or(0);
-    let ctime = metadata
-        .created()
-        .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_millis() as u64)
-        .unwrap_or(mtime);
-
-    Ok(FileItem {
-        name: file_name,
-        path: new_path.to_string_lossy().to_string(),
-        is_directory: false,
-        children: None,
-        mtime,
-        ctime,
+async fn create_file<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    parent_path: String,
+    file_name: String,
+) -> Result<FileItem, FsError> {
+    blocking(move || {
+        let new_path = remote::child(&app_handle, &parent_path, &file_name)?;
+        let entry = ActivityEntry::new(Operation::CreateFile, Via::Panel).to(new_path.url(new_path.path()));
+        let mut item = activity::record(&app_handle, entry, ops::create_file(new_path.fs(), new_path.path()))?;
+        item.path = new_path.url(new_path.path());
+        Ok(item)
     })
+    .await
 }
 
 #[tauri::command]
-async fn move_file(src: String, dest_folder: String) -> Result<String, String> {
-    let src_path = PathBuf::from(src);
-    let file_name = src_path.file_name().ok_or("Invalid source path")?;
-    let dest_path = PathBuf::from(dest_folder).join(file_name);
-
-    fs::rename(&src_path, &dest_path)
-        .map_err(|e| e.to_string())?;
-
-    Ok(dest_path.to_string_lossy().to_string())
+async fn move_file<R: Runtime>(app_handle: tauri::AppHandle<R>, src: String, dest_folder: String) -> Result<String, FsError> {
+    blocking(move || {
+        let src = remote::entry(&app_handle, &src)?;
+        let dest_folder = remote::existing(&app_handle, &dest_folder)?;
+        let bytes = logged_size(&src);
+        let dest_path = ensure_room(&src, &dest_folder, bytes, true).and_then(|_| {
+            if src.same_fs(&dest_folder) {
+                ops::move_item(src.fs(), src.path(), dest_folder.path())
+                    .or_else(|e| move_across_drives(e, &src, &dest_folder))
+            } else {
+                ops::move_between(src.fs(), src.path(), dest_folder.fs(), dest_folder.path())
+            }
+        });
+        let result = dest_path.map(|p| dest_folder.url(&p));
+        record_transfer(&app_handle, Operation::Move, &src, &dest_folder, bytes, result)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn move_folder(src: String, dest_folder: String) -> Result<String, String> {
-    let src_path = PathBuf::from(&src);
-    if !src_path.is_dir() {
-        return Err("Source is not a folder".into());
-    }
-    let folder_name = src_path.file_name().ok_or("Invalid source path")?;
-    let dest_path = PathBuf::from(&dest_folder).join(folder_name);
-
-    fs::rename(&src_path, &dest_path).map_err(|e| e.to_string())?;
-    Ok(dest_path.to_string_lossy().to_string())
+async fn move_folder<R: Runtime>(app_handle: tauri::AppHandle<R>, src: String, dest_folder: String) -> Result<String, FsError> {
+    blocking(move || {
+        let src = remote::entry(&app_handle, &src)?;
+        let dest_folder = remote::existing(&app_handle, &dest_folder)?;
+        let bytes = logged_size(&src);
+        let dest_path = ensure_room(&src, &dest_folder, bytes, true).and_then(|_| {
+            if src.same_fs(&dest_folder) {
+                ops::move_folder(src.fs(), src.path(), dest_folder.path())
+                    .or_else(|e| move_across_drives(e, &src, &dest_folder))
+            } else {
+                ops::move_between(src.fs(), src.path(), dest_folder.fs(), dest_folder.path())
+            }
+        });
+        let result = dest_path.map(|p| dest_folder.url(&p));
+        record_transfer(&app_handle, Operation::Move, &src, &dest_folder, bytes, result)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn copy_file(src: String, dest_folder: String) -> Result<String, String> {
-    let src_path = PathBuf::from(src);
-    let file_name = src_path.file_name().ok_or("Invalid source path")?;
-    let dest_path = PathBuf::from(dest_folder).join(file_name);
-
-    fs::copy(&src_path, &dest_path).map_err(|e| e.to_string())?;
-    Ok(dest_path.to_string_lossy().to_string())
-}
-
-fn copy_dir_recursive(src: &PathBuf, dest: &PathBuf) -> Result<(), String> {
-    fs::create_dir_all(dest).map_err(|e| e.to_string())?;
-    for entry in fs::read_dir(src).map_err(|e| e.to_string())? {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let path = entry.path();
-        let dest_path = dest.join(entry.file_name());
-
-        if path.is_dir() {
-            copy_dir_recursive(&path, &dest_path)?;
-        } else {
-            fs::copy(&path, &dest_path).map_err(|e| e.to_string())?;
-        }
-    }
-    Ok(())
+async fn copy_file<R: Runtime>(app_handle: tauri::AppHandle<R>, src: String, dest_folder: String) -> Result<String, FsError> {
+    blocking(move || {
+        let src = remote::existing(&app_handle, &src)?;
+        let dest_folder = remote::existing(&app_handle, &dest_folder)?;
+        let bytes = logged_size(&src);
+        let dest_path = ensure_room(&src, &dest_folder, bytes, false).and_then(|_| {
+            if src.same_fs(&dest_folder) {
+                ops::copy_file(src.fs(), src.path(), dest_folder.path())
+            } else {
+                ops::copy_between(src.fs(), src.path(), dest_folder.fs(), dest_folder.path())
+            }
+        });
+        let result = dest_path.map(|p| dest_folder.url(&p));
+        record_transfer(&app_handle, Operation::Copy, &src, &dest_folder, bytes, result)
+    })
+    .await
 }
 
+/// Local copies run on a pool of `workers` threads (default: one per core,
+/// up to `copier::MAX_DEFAULT_WORKERS`)
 #[tauri::command]
-async fn copy_folder(src: String, dest_folder: String) -> Result<String, String> {
-    let src_path = PathBuf::from(&src);
-    if !src_path.is_dir() {
-        return Err("Source is not a folder".into());
-    }
-    let folder_name = src_path.file_name().ok_or("Invalid source path")?;
-    let dest_path = PathBuf::from(&dest_folder).join(folder_name);
-
-    copy_dir_recursive(&src_path, &dest_path)?;
-    Ok(dest_path.to_string_lossy().to_string())
+async fn copy_folder<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    src: String,
+    dest_folder: String,
+    workers: Option<usize>,
+) -> Result<String, FsError> {
+    blocking(move || {
+        let src = remote::existing(&app_handle, &src)?;
+        let dest_folder = remote::existing(&app_handle, &dest_folder)?;
+        let bytes = logged_size(&src);
+        let dest_path = ensure_room(&src, &dest_folder, bytes, false).and_then(|_| {
+            if let (Location::Local(from), Location::Local(into)) = (&src, &dest_folder) {
+                let options = CopyOptions {
+                    workers: workers.unwrap_or_else(|| CopyOptions::default().workers),
+                };
+                copier::copy_folder(from, into, &options, &Progress::default())
+            } else if src.same_fs(&dest_folder) {
+                ops::copy_folder(src.fs(), src.path(), dest_folder.path())
+            } else {
+                ops::copy_between(src.fs(), src.path(), dest_folder.fs(), dest_folder.path())
+            }
+        });
+        let result = dest_path.map(|p| dest_folder.url(&p));
+        record_transfer(&app_handle, Operation::Copy, &src, &dest_folder, bytes, result)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn delete_item(path_str: String) -> Result<(), String> {
-    let path = PathBuf::from(path_str);
-    let metadata = fs::metadata(&path).map_err(|e| e.to_string())?;
-
-    if metadata.is_dir() {
-        fs::remove_dir_all(&path)
-    } else {
-        fs::remove_file(&path)
-    }
-    .map_err(|e| e.to_string())
+async fn delete_item<R: Runtime>(app_handle: tauri::AppHandle<R>, path_str: String) -> Result<(), FsError> {
+    blocking(move || {
+        let item = remote::entry(&app_handle, &path_str)?;
+        let entry = ActivityEntry::new(Operation::Delete, Via::Panel)
+            .from(item.url(item.path()))
+            .bytes(logged_size(&item));
+        activity::record(&app_handle, entry, ops::delete_item(item.fs(), item.path()))
+    })
+    .await
 }
 
 #[tauri::command]
-async fn is_dir(path: String) -> Result<bool, String> {
-    std::fs::metadata(&path)
-        .map(|m| m.is_dir())
-        .map_err(|e| e.to_string())
+async fn is_dir<R: Runtime>(app_handle: tauri::AppHandle<R>, path: String) -> Result<bool, FsError> {
+    blocking(move || {
+        let item = remote::existing(&app_handle, &path)?;
+        Ok(item.fs().metadata(item.path())?.is_dir)
+    })
+    .await
 }
 
 // --- Open with default app (Fixed for Tauri v2) ---
-#[tauri::command]
-async fn open_file<R: Runtime>(app_handle: tauri::AppHandle<R>, path: String) -> Result<(), String> {
-    // Use tauri_plugin_shell
-    app_handle.shell().open(&path, None).map_err(|e| e.to_string())
-}
 
-#[tauri::command]
-async fn open_with<R: Runtime>(_app_handle: tauri::AppHandle<R>, path: String) -> Result<(), String> {
-    let file_path = std::path::PathBuf::from(&path);
-    
-    if !file_path.exists() {
-        return Err("File does not exist".into());
-    }
-
-    #[cfg(windows)]
+/// Open `path` with the system default app. Where the opener is a separate
+/// program it goes through the launcher, so "no app for this type" and
+/// crashing handlers come back as errors.
+fn open_default<R: Runtime>(app_handle: &tauri::AppHandle<R>, path: &str) -> Result<(), FsError> {
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
     {
-        Command::new("cmd")
-            .args(&["/C", "rundll32.exe", "shell32.dll,OpenAs_RunDLL", &file_path.to_string_lossy()])
-            .spawn()
-            .map_err(|e| e.to_string())?;
+        let opener = if cfg!(target_os = "linux") { "xdg-open" } else { "open" };
+        let mut cmd = Command::new(opener);
+        cmd.arg(path);
+        match launcher::run(cmd) {
+            // No xdg-utils: let the shell plugin try gio, kde-open, …
+            Err(e) if e.kind == LaunchErrorKind::NotFound => {}
+            result => return result.map_err(FsError::from),
+        }
     }
+    // Use tauri_plugin_shell
+    app_handle
+        .shell()
+        .open(path, None)
+        .map_err(|e| FsError::new(ErrorCode::LaunchFailed, e.to_string()).with_path(path))
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        let script = format!(
-            "tell application \"Finder\" to open POSIX file \"{}\" using (choose application)",
-            file_path.to_string_lossy()
-        );
-        Command::new("osascript")
-            .args(&["-e", &script])
-            .spawn()
-            .map_err(|e| e.to_string())?;
-    }
+/// The "always use" app for `path` failed and the system default is tried
+/// instead; the log keeps why
+fn log_always_use_failure<R: Runtime>(app_handle: &tauri::AppHandle<R>, path: &str, app_id: &str, error: &FsError) {
+    let entry = ActivityEntry::new(Operation::Open, Via::Panel).from(path).to(app_id);
+    let _ = activity::record(app_handle, entry, Err::<(), _>(error));
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        // Try 'gio' first (GNOME/Standard), then 'kioclient5' (KDE), then fallback to 'mimeopen'
-        let gio_status = Command::new("gio")
-            .args(&["open", "--launch", &file_path.to_string_lossy()])
-            .spawn();
-
-        if gio_status.is_err() {
-             Command::new("kioclient5")
-                .args(&["exec", &file_path.to_string_lossy()])
-                .spawn()
-                .map_err(|_| "Could not find a suitable 'Open With' handler (gio or kioclient5).".to_string())?;
+#[tauri::command]
+async fn open_file<R: Runtime>(app_handle: tauri::AppHandle<R>, path: String) -> Result<(), FsError> {
+    let path = path_policy::existing(&app_handle, &path)?.to_string_lossy().to_string();
+    spawn_blocking(move || {
+        // "Always use this app" from the Open With dialog wins over the system default
+        if let Ok(Some((app_id, exec))) = app_usage::always_app(&app_handle, &path) {
+            let launched = openwith::open_paths_with_app(&app_handle, &exec, std::slice::from_ref(&path), Some(&app_id));
+            match launched.into_iter().next().and_then(|r| r.error) {
+                None => return Ok(()),
+                // The app may have been uninstalled since; fall back to the default
+                Some(e) => log_always_use_failure(&app_handle, &path, &app_id, &e),
+            }
         }
-    }
-
-    Ok(())
+        open_default(&app_handle, &path)
+    })
+    .await
+    .map_err(|e| FsError::from(e.to_string()))?
 }
 
+/// Open several files at once. Files whose type has an "always use" app are
+/// handed to that app together (one process when it takes a file list);
+/// everything else goes to the system default, one file at a time.
 #[tauri::command]
-async fn rename_item(old_path: String, new_name: String) -> Result<String, String> {
-    let old_path = PathBuf::from(old_path);
-    let parent = old_path.parent().ok_or("Invalid path")?;
-    let new_path = parent.join(new_name);
+async fn open_files<R: Runtime>(app_handle: tauri::AppHandle<R>, paths: Vec<String>) -> Result<Vec<LaunchResult>, FsError> {
+    spawn_blocking(move || {
+        let mut results: Vec<Option<LaunchResult>> = vec![None; paths.len()];
+        // Paths outside the approved folders fail on their own; the rest still open
+        let checked: Vec<Result<String, FsError>> = paths
+            .iter()
+            .map(|p| path_policy::existing(&app_handle, p).map(|p| p.to_string_lossy().to_string()))
+            .collect();
+        for (i, check) in checked.iter().enumerate() {
+            if let Err(e) = check {
+                results[i] = Some(LaunchResult::new(&paths[i], Err(e.clone())));
+            }
+        }
+        let paths: Vec<String> = checked
+            .into_iter()
+            .zip(&paths)
+            .map(|(check, original)| check.unwrap_or_else(|_| original.clone()))
+            .collect();
+
+        // (app id, exec, indices into `paths`)
+        let mut groups: Vec<(String, String, Vec<usize>)> = Vec::new();
+        for (i, path) in paths.iter().enumerate() {
+            if results[i].is_some() {
+                continue;
+            }
+            if let Ok(Some((app_id, exec))) = app_usage::always_app(&app_handle, path) {
+                match groups.iter_mut().find(|(id, _, _)| *id == app_id) {
+                    Some((_, _, indices)) => indices.push(i),
+                    None => groups.push((app_id, exec, vec![i])),
+                }
+            }
+        }
+        for (app_id, exec, indices) in groups {
+            let files: Vec<String> = indices.iter().map(|&i| paths[i].clone()).collect();
+            let launched = openwith::open_paths_with_app(&app_handle, &exec, &files, Some(&app_id));
+            for (i, result) in indices.into_iter().zip(launched) {
+                match &result.error {
+                    // Same fallback as open_file: retry with the system default below
+                    Some(e) => log_always_use_failure(&app_handle, &paths[i], &app_id, e),
+                    None => results[i] = Some(result),
+                }
+            }
+        }
 
-    fs::rename(&old_path, &new_path).map_err(|e| e.to_string())?;
-    Ok(new_path.to_string_lossy().to_string())
+        paths
+            .iter()
+            .zip(results)
+            .map(|(path, result)| {
+                result.unwrap_or_else(|| LaunchResult::new(path, open_default(&app_handle, path)))
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| FsError::from(e.to_string()))
 }
 
-fn dir_size(path: &Path) -> u64 {
-    let mut size = 0;
+#[tauri::command]
+async fn open_with<R: Runtime>(app_handle: tauri::AppHandle<R>, path: String) -> Result<(), FsError> {
+    let file_path = path_policy::existing(&app_handle, &path)?;
+
+    spawn_blocking(move || {
+        #[cfg(windows)]
+        {
+            let mut cmd = Command::new("cmd");
+            cmd.args(["/C", "rundll32.exe", "shell32.dll,OpenAs_RunDLL", &file_path.to_string_lossy()]);
+            launcher::run(cmd).map_err(FsError::from)?;
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            let script = format!(
+                "tell application \"Finder\" to open POSIX file \"{}\" using (choose application)",
+                file_path.to_string_lossy()
+            );
+            let mut cmd = Command::new("osascript");
+            cmd.args(["-e", &script]);
+            launcher::run(cmd).map_err(FsError::from)?;
+        }
 
-    if let Ok(entries) = fs::read_dir(path) {
-        for entry in entries.flatten() {
-            if let Ok(metadata) = entry.metadata() {
-                if metadata.is_dir() {
-                    size += dir_size(&entry.path());
-                } else {
-                    size += metadata.len();
+        #[cfg(target_os = "linux")]
+        {
+            // Try 'gio' first (GNOME/Standard), then 'kioclient5' (KDE). The
+            // fallback also runs when gio starts but fails, not only when it is
+            // missing; the first real error is the one reported.
+            let mut gio = Command::new("gio");
+            gio.args(["open", &file_path.to_string_lossy()]);
+            if let Err(gio_error) = launcher::run(gio) {
+                let mut kio = Command::new("kioclient5");
+                kio.args(["exec", &file_path.to_string_lossy()]);
+                if let Err(kio_error) = launcher::run(kio) {
+                    return Err(match (gio_error.kind, kio_error.kind) {
+                        (LaunchErrorKind::NotFound, LaunchErrorKind::NotFound) => FsError::new(
+                            ErrorCode::AppNotFound,
+                            "Could not find a suitable 'Open With' handler (gio or kioclient5).",
+                        ),
+                        (LaunchErrorKind::NotFound, _) => kio_error.into(),
+                        _ => gio_error.into(),
+                    });
                 }
             }
         }
-    }
 
-    size
+        Ok(())
+    })
+    .await
+    .map_err(|e| FsError::from(e.to_string()))?
 }
 
+#[tauri::command]
+async fn rename_item<R: Runtime>(app_handle: tauri::AppHandle<R>, old_path: String, new_name: String) -> Result<String, FsError> {
+    blocking(move || {
+        path_policy::validate_name(&new_name)?;
+        let item = remote::entry(&app_handle, &old_path)?;
+        let entry = ActivityEntry::new(Operation::Rename, Via::Panel)
+            .from(item.url(item.path()))
+            .to(item.url(&item.path().with_file_name(&new_name)));
+        let new_path = activity::record(&app_handle, entry, ops::rename_item(item.fs(), item.path(), &new_name))?;
+        Ok(item.url(&new_path))
+    })
+    .await
+}
 
 #[tauri::command]
-async fn get_file_info(path: String) -> Result<FileProperties, String> {
-    let path = PathBuf::from(&path);
-    let metadata = fs::metadata(&path).map_err(|e| e.to_string())?;
-
-    let name = path
-        .file_name()
-        .ok_or("Invalid file path")?
-        .to_string_lossy()
-        .to_string();
-
-    let mtime = metadata
-        .modified()
-        .map(|t| t.duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis() as u64)
-        .unwrap_or(0);
-
-    let ctime = metadata
-        .created()
-        .map(|t| t.duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis() as u64)
-        .unwrap_or(mtime);
-
-    let size = if metadata.is_dir() {
-        dir_size(&path)
-    } else {
-        metadata.len()
-    };
+async fn get_file_info<R: Runtime>(app_handle: tauri::AppHandle<R>, path: String) -> Result<FileProperties, FsError> {
+    blocking(move || {
+        let item = remote::existing(&app_handle, &path)?;
+        let (fs, path) = (item.fs(), item.path());
+        let metadata = fs.metadata(path)?;
+        let name = ops::display_name(path);
+        // Content sniffing reads the local file; on a server go by name only
+        let mime_type = match item {
+            Location::Local(_) => mime::detect(path),
+            Location::Remote { .. } if metadata.is_dir => mime::DIRECTORY.to_string(),
+            Location::Remote { .. } => mime::from_name(&name).unwrap_or_else(|| mime::OCTET_STREAM.to_string()),
+        };
+        let volume = match item {
+            Location::Local(_) if metadata.is_dir => space::volume_space(path).ok(),
+            _ => None,
+        };
 
-    Ok(FileProperties{
-        name,
-        path: path.to_string_lossy().to_string(),
-        is_directory: metadata.is_dir(),
-        size,
-        children: None,
-        mtime,
-        ctime,
+        Ok(FileProperties{
+            name,
+            path: item.url(path),
+            is_directory: metadata.is_dir,
+            size: ops::item_size(fs, path, &metadata),
+            children: None,
+            mtime: metadata.mtime,
+            ctime: metadata.ctime,
+            mime_type,
+            free_space: volume.map(|v| v.free),
+            total_space: volume.map(|v| v.total),
+        })
     })
+    .await
 }
 
 
 
 fn main() {
+    // `filecanvas --hot-folders` runs the hot folder watchers without a window
+    if std::env::args().any(|a| a == "--hot-folders") {
+        if let Err(e) = hotfolders::run_headless() {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     // Prevent GTK/Tauri initialization in headless CI
     if std::env::var("CI").is_ok() {
         println!("CI environment detected — skipping Tauri runtime startup");
@@ -388,10 +537,20 @@ fn main() {
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
+        .manage(HotFolderService::default())
+        .manage(PathPolicy::default())
+        .manage(RemoteSessions::default())
         .invoke_handler(tauri::generate_handler![
             rename_item,
             get_file_info,
             pick_folder,
+            list_approved_roots,
+            revoke_root,
+            connect_remote,
+            connect_s3,
+            connect_webdav,
+            disconnect_remote,
+            forget_remote,
             read_folder,
             create_folder,
             create_file,
@@ -401,10 +560,36 @@ fn main() {
             copy_file,
             delete_item,
             open_file,
+            open_files,
             open_with,
             list_open_with_apps,
             open_with_app,
-            is_dir
+            open_files_with_app,
+            set_default_app,
+            set_always_use_app,
+            list_custom_apps,
+            save_custom_app,
+            delete_custom_app,
+            resolve_icons,
+            terminal_status,
+            set_terminal,
+            is_dir,
+            plan_sync,
+            run_sync,
+            plan_transfer,
+            transfer_filtered,
+            validate_connections,
+            organize_folder,
+            list_organize_runs,
+            undo_organize_run,
+            list_hot_folder_rules,
+            save_hot_folder_rules,
+            start_hot_folders,
+            stop_hot_folders,
+            hot_folders_status,
+            hot_folder_activity,
+            query_activity,
+            export_activity
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,813 @@
+// src-tauri/src/mime.rs
+//
+// Content-based MIME detection.
+//
+// Order of checks:
+//   1. magic bytes (including a look inside ZIP containers for OOXML / ODF / EPUB / JAR)
+//   2. the shared-mime-info glob database (`<data dir>/mime/globs2`)
+//   3. a plain-text sniff, then `application/octet-stream`
+//
+// When magic only finds a generic container (zip, xml, plain text …) and the
+// glob result is a subclass of it, the more specific glob result wins
+// (`.jar` → application/x-java-archive instead of application/zip).
+// A `.txt` name also beats a type sniffed from the text itself: a note that
+// starts with `#!` is still a note, and isn't offered to script runners.
+//
+// Subclass and alias data also comes from shared-mime-info. Platforms without
+// the database fall back to a small built-in extension table.
+
+use globset::{GlobBuilder, GlobMatcher};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+pub const OCTET_STREAM: &str = "application/octet-stream";
+pub const DIRECTORY: &str = "inode/directory";
+
+const HEAD_LEN: usize = 4096;
+
+// --- Data Structures ---
+
+struct GlobEntry {
+    weight: u32,
+    mime: String,
+    case_sensitive: bool,
+}
+
+#[derive(Default)]
+struct MimeDb {
+    /// Whole file names ("makefile", "meson.build")
+    literals: HashMap<String, Vec<(String, GlobEntry)>>,
+    /// `*.ext` patterns keyed by lower-cased suffix including the dot (".tar.gz")
+    suffixes: HashMap<String, Vec<(String, GlobEntry)>>,
+    /// Everything else (`*.so.[0-9]*`, `README*` …)
+    complex: Vec<(GlobMatcher, GlobEntry)>,
+    subclasses: HashMap<String, Vec<String>>,
+    aliases: HashMap<String, String>,
+}
+
+// --- Database ---
+
+fn mime_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    match std::env::var("XDG_DATA_HOME") {
+        Ok(d) if !d.is_empty() => dirs.push(PathBuf::from(d)),
+        _ => {
+            if let Ok(home) = std::env::var("HOME") {
+                dirs.push(PathBuf::from(home).join(".local/share"));
+            }
+        }
+    }
+
+    let system = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    dirs.extend(system.split(':').map(PathBuf::from));
+
+    dirs.into_iter().map(|d| d.join("mime")).collect()
+}
+
+fn is_glob_meta(c: char) -> bool {
+    matches!(c, '*' | '?' | '[')
+}
+
+impl MimeDb {
+    fn load() -> Self {
+        let mut db = MimeDb::default();
+        for dir in mime_dirs() {
+            if let Ok(text) = std::fs::read_to_string(dir.join("globs2")) {
+                db.parse_globs2(&text);
+            }
+            if let Ok(text) = std::fs::read_to_string(dir.join("subclasses")) {
+                db.parse_subclasses(&text);
+            }
+            if let Ok(text) = std::fs::read_to_string(dir.join("aliases")) {
+                db.parse_aliases(&text);
+            }
+        }
+        db
+    }
+
+    /// `child/type parent/type`
+    fn parse_subclasses(&mut self, text: &str) {
+        for (child, parent) in pairs(text) {
+            let parents = self.subclasses.entry(child).or_default();
+            if !parents.contains(&parent) {
+                parents.push(parent);
+            }
+        }
+    }
+
+    /// `alias/type canonical/type`; earlier directories win
+    fn parse_aliases(&mut self, text: &str) {
+        for (alias, canonical) in pairs(text) {
+            self.aliases.entry(alias).or_insert(canonical);
+        }
+    }
+
+    /// `weight:mime/type:glob[:flags]`
+    fn parse_globs2(&mut self, text: &str) {
+        for line in text.lines() {
+            if line.starts_with('#') || line.trim().is_empty() {
+                continue;
+            }
+            let mut parts = line.splitn(4, ':');
+            let (Some(weight), Some(mime), Some(glob)) = (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+            let case_sensitive = parts.next().is_some_and(|f| f.split(',').any(|f| f == "cs"));
+            let entry = GlobEntry {
+                weight: weight.parse().unwrap_or(50),
+                mime: mime.to_string(),
+                case_sensitive,
+            };
+
+            if !glob.contains(is_glob_meta) {
+                self.literals
+                    .entry(glob.to_lowercase())
+                    .or_default()
+                    .push((glob.to_string(), entry));
+            } else if let Some(suffix) = glob.strip_prefix('*').filter(|s| {
+                s.starts_with('.') && !s.contains(is_glob_meta)
+            }) {
+                self.suffixes
+                    .entry(suffix.to_lowercase())
+                    .or_default()
+                    .push((suffix.to_string(), entry));
+            } else if let Ok(glob) = GlobBuilder::new(glob)
+                .case_insensitive(!case_sensitive)
+                .literal_separator(false)
+                .build()
+            {
+                self.complex.push((glob.compile_matcher(), entry));
+            }
+        }
+    }
+
+    fn canonical<'a>(&'a self, mime: &'a str) -> &'a str {
+        self.aliases.get(mime).map(|s| s.as_str()).unwrap_or(mime)
+    }
+
+    /// Literal names, then the longest `*.ext` suffix, then full globs.
+    /// Ties inside one step go to the highest weight.
+    fn match_name(&self, name: &str) -> Option<&str> {
+        fn best<'a>(candidates: &'a [(String, GlobEntry)], original: &str) -> Option<&'a str> {
+            candidates
+                .iter()
+                .filter(|(pattern, e)| !e.case_sensitive || pattern == original)
+                .max_by_key(|(_, e)| e.weight)
+                .map(|(_, e)| e.mime.as_str())
+        }
+
+        if let Some(found) = self
+            .literals
+            .get(&name.to_lowercase())
+            .and_then(|c| best(c, name))
+        {
+            return Some(found);
+        }
+
+        // Leftmost dot first, so ".tar.gz" beats ".gz"
+        for (i, _) in name.char_indices().filter(|(_, c)| *c == '.') {
+            let suffix = &name[i..];
+            if let Some(found) = self
+                .suffixes
+                .get(&suffix.to_lowercase())
+                .and_then(|c| best(c, suffix))
+            {
+                return Some(found);
+            }
+        }
+
+        self.complex
+            .iter()
+            .filter(|(m, _)| m.is_match(name))
+            .max_by_key(|(_, e)| e.weight)
+            .map(|(_, e)| e.mime.as_str())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.literals.is_empty() && self.suffixes.is_empty() && self.complex.is_empty()
+    }
+}
+
+fn pairs(text: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    text.lines()
+        .filter(|l| !l.starts_with('#'))
+        .filter_map(|l| l.split_once(' '))
+        .map(|(a, b)| (a.trim().to_string(), b.trim().to_string()))
+}
+
+fn db() -> &'static MimeDb {
+    static DB: OnceLock<MimeDb> = OnceLock::new();
+    DB.get_or_init(MimeDb::load)
+}
+
+/// Used when shared-mime-info is not installed (Windows, macOS)
+fn builtin_glob(name: &str) -> Option<&'static str> {
+    let ext = Path::new(name).extension()?.to_str()?.to_lowercase();
+    Some(match ext.as_str() {
+        "txt" | "text" | "log" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "xml" => "application/xml",
+        "json" => "application/json",
+        "js" | "mjs" => "application/javascript",
+        "py" => "text/x-python",
+        "rs" => "text/x-rust",
+        "c" | "h" => "text/x-c",
+        "cpp" | "cc" | "hpp" => "text/x-c++src",
+        "java" => "text/x-java",
+        "sh" => "application/x-shellscript",
+        "pdf" => "application/pdf",
+        "doc" => "application/msword",
+        "xls" => "application/vnd.ms-excel",
+        "ppt" => "application/vnd.ms-powerpoint",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "odt" => "application/vnd.oasis.opendocument.text",
+        "ods" => "application/vnd.oasis.opendocument.spreadsheet",
+        "odp" => "application/vnd.oasis.opendocument.presentation",
+        "epub" => "application/epub+zip",
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "bmp" => "image/bmp",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/x-wav",
+        "flac" => "audio/flac",
+        "ogg" => "audio/ogg",
+        "mp4" => "video/mp4",
+        "mkv" => "video/x-matroska",
+        "webm" => "video/webm",
+        "mov" => "video/quicktime",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "tar" => "application/x-tar",
+        "7z" => "application/x-7z-compressed",
+        _ => return None,
+    })
+}
+
+// --- Magic ---
+
+fn starts_at(buf: &[u8], offset: usize, magic: &[u8]) -> bool {
+    buf.len() >= offset + magic.len() && &buf[offset..offset + magic.len()] == magic
+}
+
+fn contains(buf: &[u8], needle: &[u8]) -> bool {
+    buf.windows(needle.len()).any(|w| w.eq_ignore_ascii_case(needle))
+}
+
+fn u16_le(buf: &[u8], at: usize) -> usize {
+    u16::from_le_bytes([buf[at], buf[at + 1]]) as usize
+}
+
+fn u32_le(buf: &[u8], at: usize) -> usize {
+    u32::from_le_bytes([buf[at], buf[at + 1], buf[at + 2], buf[at + 3]]) as usize
+}
+
+fn sniff(file: &mut File, head: &[u8]) -> Option<String> {
+    let fixed = |m: &str| Some(m.to_string());
+
+    if starts_at(head, 0, b"PK\x03\x04") {
+        return fixed(sniff_zip(file, head).unwrap_or("application/zip"));
+    }
+    if starts_at(head, 0, b"%PDF-") {
+        return fixed("application/pdf");
+    }
+    if starts_at(head, 0, b"\x89PNG\r\n\x1a\n") {
+        return fixed("image/png");
+    }
+    if starts_at(head, 0, b"\xff\xd8\xff") {
+        return fixed("image/jpeg");
+    }
+    if starts_at(head, 0, b"GIF87a") || starts_at(head, 0, b"GIF89a") {
+        return fixed("image/gif");
+    }
+    if starts_at(head, 0, b"RIFF") {
+        if starts_at(head, 8, b"WEBP") {
+            return fixed("image/webp");
+        }
+        if starts_at(head, 8, b"WAVE") {
+            return fixed("audio/x-wav");
+        }
+        if starts_at(head, 8, b"AVI ") {
+            return fixed("video/x-msvideo");
+        }
+    }
+    if starts_at(head, 4, b"ftyp") && head.len() >= 12 {
+        return fixed(match &head[8..12] {
+            b"qt  " => "video/quicktime",
+            b"M4A " | b"M4B " => "audio/mp4",
+            b"heic" | b"heix" | b"mif1" | b"msf1" => "image/heif",
+            b"avif" | b"avis" => "image/avif",
+            b"3gp4" | b"3gp5" | b"3gp6" | b"3ge6" => "video/3gpp",
+            _ => "video/mp4",
+        });
+    }
+    if starts_at(head, 0, b"\x1a\x45\xdf\xa3") {
+        let doctype = &head[..head.len().min(64)];
+        return fixed(if contains(doctype, b"webm") {
+            "video/webm"
+        } else {
+            "video/x-matroska"
+        });
+    }
+    if starts_at(head, 0, b"ID3") || (head.len() >= 2 && head[0] == 0xff && matches!(head[1], 0xfb | 0xfa | 0xf3 | 0xf2)) {
+        return fixed("audio/mpeg");
+    }
+    if starts_at(head, 0, b"fLaC") {
+        return fixed("audio/flac");
+    }
+    if starts_at(head, 0, b"OggS") {
+        return fixed(if contains(&head[..head.len().min(64)], b"theora") {
+            "video/ogg"
+        } else {
+            "audio/ogg"
+        });
+    }
+    if starts_at(head, 0, b"BM") && head.len() >= 14 && starts_at(head, 6, b"\0\0\0\0") {
+        return fixed("image/bmp");
+    }
+    if starts_at(head, 0, b"II*\0") || starts_at(head, 0, b"MM\0*") {
+        return fixed("image/tiff");
+    }
+    if starts_at(head, 0, b"\0\0\x01\0") && head.len() >= 6 && head[4] > 0 {
+        return fixed("image/vnd.microsoft.icon");
+    }
+    if starts_at(head, 0, b"\x1f\x8b") {
+        return fixed("application/gzip");
+    }
+    if starts_at(head, 0, b"BZh") {
+        return fixed("application/x-bzip");
+    }
+    if starts_at(head, 0, b"\xfd7zXZ\0") {
+        return fixed("application/x-xz");
+    }
+    if starts_at(head, 0, b"7z\xbc\xaf\x27\x1c") {
+        return fixed("application/x-7z-compressed");
+    }
+    if starts_at(head, 0, b"Rar!\x1a\x07") {
+        return fixed("application/vnd.rar");
+    }
+    if starts_at(head, 0, b"\x28\xb5\x2f\xfd") {
+        return fixed("application/zstd");
+    }
+    if starts_at(head, 257, b"ustar") {
+        return fixed("application/x-tar");
+    }
+    if starts_at(head, 0, b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1") {
+        return fixed("application/x-ole-storage");
+    }
+    if starts_at(head, 0, b"SQLite format 3\0") {
+        return fixed("application/vnd.sqlite3");
+    }
+    if starts_at(head, 0, b"\x7fELF") {
+        // e_type 3 (ET_DYN) covers both shared libraries and PIE executables
+        return fixed(if head.len() > 16 && head[16] == 1 {
+            "application/x-object"
+        } else if head.len() > 16 && head[16] == 3 {
+            "application/x-sharedlib"
+        } else {
+            "application/x-executable"
+        });
+    }
+    if starts_at(head, 0, b"MZ") {
+        return fixed("application/x-ms-dos-executable");
+    }
+    if starts_at(head, 0, b"wOFF") {
+        return fixed("font/woff");
+    }
+    if starts_at(head, 0, b"wOF2") {
+        return fixed("font/woff2");
+    }
+    if starts_at(head, 0, b"OTTO") {
+        return fixed("font/otf");
+    }
+    if starts_at(head, 0, b"\0\x01\0\0\0") {
+        return fixed("font/ttf");
+    }
+    if starts_at(head, 0, b"%!PS") {
+        return fixed("application/postscript");
+    }
+    if starts_at(head, 0, b"{\\rtf") {
+        return fixed("application/rtf");
+    }
+
+    sniff_text_markup(head)
+}
+
+/// Text formats that announce themselves in the first bytes
+fn sniff_text_markup(head: &[u8]) -> Option<String> {
+    let text = head.strip_prefix(b"\xef\xbb\xbf").unwrap_or(head);
+    let start = text.iter().position(|b| !b.is_ascii_whitespace())?;
+    let text = &text[start..];
+
+    if let Some(line) = text.strip_prefix(b"#!") {
+        let line = line.split(|&b| b == b'\n').next().unwrap_or(line);
+        let line = String::from_utf8_lossy(line);
+        let mut words = line.split_whitespace();
+        let mut interpreter = words.next().unwrap_or("").rsplit('/').next().unwrap_or("");
+        if interpreter == "env" {
+            interpreter = words.find(|w| !w.starts_with('-')).unwrap_or("");
+        }
+        let mime = match interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.') {
+            "sh" | "bash" | "dash" | "zsh" | "ksh" => "application/x-shellscript",
+            "python" => "text/x-python",
+            "perl" => "application/x-perl",
+            "ruby" => "application/x-ruby",
+            "node" => "application/javascript",
+            _ => return None,
+        };
+        return Some(mime.to_string());
+    }
+
+    let lower = |n: usize| text[..text.len().min(n)].to_ascii_lowercase();
+    if lower(5) == b"<?xml" {
+        return Some(if contains(head, b"<svg") {
+            "image/svg+xml".to_string()
+        } else {
+            "application/xml".to_string()
+        });
+    }
+    if lower(14) == b"<!doctype html" || lower(5) == b"<html" {
+        return Some("text/html".to_string());
+    }
+    if lower(4) == b"<svg" {
+        return Some("image/svg+xml".to_string());
+    }
+    None
+}
+
+/// Look at the entry names of a ZIP archive to tell OOXML, ODF, EPUB,
+/// JAR and APK files apart from plain zips.
+fn sniff_zip(file: &mut File, head: &[u8]) -> Option<&'static str> {
+    // ODF and EPUB store an uncompressed "mimetype" entry first
+    if head.len() >= 38 && &head[30..38] == b"mimetype" && u16_le(head, 8) == 0 {
+        let size = u32_le(head, 18);
+        let start = 38 + u16_le(head, 28);
+        if let Some(content) = head.get(start..start + size) {
+            match std::str::from_utf8(content).map(str::trim) {
+                Ok("application/epub+zip") => return Some("application/epub+zip"),
+                Ok("application/vnd.oasis.opendocument.text") => {
+                    return Some("application/vnd.oasis.opendocument.text")
+                }
+                Ok("application/vnd.oasis.opendocument.spreadsheet") => {
+                    return Some("application/vnd.oasis.opendocument.spreadsheet")
+                }
+                Ok("application/vnd.oasis.opendocument.presentation") => {
+                    return Some("application/vnd.oasis.opendocument.presentation")
+                }
+                Ok("application/vnd.oasis.opendocument.graphics") => {
+                    return Some("application/vnd.oasis.opendocument.graphics")
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for name in zip_entry_names(file)? {
+        let mime = match name.as_str() {
+            "word/document.xml" => {
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+            }
+            "xl/workbook.xml" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            "ppt/presentation.xml" => {
+                "application/vnd.openxmlformats-officedocument.presentationml.presentation"
+            }
+            "AndroidManifest.xml" => "application/vnd.android.package-archive",
+            "META-INF/MANIFEST.MF" => "application/x-java-archive",
+            _ => continue,
+        };
+        return Some(mime);
+    }
+    None
+}
+
+/// Entry names from the ZIP central directory
+fn zip_entry_names(file: &mut File) -> Option<Vec<String>> {
+    const EOCD: &[u8] = b"PK\x05\x06";
+    const CENTRAL: &[u8] = b"PK\x01\x02";
+    const MAX_CENTRAL: usize = 4 * 1024 * 1024;
+
+    let len = file.metadata().ok()?.len();
+    // End record (22 bytes) plus the largest possible archive comment
+    let tail_len = len.min(22 + 65535);
+    file.seek(SeekFrom::Start(len - tail_len)).ok()?;
+    let mut tail = vec![0u8; tail_len as usize];
+    file.read_exact(&mut tail).ok()?;
+
+    let eocd = tail.windows(4).rposition(|w| w == EOCD)?;
+    let record = tail.get(eocd..eocd + 22)?;
+    let cd_size = u32_le(record, 12).min(MAX_CENTRAL);
+    let cd_offset = u32_le(record, 16) as u64;
+
+    file.seek(SeekFrom::Start(cd_offset)).ok()?;
+    let mut cd = vec![0u8; cd_size];
+    file.read_exact(&mut cd).ok()?;
+
+    let mut names = Vec::new();
+    let mut at = 0;
+    while at + 46 <= cd.len() && &cd[at..at + 4] == CENTRAL {
+        let name_len = u16_le(&cd, at + 28);
+        let extra_len = u16_le(&cd, at + 30);
+        let comment_len = u16_le(&cd, at + 32);
+        let name = cd.get(at + 46..at + 46 + name_len)?;
+        names.push(String::from_utf8_lossy(name).into_owned());
+        at += 46 + name_len + extra_len + comment_len;
+    }
+    Some(names)
+}
+
+/// No NUL bytes and valid UTF-8 (a multi-byte char cut off at the end is fine)
+fn looks_like_text(head: &[u8]) -> bool {
+    if head.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none() && head.len() - e.valid_up_to() < 4,
+    }
+}
+
+// --- Detection ---
+
+impl MimeDb {
+    fn name_type(&self, name: &str) -> Option<String> {
+        if self.is_empty() {
+            return builtin_glob(name).map(str::to_string);
+        }
+        self.match_name(name).map(|m| self.canonical(m).to_string())
+    }
+
+    fn detect(&self, path: &Path) -> String {
+        let Ok(metadata) = std::fs::metadata(path) else {
+            return OCTET_STREAM.to_string();
+        };
+        if metadata.is_dir() {
+            return DIRECTORY.to_string();
+        }
+
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let by_name = self.name_type(&name);
+
+        let mut head = Vec::with_capacity(HEAD_LEN);
+        let magic = File::open(path).ok().and_then(|mut file| {
+            (&mut file).take(HEAD_LEN as u64).read_to_end(&mut head).ok()?;
+            sniff(&mut file, &head)
+        });
+
+        match (magic, by_name) {
+            // Magic found a container, the name says what kind (zip → jar, xml → svg)
+            (Some(magic), Some(by_name)) if self.is_subclass(&by_name, &magic) => by_name,
+            // `notes.txt` stays plain text even when it starts with `#!` or `<html>`
+            (Some(magic), Some(by_name)) if by_name == "text/plain" && self.is_subclass(&magic, &by_name) => by_name,
+            (Some(magic), _) => magic,
+            (None, Some(by_name)) => by_name,
+            (None, None) if metadata.len() == 0 => "application/x-zerosize".to_string(),
+            (None, None) if looks_like_text(&head) => "text/plain".to_string(),
+            (None, None) => OCTET_STREAM.to_string(),
+        }
+    }
+
+    fn ancestors(&self, mime: &str) -> Vec<String> {
+        let mut out: Vec<String> = Vec::new();
+        let mut queue = vec![self.canonical(mime).to_string()];
+
+        while let Some(current) = queue.pop() {
+            if out.contains(&current) {
+                continue;
+            }
+
+            let mut parents: Vec<String> = self.subclasses.get(&current).cloned().unwrap_or_default();
+            if parents.is_empty() {
+                if current.ends_with("+xml") {
+                    parents.push("application/xml".to_string());
+                } else if current.ends_with("+zip") {
+                    parents.push("application/zip".to_string());
+                } else if current.starts_with("text/") && current != "text/plain" {
+                    parents.push("text/plain".to_string());
+                }
+            }
+            out.push(current);
+            // Reverse so the first listed parent is visited first
+            queue.extend(parents.into_iter().map(|p| self.canonical(&p).to_string()).rev());
+        }
+
+        if !mime.starts_with("inode/") && !out.iter().any(|m| m == OCTET_STREAM) {
+            out.push(OCTET_STREAM.to_string());
+        }
+        out
+    }
+
+    fn is_subclass(&self, mime: &str, parent: &str) -> bool {
+        let parent = self.canonical(parent);
+        self.ancestors(mime).iter().any(|m| m == parent)
+    }
+}
+
+// --- Public API ---
+
+/// MIME type from the file name alone
+pub fn from_name(name: &str) -> Option<String> {
+    db().name_type(name)
+}
+
+/// Detect the MIME type of a file or folder, looking at its content first
+pub fn detect(path: &Path) -> String {
+    db().detect(path)
+}
+
+/// The type itself (canonicalized) followed by all of its ancestors. Implicit rules from the shared-mime-info spec apply:
+/// every text/* is text/plain, `+xml` / `+zip` suffixes are xml / zip, and
+/// every non-inode type is application/octet-stream.
+pub fn ancestors(mime: &str) -> Vec<String> {
+    db().ancestors(mime)
+}
+
+/// True if `mime` is `parent` or (transitively) a subclass of it
+pub fn is_subclass(mime: &str, parent: &str) -> bool {
+    db().is_subclass(mime, parent)
+}
+
+/// Resolve an alias such as `image/x-icon` to its canonical name
+pub fn canonical(mime: &str) -> String {
+    db().canonical(mime).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small shared-mime-info database, so results don't depend on the host
+    fn test_db() -> MimeDb {
+        let mut db = MimeDb::default();
+        db.parse_globs2(
+            "# weight:type:glob\n\
+             50:text/plain:*.txt\n\
+             50:application/zip:*.zip\n\
+             50:application/x-java-archive:*.jar\n\
+             50:application/gzip:*.gz\n\
+             55:application/x-compressed-tar:*.tar.gz\n\
+             50:text/x-csrc:*.c\n\
+             50:text/x-c++src:*.C:cs\n\
+             50:text/x-makefile:makefile\n\
+             50:text/x-readme:README*\n\
+             50:image/x-icon:*.ico\n\
+             50:application/xml:*.xml\n\
+             60:text/x-heavy:*.w\n\
+             40:text/x-light:*.w\n",
+        );
+        db.parse_subclasses(
+            "application/x-java-archive application/zip\n\
+             application/x-shellscript text/plain\n\
+             application/xml text/plain\n\
+             text/x-python text/plain\n\
+             application/x-compressed-tar application/gzip\n",
+        );
+        db.parse_aliases("image/x-icon image/vnd.microsoft.icon\n");
+        db
+    }
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("filecanvas-mime-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A stored (uncompressed) ZIP archive with empty entries
+    fn zip(names: &[&str]) -> Vec<u8> {
+        let (mut out, mut central) = (Vec::new(), Vec::new());
+        for name in names {
+            let offset = out.len() as u32;
+            out.extend_from_slice(b"PK\x03\x04");
+            out.extend_from_slice(&[20, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            out.extend_from_slice(&[0; 12]);
+            out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            out.extend_from_slice(&[0, 0]);
+            out.extend_from_slice(name.as_bytes());
+
+            central.extend_from_slice(b"PK\x01\x02");
+            central.extend_from_slice(&[20, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            central.extend_from_slice(&[0; 12]);
+            central.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            central.extend_from_slice(&[0; 12]);
+            central.extend_from_slice(&offset.to_le_bytes());
+            central.extend_from_slice(name.as_bytes());
+        }
+        let cd_offset = out.len() as u32;
+        out.extend_from_slice(&central);
+        out.extend_from_slice(b"PK\x05\x06\0\0\0\0");
+        out.extend_from_slice(&(names.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(names.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+        out.extend_from_slice(&cd_offset.to_le_bytes());
+        out.extend_from_slice(&[0, 0]);
+        out
+    }
+
+    #[test]
+    fn globs_prefer_literals_long_suffixes_and_weight() {
+        let db = test_db();
+        assert_eq!(db.match_name("Makefile"), Some("text/x-makefile"));
+        assert_eq!(db.match_name("backup.tar.gz"), Some("application/x-compressed-tar"));
+        assert_eq!(db.match_name("notes.gz"), Some("application/gzip"));
+        assert_eq!(db.match_name("README.md"), Some("text/x-readme"));
+        assert_eq!(db.match_name("thing.w"), Some("text/x-heavy"));
+        assert_eq!(db.match_name("photo"), None);
+        assert_eq!(db.name_type("favicon.ico").as_deref(), Some("image/vnd.microsoft.icon"));
+    }
+
+    #[test]
+    fn globs_ignore_case_unless_marked_case_sensitive() {
+        let db = test_db();
+        assert_eq!(db.match_name("NOTES.TXT"), Some("text/plain"));
+        assert_eq!(db.match_name("main.c"), Some("text/x-csrc"));
+        assert_eq!(db.match_name("main.C"), Some("text/x-c++src"));
+    }
+
+    #[test]
+    fn ancestors_follow_subclasses_aliases_and_implicit_rules() {
+        let db = test_db();
+        assert_eq!(
+            db.ancestors("application/x-java-archive"),
+            ["application/x-java-archive", "application/zip", OCTET_STREAM]
+        );
+        assert_eq!(db.ancestors("text/x-csrc"), ["text/x-csrc", "text/plain", OCTET_STREAM]);
+        assert_eq!(
+            db.ancestors("image/svg+xml"),
+            ["image/svg+xml", "application/xml", "text/plain", OCTET_STREAM]
+        );
+        assert_eq!(db.ancestors("application/epub+zip"), ["application/epub+zip", "application/zip", OCTET_STREAM]);
+        assert_eq!(db.ancestors("image/x-icon")[0], "image/vnd.microsoft.icon");
+        assert_eq!(db.ancestors(DIRECTORY), [DIRECTORY]);
+        assert!(db.is_subclass("application/x-compressed-tar", "application/gzip"));
+        assert!(!db.is_subclass("application/zip", "application/x-java-archive"));
+    }
+
+    #[test]
+    fn magic_wins_unless_the_name_is_more_specific() {
+        let db = test_db();
+        let dir = scratch("magic");
+        let detect = |name: &str, contents: &[u8]| {
+            std::fs::write(dir.join(name), contents).unwrap();
+            db.detect(&dir.join(name))
+        };
+
+        assert_eq!(detect("photo.txt", b"\x89PNG\r\n\x1a\n...."), "image/png");
+        assert_eq!(detect("report", b"%PDF-1.7\n"), "application/pdf");
+        assert_eq!(detect("backup.tar.gz", b"\x1f\x8b\x08\0"), "application/x-compressed-tar");
+        assert_eq!(detect("data.gz", b"\x1f\x8b\x08\0"), "application/gzip");
+
+        let mut elf = b"\x7fELF".to_vec();
+        elf.extend_from_slice(&[2, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3, 0]);
+        assert_eq!(detect("libfoo.so", &elf), "application/x-sharedlib");
+
+        // The container tells zip from jar and Office files
+        assert_eq!(detect("plain.bin", &zip(&["a.txt"])), "application/zip");
+        assert_eq!(detect("plain.jar", &zip(&["a.txt"])), "application/x-java-archive");
+        assert_eq!(detect("app.zip", &zip(&["META-INF/MANIFEST.MF"])), "application/x-java-archive");
+        assert_eq!(
+            detect("letter", &zip(&["[Content_Types].xml", "word/document.xml"])),
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+        );
+
+        assert_eq!(detect("drawing.xml", b"<?xml version=\"1.0\"?><svg/>"), "image/svg+xml");
+        assert_eq!(detect("empty", b""), "application/x-zerosize");
+        assert_eq!(detect("notes", "plain words, ünïcode".as_bytes()), "text/plain");
+        assert_eq!(detect("blob", b"\0\x01\x02\x03"), OCTET_STREAM);
+        assert_eq!(db.detect(&dir), DIRECTORY);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_txt_name_beats_a_shebang() {
+        let db = test_db();
+        let dir = scratch("shebang");
+        let detect = |name: &str, contents: &str| {
+            std::fs::write(dir.join(name), contents).unwrap();
+            db.detect(&dir.join(name))
+        };
+
+        assert_eq!(detect("install", "#!/bin/sh\necho hi\n"), "application/x-shellscript");
+        assert_eq!(detect("tool", "#!/usr/bin/env -S python3 -u\n"), "text/x-python");
+        assert_eq!(detect("notes.txt", "#!/bin/sh\necho hi\n"), "text/plain");
+        assert_eq!(detect("page.txt", "<!DOCTYPE html><html></html>"), "text/plain");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,320 @@
+// src-tauri/src/mimeapps.rs
+//
+// Default applications and associations from the XDG mime-apps spec:
+// `mimeapps.list` ([Default Applications], [Added Associations],
+// [Removed Associations]) and the `mimeinfo.cache` files written by
+// update-desktop-database.
+//
+// Files are read in precedence order (user config first). A removal hides
+// associations added by lower-precedence files and by the caches.
+
+use crate::desktop_entry::current_desktops;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+const DEFAULT: &str = "Default Applications";
+const ADDED: &str = "Added Associations";
+const REMOVED: &str = "Removed Associations";
+
+// --- Data Structures ---
+
+/// What the mimeapps files say about one set of MIME types
+#[derive(Debug, Default)]
+pub struct Associations {
+    /// Desktop id of the default app, if one is configured and installed
+    pub default: Option<String>,
+    /// Associated desktop ids in preference order
+    pub added: Vec<String>,
+    pub removed: HashSet<String>,
+}
+
+/// Minimal INI document that keeps comments and unknown keys intact on save
+#[derive(Debug, Default)]
+struct IniFile {
+    /// Lines before the first section
+    preamble: Vec<String>,
+    sections: Vec<(String, Vec<String>)>,
+}
+
+// --- Helpers ---
+
+fn env_dir(var: &str, fallback: &str) -> PathBuf {
+    match std::env::var(var) {
+        Ok(d) if !d.is_empty() => PathBuf::from(d),
+        _ => PathBuf::from(std::env::var("HOME").unwrap_or_default()).join(fallback),
+    }
+}
+
+fn env_dirs(var: &str, fallback: &str) -> Vec<PathBuf> {
+    std::env::var(var)
+        .ok()
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| fallback.to_string())
+        .split(':')
+        .map(PathBuf::from)
+        .collect()
+}
+
+fn config_home() -> PathBuf {
+    env_dir("XDG_CONFIG_HOME", ".config")
+}
+
+fn data_home() -> PathBuf {
+    env_dir("XDG_DATA_HOME", ".local/share")
+}
+
+/// `mimeapps.list` files in precedence order, desktop-specific ones first
+fn mimeapps_files() -> Vec<PathBuf> {
+    let mut dirs = vec![config_home()];
+    dirs.extend(env_dirs("XDG_CONFIG_DIRS", "/etc/xdg"));
+    dirs.push(data_home().join("applications"));
+    dirs.extend(
+        env_dirs("XDG_DATA_DIRS", "/usr/local/share:/usr/share")
+            .into_iter()
+            .map(|d| d.join("applications")),
+    );
+
+    let desktops = current_desktops();
+    let mut files = Vec::new();
+    for dir in dirs {
+        for desktop in &desktops {
+            files.push(dir.join(format!("{}-mimeapps.list", desktop)));
+        }
+        files.push(dir.join("mimeapps.list"));
+    }
+    files
+}
+
+fn mimeinfo_caches() -> Vec<PathBuf> {
+    let mut dirs = vec![data_home()];
+    dirs.extend(env_dirs("XDG_DATA_DIRS", "/usr/local/share:/usr/share"));
+    dirs.into_iter()
+        .map(|d| d.join("applications/mimeinfo.cache"))
+        .collect()
+}
+
+/// Desktop file id of an installed .desktop file: its path relative to the
+/// `applications` directory with `/` replaced by `-`
+pub fn desktop_id(path: &Path) -> Option<String> {
+    let mut parts = Vec::new();
+    for component in path.iter().rev() {
+        let part = component.to_str()?;
+        if part == "applications" {
+            parts.reverse();
+            return (!parts.is_empty()).then(|| parts.join("-"));
+        }
+        parts.push(part);
+    }
+    None
+}
+
+/// Folders .desktop files are installed in, highest precedence first
+fn application_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![
+        data_home().join("applications"),
+        data_home().join("flatpak/exports/share/applications"),
+        PathBuf::from("/var/lib/flatpak/exports/share/applications"),
+    ];
+    dirs.extend(
+        env_dirs("XDG_DATA_DIRS", "/usr/local/share:/usr/share")
+            .into_iter()
+            .map(|d| d.join("applications")),
+    );
+    dirs
+}
+
+/// Desktop file id of `path` if it is a .desktop file installed in one of
+/// the application folders
+pub fn installed_desktop_id(path: &Path) -> Option<String> {
+    let plain = path.components().all(|c| matches!(c, Component::RootDir | Component::Normal(_)));
+    let installed = plain
+        && path.extension().is_some_and(|e| e == "desktop")
+        && path.is_file()
+        && application_dirs().iter().any(|dir| path.starts_with(dir));
+    installed.then(|| desktop_id(path)).flatten()
+}
+
+fn split_ids(value: &str) -> impl Iterator<Item = &str> {
+    value.split(';').map(str::trim).filter(|s| !s.is_empty())
+}
+
+impl IniFile {
+    fn parse(text: &str) -> Self {
+        let mut ini = IniFile::default();
+        for line in text.lines() {
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') && trimmed.ends_with(']') {
+                ini.sections
+                    .push((trimmed[1..trimmed.len() - 1].to_string(), Vec::new()));
+            } else if let Some((_, lines)) = ini.sections.last_mut() {
+                lines.push(line.to_string());
+            } else {
+                ini.preamble.push(line.to_string());
+            }
+        }
+        ini
+    }
+
+    fn read(path: &Path) -> Option<Self> {
+        fs::read_to_string(path).ok().map(|t| Self::parse(&t))
+    }
+
+    fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.sections
+            .iter()
+            .filter(|(name, _)| name == section)
+            .flat_map(|(_, lines)| lines.iter())
+            .filter_map(|l| l.split_once('='))
+            .find(|(k, _)| k.trim() == key)
+            .map(|(_, v)| v.trim())
+    }
+
+    /// Replace the key's value, or append it to the section (created if missing).
+    /// An empty value removes the key.
+    fn set(&mut self, section: &str, key: &str, value: &str) {
+        let index = match self.sections.iter().position(|(name, _)| name == section) {
+            Some(i) => i,
+            None => {
+                if let Some((_, last)) = self.sections.last_mut() {
+                    if last.last().is_some_and(|l| !l.trim().is_empty()) {
+                        last.push(String::new());
+                    }
+                }
+                self.sections.push((section.to_string(), Vec::new()));
+                self.sections.len() - 1
+            }
+        };
+        let lines = &mut self.sections[index].1;
+        let existing = lines
+            .iter()
+            .position(|l| l.split_once('=').is_some_and(|(k, _)| k.trim() == key));
+
+        match (existing, value.is_empty()) {
+            (Some(i), true) => {
+                lines.remove(i);
+            }
+            (Some(i), false) => lines[i] = format!("{}={}", key, value),
+            (None, true) => {}
+            (None, false) => {
+                // Keep the entry above any trailing blank lines of the section
+                let at = lines
+                    .iter()
+                    .rposition(|l| !l.trim().is_empty())
+                    .map(|i| i + 1)
+                    .unwrap_or(0);
+                lines.insert(at, format!("{}={}", key, value));
+            }
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut out = String::new();
+        for line in &self.preamble {
+            out.push_str(line);
+            out.push('\n');
+        }
+        for (name, lines) in &self.sections {
+            // Sections left with nothing but blank lines are dropped
+            if lines.iter().all(|l| l.trim().is_empty()) {
+                continue;
+            }
+            out.push_str(&format!("[{}]\n", name));
+            for line in lines {
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+// --- Lookup ---
+
+/// Collect defaults and associations for `mimes` (the detected type plus its
+/// parents, most specific first). `installed` tells whether a desktop id
+/// exists on this system; defaults pointing at missing apps are skipped.
+pub fn associations(mimes: &[String], installed: impl Fn(&str) -> bool) -> Associations {
+    let mut result = Associations::default();
+    let lists: Vec<IniFile> = mimeapps_files().iter().filter_map(|p| IniFile::read(p)).collect();
+
+    // Default: the first installed entry, checking each MIME type across all
+    // files before falling back to its parent
+    'outer: for mime in mimes {
+        for list in &lists {
+            if let Some(value) = list.get(DEFAULT, mime) {
+                if let Some(id) = split_ids(value).find(|id| installed(id)) {
+                    result.default = Some(id.to_string());
+                    break 'outer;
+                }
+            }
+        }
+    }
+
+    let push = |result: &mut Associations, id: &str| {
+        if !result.removed.contains(id) && !result.added.iter().any(|a| a == id) {
+            result.added.push(id.to_string());
+        }
+    };
+
+    for list in &lists {
+        for mime in mimes {
+            if let Some(value) = list.get(ADDED, mime) {
+                split_ids(value).for_each(|id| push(&mut result, id));
+            }
+        }
+        // Removals hide associations from lower-precedence files and the caches
+        for mime in mimes {
+            if let Some(value) = list.get(REMOVED, mime) {
+                result.removed.extend(split_ids(value).map(str::to_string));
+            }
+        }
+    }
+
+    for cache in mimeinfo_caches() {
+        if let Some(ini) = IniFile::read(&cache) {
+            for mime in mimes {
+                if let Some(value) = ini.get("MIME Cache", mime) {
+                    split_ids(value).for_each(|id| push(&mut result, id));
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Make `desktop_id` the default for `mime` in the user's mimeapps.list.
+/// Like gio/xdg-mime, the app is also moved to the front of the added
+/// associations and taken out of the removed ones.
+pub fn set_default(mime: &str, desktop_id: &str) -> Result<(), String> {
+    let dir = config_home();
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join("mimeapps.list");
+    let mut ini = IniFile::read(&path).unwrap_or_default();
+
+    ini.set(DEFAULT, mime, &format!("{};", desktop_id));
+
+    let added: Vec<String> = std::iter::once(desktop_id.to_string())
+        .chain(
+            ini.get(ADDED, mime)
+                .map(|v| split_ids(v).filter(|id| *id != desktop_id).map(str::to_string).collect::<Vec<_>>())
+                .unwrap_or_default(),
+        )
+        .collect();
+    ini.set(ADDED, mime, &format!("{};", added.join(";")));
+
+    let removed: Vec<String> = ini
+        .get(REMOVED, mime)
+        .map(|v| split_ids(v).filter(|id| *id != desktop_id).map(str::to_string).collect())
+        .unwrap_or_default();
+    ini.set(
+        REMOVED,
+        mime,
+        &if removed.is_empty() { String::new() } else { format!("{};", removed.join(";")) },
+    );
+
+    let tmp = path.with_extension("list.tmp");
+    fs::write(&tmp, ini.to_text()).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, &path).map_err(|e| e.to_string())
+}
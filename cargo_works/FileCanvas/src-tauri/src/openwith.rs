@@ -1,5 +1,10 @@
+use crate::activity::{ActivityEntry, ActivityLog, Operation, Via};
+use crate::error::{ErrorCode, FsError};
+use crate::launcher::{self, LaunchError, Launched};
 use serde::Serialize;
 use std::process::Command;
+use tauri::async_runtime::spawn_blocking;
+use tauri::Runtime;
 
 #[derive(Debug, Serialize, Clone)]
 pub struct OpenWithApp {
@@ -8,153 +13,444 @@ pub struct OpenWithApp {
     pub exec: String,
     pub icon: Option<String>,
     pub is_recommended: bool,
+    /// The system default for this file's type (mimeapps.list)
+    pub is_default: bool,
+    /// Desktop action id ("new-private-window") when this entry is an extra
+    /// action of an app rather than the app itself
+    pub action: Option<String>,
+    /// Needs to run inside a terminal emulator (Terminal=true)
+    pub terminal: bool,
+    pub categories: Vec<String>,
+    /// Position in the "recent" group for this file type (0 = best), if used before
+    pub recent_rank: Option<usize>,
+    /// Chosen to always open this file type from `open_file`
+    pub is_always: bool,
+}
+
+/// Outcome of opening one file
+#[derive(Debug, Serialize, Clone)]
+pub struct LaunchResult {
+    pub path: String,
+    pub success: bool,
+    pub error: Option<FsError>,
+}
+
+impl LaunchResult {
+    pub fn new(path: &str, outcome: Result<(), FsError>) -> Self {
+        LaunchResult {
+            path: path.to_string(),
+            success: outcome.is_ok(),
+            error: outcome.err(),
+        }
+    }
 }
 
 #[tauri::command]
-pub fn list_open_with_apps(file_path: String) -> Vec<OpenWithApp> {
+pub fn list_open_with_apps<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    file_path: String,
+) -> Result<Vec<OpenWithApp>, FsError> {
+    let file_path = crate::path_policy::existing(&app_handle, &file_path)?
+        .to_string_lossy()
+        .to_string();
+    let mut apps = system_apps(&file_path);
+
+    // Custom commands for this file type go first; catch-all ones follow the
+    // recommended system apps
+    let custom = crate::custom_apps::load(&app_handle).unwrap_or_default();
+    if !custom.is_empty() {
+        let file_mimes = crate::mime::ancestors(&crate::mime::detect(std::path::Path::new(&file_path)));
+        let (mut matched, mut generic) = (Vec::new(), Vec::new());
+        for app in custom {
+            match crate::custom_apps::match_file(&app, &file_path, &file_mimes) {
+                Some(true) => matched.push(custom_entry(app, true)),
+                Some(false) => generic.push(custom_entry(app, false)),
+                None => {}
+            }
+        }
+        let split = apps.iter().position(|a| !a.is_recommended).unwrap_or(apps.len());
+        apps.splice(split..split, generic);
+        apps.splice(0..0, matched);
+    }
+
+    // Apps this type was opened with before, ranked by frecency
+    if let Ok(usage) = crate::app_usage::for_file(&app_handle, &file_path) {
+        let ranked = usage
+            .apps
+            .iter()
+            .filter(|u| u.count > 0)
+            .take(crate::app_usage::MAX_RECENT);
+        for (rank, used) in ranked.enumerate() {
+            if let Some(app) = apps.iter_mut().find(|a| a.id == used.app_id) {
+                app.recent_rank = Some(rank);
+            }
+        }
+        if let Some(always) = &usage.always {
+            if let Some(app) = apps.iter_mut().find(|a| &a.id == always) {
+                app.is_always = true;
+            }
+        }
+    }
+    Ok(apps)
+}
+
+fn system_apps(file_path: &str) -> Vec<OpenWithApp> {
     #[cfg(target_os = "linux")]
     {
-        return linux_apps(&file_path);
+        return linux_apps(file_path);
     }
 
     #[cfg(target_os = "windows")]
     {
-        return windows_apps(&file_path);
+        return windows_apps(file_path);
     }
 
     #[cfg(target_os = "macos")]
     {
-        return macos_apps(&file_path);
+        return macos_apps(file_path);
     }
 
     #[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
     Vec::new()
 }
 
+fn custom_entry(app: crate::custom_apps::CustomApp, is_recommended: bool) -> OpenWithApp {
+    OpenWithApp {
+        id: format!("{}{}", crate::custom_apps::ID_PREFIX, app.id),
+        name: app.name,
+        exec: app.command,
+        icon: None,
+        is_recommended,
+        is_default: false,
+        action: None,
+        terminal: app.terminal,
+        categories: Vec::new(),
+        recent_rank: None,
+        is_always: false,
+    }
+}
+
 #[tauri::command]
-pub fn open_with_app(exec: String, file_path: String) -> Result<(), String> {
-    #[cfg(target_os = "macos")]
-    {
-        // macOS needs special handling for .app bundles
-        if exec.ends_with(".app") {
-            Command::new("open")
-                .arg("-a")
-                .arg(&exec)
-                .arg(&file_path)
-                .spawn()
-                .map_err(|e| e.to_string())?;
-        } else {
-            Command::new(&exec)
-                .arg(&file_path)
-                .spawn()
-                .map_err(|e| e.to_string())?;
-        }
-        return Ok(());
+pub async fn open_with_app<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    exec: String,
+    file_path: String,
+    app_id: Option<String>,
+) -> Result<(), FsError> {
+    let file_path = crate::path_policy::existing(&app_handle, &file_path)?
+        .to_string_lossy()
+        .to_string();
+    let results = spawn_blocking(move || {
+        open_paths_with_app(&app_handle, &exec, &[file_path], app_id.as_deref())
+    })
+    .await
+    .map_err(|e| FsError::from(e.to_string()))?;
+
+    match results.into_iter().next() {
+        Some(LaunchResult { error: Some(e), .. }) => Err(e),
+        _ => Ok(()),
     }
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        // Parse the exec command
-        let parts: Vec<&str> = exec.split_whitespace().collect();
-        if parts.is_empty() {
-            return Err("Empty exec command".to_string());
+/// Open several files with one app: a single process for all of them when
+/// the Exec line takes a list (%F / %U), one process per file otherwise
+#[tauri::command]
+pub async fn open_files_with_app<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    exec: String,
+    file_paths: Vec<String>,
+    app_id: Option<String>,
+) -> Result<Vec<LaunchResult>, FsError> {
+    spawn_blocking(move || {
+        let mut results: Vec<Option<LaunchResult>> = vec![None; file_paths.len()];
+        // Paths outside the approved folders fail on their own; the rest still open
+        let mut allowed = Vec::new();
+        let mut indices = Vec::new();
+        for (i, path) in file_paths.iter().enumerate() {
+            match crate::path_policy::existing(&app_handle, path) {
+                Ok(p) => {
+                    allowed.push(p.to_string_lossy().to_string());
+                    indices.push(i);
+                }
+                Err(e) => results[i] = Some(LaunchResult::new(path, Err(e))),
+            }
+        }
+        // Starting the app without any of its files would look like success
+        let launched = if allowed.is_empty() {
+            Vec::new()
+        } else {
+            open_paths_with_app(&app_handle, &exec, &allowed, app_id.as_deref())
+        };
+        for (i, result) in indices.into_iter().zip(launched) {
+            results[i] = Some(result);
         }
+        file_paths
+            .iter()
+            .zip(results)
+            .map(|(path, result)| {
+                result.unwrap_or_else(|| LaunchResult::new(path, Err(FsError::from("Not opened".to_string()))))
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| FsError::from(e.to_string()))
+}
 
-        let first_cmd = parts[0];
-
-        // Handle Flatpak apps
-        if first_cmd == "flatpak" || first_cmd == "/usr/bin/flatpak" {
-            // Look for the app ID in the command
-            // Common patterns: "flatpak run org.videolan.VLC"
-            let app_id = parts.iter()
-                .skip_while(|&&p| p == "flatpak" || p == "run" || p.starts_with('-'))
-                .find(|&&p| p.contains('.'))
-                .ok_or_else(|| "Could not find Flatpak app ID".to_string())?;
-
-            Command::new("flatpak")
-                .arg("run")
-                .arg(app_id)
-                .arg(&file_path)
-                .spawn()
-                .map_err(|e| format!("Failed to launch Flatpak app: {}", e))?;
-            return Ok(());
+/// Launch and record the usage, once per file type that was opened.
+/// Blocks for the launcher's watch window.
+pub fn open_paths_with_app<R: Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    exec: &str,
+    files: &[String],
+    app_id: Option<&str>,
+) -> Vec<LaunchResult> {
+    let results = launch_app(app_handle, exec, files, app_id);
+
+    // Usage only feeds the recent list; it must not fail a launch that worked
+    if let Some(id) = app_id {
+        let mut types = std::collections::HashSet::new();
+        for result in results.iter().filter(|r| r.success) {
+            if !types.insert(crate::app_usage::type_key(&result.path)) {
+                continue;
+            }
+            if let Err(e) = crate::app_usage::record(app_handle, &result.path, id, exec) {
+                // The launch worked, so the entry says so and carries the note
+                let mut entry = ActivityEntry::new(Operation::Open, Via::Panel).from(&result.path).to(id);
+                entry.success = true;
+                entry.error = Some(format!("Could not record app usage: {}", e));
+                match ActivityLog::from_app(app_handle) {
+                    Ok(log) => log.append(&entry),
+                    Err(log_error) => eprintln!("activity log: {}", log_error),
+                }
+            }
         }
+    }
+    results
+}
+
+/// Start every process first, then supervise them, so opening many files
+/// costs one watch window rather than one per process
+fn launch_app<R: Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    exec: &str,
+    files: &[String],
+    app_id: Option<&str>,
+) -> Vec<LaunchResult> {
+    let pending = start_processes(app_handle, exec, files, app_id);
+    pending
+        .into_iter()
+        .flat_map(|(files, started)| {
+            let outcome = started.and_then(Launched::supervise).map_err(FsError::from);
+            files
+                .into_iter()
+                .map(|f| LaunchResult::new(&f, outcome.clone()))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// The processes to start for `files`, each with the files it was given
+type Pending = Vec<(Vec<String>, Result<Launched, LaunchError>)>;
+
+fn start_processes<R: Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    exec: &str,
+    files: &[String],
+    app_id: Option<&str>,
+) -> Pending {
+    // A problem with the app itself fails every file
+    let all_failed = |e: LaunchError| -> Pending { vec![(files.to_vec(), Err(e))] };
+
+    // User-defined commands have their own placeholders and work everywhere
+    if let Some(id) = app_id.and_then(|id| id.strip_prefix(crate::custom_apps::ID_PREFIX)) {
+        return match crate::custom_apps::find(app_handle, id) {
+            Ok(app) => files
+                .iter()
+                .map(|f| (vec![f.clone()], crate::custom_apps::launch(app_handle, &app, f)))
+                .collect(),
+            Err(e) => all_failed(e.into()),
+        };
+    }
+
+    // Only Linux desktop entries carry the extra %c / %i / %k / Terminal information
+    #[cfg(not(target_os = "linux"))]
+    let _ = (app_id, app_handle);
 
-        // Handle Snap apps
-        if first_cmd == "snap" || first_cmd.starts_with("/snap/") {
-            // For snap, just use the full command as-is
-            Command::new("sh")
-                .arg("-c")
-                .arg(format!("{} \"{}\"", exec, file_path))
-                .spawn()
-                .map_err(|e| format!("Failed to launch Snap app: {}", e))?;
-            return Ok(());
+    #[cfg(target_os = "macos")]
+    {
+        // macOS needs special handling for .app bundles; `open -a` hands
+        // every file to the same instance
+        if exec.ends_with(".app") {
+            let mut cmd = Command::new("open");
+            cmd.arg("-a").arg(exec).args(files);
+            return vec![(files.to_vec(), launcher::spawn(cmd))];
         }
+        return files
+            .iter()
+            .map(|f| {
+                let mut cmd = Command::new(exec);
+                cmd.arg(f);
+                (vec![f.clone()], launcher::spawn(cmd))
+            })
+            .collect();
+    }
 
-        // Regular apps - try to find full path if needed
-        let actual_exec = if first_cmd.starts_with('/') {
-            first_cmd.to_string()
-        } else {
-            // Use 'which' to find the full path
-            match Command::new("which").arg(first_cmd).output() {
-                Ok(output) if output.status.success() => {
-                    String::from_utf8_lossy(&output.stdout).trim().to_string()
+    #[cfg(target_os = "linux")]
+    {
+        // %c / %i / %k and the working directory come from the entry itself
+        let entry = app_id
+            .and_then(|id| crate::desktop_entry::DesktopEntry::load(std::path::Path::new(entry_path(id))));
+        let ctx = entry
+            .as_ref()
+            .map(|e| crate::desktop_exec::ExecContext {
+                name: e.name.clone(),
+                icon: e.icon.clone(),
+                desktop_file: Some(e.path.display().to_string()),
+            })
+            .unwrap_or_default();
+        let working_dir = entry.as_ref().and_then(|e| e.working_dir.clone());
+        let in_terminal = entry.as_ref().is_some_and(|e| e.terminal);
+
+        // An installed app runs its own Exec line; the one the webview sent
+        // is only used for ids that aren't desktop entries
+        let entry_exec = match &entry {
+            Some(e) => match entry_exec(e, app_id.and_then(entry_action)) {
+                Some(exec) => Some(exec),
+                None => {
+                    let message = format!("{} has nothing to run", app_id.unwrap_or_default());
+                    return all_failed(LaunchError::from(message));
                 }
-                _ => first_cmd.to_string()
-            }
+            },
+            None => None,
         };
+        let exec = entry_exec.as_deref().unwrap_or(exec);
 
-        // Launch with any additional arguments from the original exec
-        let mut cmd = Command::new(&actual_exec);
-        
-        // Add any additional args from the exec line (excluding the first command)
-        for arg in parts.iter().skip(1) {
-            cmd.arg(arg);
-        }
-        
-        cmd.arg(&file_path)
-            .spawn()
-            .map_err(|e| format!("Failed to launch {}: {}", actual_exec, e))?;
-        
-        return Ok(());
+        // Flatpak (`@@u %U @@`) and snap launchers are ordinary Exec lines,
+        // so they go through the same expansion; nothing runs through a shell
+        let launches = match crate::desktop_exec::expand(exec, files, &ctx) {
+            Ok(launches) => launches,
+            Err(e) => return all_failed(e.into()),
+        };
+        return launches
+            .into_iter()
+            .map(|launch| {
+                // Console programs (Terminal=true) need an emulator to draw in
+                let argv = if in_terminal {
+                    crate::terminal::wrap(app_handle, launch.argv)
+                } else {
+                    Ok(launch.argv)
+                };
+                let started = argv.map_err(LaunchError::from).and_then(|argv| {
+                    let mut cmd = Command::new(&argv[0]);
+                    cmd.args(&argv[1..]);
+                    if let Some(dir) = &working_dir {
+                        cmd.current_dir(dir);
+                    }
+                    launcher::spawn(cmd)
+                });
+                (launch.files, started)
+            })
+            .collect();
     }
 
     #[cfg(target_os = "windows")]
     {
-        Command::new(&exec)
-            .arg(&file_path)
-            .spawn()
-            .map_err(|e| e.to_string())?;
-        return Ok(());
+        return files
+            .iter()
+            .map(|f| {
+                let mut cmd = Command::new(exec);
+                cmd.arg(f);
+                (vec![f.clone()], launcher::spawn(cmd))
+            })
+            .collect();
     }
 
     #[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
-    Err("Unsupported platform".to_string())
+    all_failed(LaunchError::from("Unsupported platform".to_string()))
+}
+
+/// Make `app_id` (an entry from `list_open_with_apps`) the default app for
+/// the type of `file_path`. Returns the MIME type the default was set for.
+#[tauri::command]
+pub fn set_default_app<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    app_id: String,
+    file_path: String,
+) -> Result<String, FsError> {
+    #[cfg(target_os = "linux")]
+    {
+        let desktop_id = crate::mimeapps::installed_desktop_id(std::path::Path::new(entry_path(&app_id)))
+            .ok_or_else(|| FsError::new(ErrorCode::Unsupported, format!("Not an installed desktop entry: {}", app_id)))?;
+        let file_path = crate::path_policy::existing(&app_handle, &file_path)?;
+        let mime = crate::mime::detect(&file_path);
+        crate::mimeapps::set_default(&mime, &desktop_id)?;
+        return Ok(mime);
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (app_handle, app_id, file_path);
+        Err(FsError::new(
+            ErrorCode::Unsupported,
+            "Setting the default app is only supported on Linux",
+        ))
+    }
+}
+
+/// Desktop actions are listed as `<path>#<action id>`; strip the action part
+#[cfg(target_os = "linux")]
+fn entry_path(app_id: &str) -> &str {
+    match app_id.rsplit_once('#') {
+        Some((path, _)) if !std::path::Path::new(app_id).exists() => path,
+        _ => app_id,
+    }
+}
+
+/// The action part of a `<path>#<action id>` app id
+#[cfg(target_os = "linux")]
+fn entry_action(app_id: &str) -> Option<&str> {
+    app_id.get(entry_path(app_id).len() + 1..)
+}
+
+/// `Exec=` of the entry, or of its action `action`
+#[cfg(target_os = "linux")]
+fn entry_exec(entry: &crate::desktop_entry::DesktopEntry, action: Option<&str>) -> Option<String> {
+    match action {
+        Some(action) => entry.actions.iter().find(|a| a.id == action).map(|a| a.exec.clone()),
+        None => entry.exec.clone(),
+    }
 }
 
 // Linux implementation
 #[cfg(target_os = "linux")]
 fn linux_apps(file_path: &str) -> Vec<OpenWithApp> {
-    use std::fs;
+    use crate::desktop_entry::DesktopEntry;
     use std::collections::HashSet;
+    use std::fs;
 
     let mut apps = Vec::new();
     let mut seen_ids = HashSet::new();
     let home = std::env::var("HOME").unwrap_or_default();
 
-    // Get file extension for filtering
-    let extension = std::path::Path::new(file_path)
-        .extension()
-        .and_then(|s| s.to_str())
-        .unwrap_or("");
+    // The detected type plus its parents (text/csv is also text/plain). Apps
+    // that only list application/octet-stream are not worth recommending.
+    let detected = crate::mime::detect(std::path::Path::new(file_path));
+    let file_mimes: Vec<String> = crate::mime::ancestors(&detected)
+        .into_iter()
+        .filter(|m| m == &detected || m != crate::mime::OCTET_STREAM)
+        .collect();
 
+    // Highest precedence first: a user copy of a .desktop file hides the system one
     let dirs = [
-        "/usr/share/applications",
         &format!("{}/.local/share/applications", home),
-        "/var/lib/flatpak/exports/share/applications",
         &format!("{}/.local/share/flatpak/exports/share/applications", home),
+        "/var/lib/flatpak/exports/share/applications",
+        "/usr/share/applications",
     ];
 
+    // Apps keyed by desktop file id, which is what mimeapps.list refers to
+    let mut desktop_ids = Vec::new();
+
     for dir in dirs {
         if let Ok(entries) = fs::read_dir(dir) {
             for entry in entries.flatten() {
@@ -162,133 +458,126 @@ fn linux_apps(file_path: &str) -> Vec<OpenWithApp> {
                 if path.extension().and_then(|s| s.to_str()) != Some("desktop") {
                     continue;
                 }
+                let Some(desktop_id) = crate::mimeapps::desktop_id(&path) else {
+                    continue;
+                };
 
-                if let Ok(content) = fs::read_to_string(&path) {
-                    if let Some(app) = parse_desktop_file(&content, &path, extension) {
-                        // Avoid duplicates
-                        if !seen_ids.contains(&app.id) {
-                            seen_ids.insert(app.id.clone());
-                            apps.push(app);
-                        }
-                    }
+                // Avoid duplicates; a hidden user copy still shadows the system one
+                if !seen_ids.insert(desktop_id.clone()) {
+                    continue;
+                }
+
+                let Some(entry) = DesktopEntry::load(&path) else {
+                    continue;
+                };
+                for app in desktop_entry_apps(&entry, &file_mimes) {
+                    desktop_ids.push(desktop_id.clone());
+                    apps.push(app);
                 }
             }
         }
     }
 
-    // Separate recommended and other apps, then sort each group
-    let mut recommended: Vec<_> = apps.iter().filter(|a| a.is_recommended).cloned().collect();
-    let mut others: Vec<_> = apps.iter().filter(|a| !a.is_recommended).cloned().collect();
-    
-    recommended.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-    others.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-    
+    // Defaults and added/removed associations from mimeapps.list and mimeinfo.cache
+    let assoc = crate::mimeapps::associations(&file_mimes, |id| seen_ids.contains(id));
+    let rank = |id: &str| {
+        if assoc.default.as_deref() == Some(id) {
+            0
+        } else {
+            assoc.added.iter().position(|a| a == id).map_or(usize::MAX, |p| p + 1)
+        }
+    };
+
+    let mut ranked: Vec<(usize, OpenWithApp)> = Vec::new();
+    for (desktop_id, mut app) in desktop_ids.into_iter().zip(apps) {
+        app.is_default =
+            app.action.is_none() && assoc.default.as_deref() == Some(desktop_id.as_str());
+        if assoc.removed.contains(&desktop_id) && !app.is_default {
+            app.is_recommended = false;
+        } else if app.is_default || assoc.added.contains(&desktop_id) {
+            app.is_recommended = true;
+        }
+        ranked.push((rank(&desktop_id), app));
+    }
+
+    // Recommended apps in association order (default first), then everything
+    // else alphabetically
+    let mut recommended: Vec<_> = ranked.iter().filter(|(_, a)| a.is_recommended).cloned().collect();
+    let mut others: Vec<_> = ranked.iter().filter(|(_, a)| !a.is_recommended).cloned().collect();
+
+    recommended.sort_by(|(ra, a), (rb, b)| {
+        ra.cmp(rb).then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    others.sort_by(|(_, a), (_, b)| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+
     // Combine: recommended first, then others
     recommended.extend(others);
-    recommended
+    recommended.into_iter().map(|(_, app)| app).collect()
 }
 
+/// The app itself plus one entry per desktop action ("New Private Window").
+/// Hidden, NoDisplay and unavailable (TryExec, OnlyShowIn) entries give none.
 #[cfg(target_os = "linux")]
-fn parse_desktop_file(content: &str, path: &std::path::Path, file_ext: &str) -> Option<OpenWithApp> {
-    let mut name = None;
-    let mut exec = None;
-    let mut icon = None;
-    let mut mime_types = Vec::new();
-    let mut no_display = false;
-
-    for line in content.lines() {
-        let line = line.trim();
-        
-        if line.starts_with("Name=") && name.is_none() {
-            name = Some(line[5..].to_string());
-        } else if line.starts_with("Exec=") {
-            let exec_raw = line[5..].to_string();
-            // Clean up exec string but preserve the base command
-            let cleaned = exec_raw
-                .replace("%f", "")
-                .replace("%F", "")
-                .replace("%u", "")
-                .replace("%U", "")
-                .replace("%d", "")
-                .replace("%D", "")
-                .replace("%n", "")
-                .replace("%N", "")
-                .replace("%i", "")
-                .replace("%c", "")
-                .replace("%k", "")
-                .replace("%v", "")
-                .replace("%m", "")
-                .trim()
-                .to_string();
-            
-            // Store the full exec line for special cases like Flatpak/Snap
-            exec = Some(cleaned);
-        } else if line.starts_with("Icon=") {
-            icon = Some(line[5..].to_string());
-        } else if line.starts_with("MimeType=") {
-            mime_types = line[9..].split(';').map(|s| s.to_string()).collect();
-        } else if line == "NoDisplay=true" {
-            no_display = true;
-        }
-    }
-
-    // Skip if NoDisplay is true
-    if no_display {
-        return None;
+fn desktop_entry_apps(
+    entry: &crate::desktop_entry::DesktopEntry,
+    file_mimes: &[String],
+) -> Vec<OpenWithApp> {
+    if entry.no_display || !entry.is_available() {
+        return Vec::new();
     }
+    let Some(exec) = entry.exec.clone() else {
+        return Vec::new();
+    };
 
     // Check if app can handle this file type
-    let is_recommended = check_mime_match(&mime_types, file_ext);
-
-    if let (Some(name), Some(exec)) = (name, exec) {
-        Some(OpenWithApp {
-            id: path.display().to_string(),
-            name,
-            exec,
-            icon,
-            is_recommended,
-        })
-    } else {
-        None
-    }
+    let is_recommended = check_mime_match(&entry.mime_types, file_mimes);
+    let id = entry.path.display().to_string();
+
+    let mut apps = vec![OpenWithApp {
+        id: id.clone(),
+        name: entry.name.clone(),
+        exec,
+        icon: entry.icon.clone(),
+        is_recommended,
+        is_default: false,
+        action: None,
+        terminal: entry.terminal,
+        categories: entry.categories.clone(),
+        recent_rank: None,
+        is_always: false,
+    }];
+
+    apps.extend(entry.actions.iter().map(|action| OpenWithApp {
+        id: format!("{}#{}", id, action.id),
+        name: format!("{}: {}", entry.name, action.name),
+        exec: action.exec.clone(),
+        icon: action.icon.clone().or_else(|| entry.icon.clone()),
+        is_recommended,
+        is_default: false,
+        action: Some(action.id.clone()),
+        terminal: entry.terminal,
+        categories: entry.categories.clone(),
+        recent_rank: None,
+        is_always: false,
+    }));
+    apps
 }
 
 #[cfg(target_os = "linux")]
-fn check_mime_match(mime_types: &[String], file_ext: &str) -> bool {
-    if file_ext.is_empty() || mime_types.is_empty() {
-        return false; // Not recommended if no extension or no MIME types
+fn check_mime_match(mime_types: &[String], file_mimes: &[String]) -> bool {
+    if file_mimes.is_empty() || mime_types.is_empty() {
+        return false; // Not recommended if the type is unknown or the app lists no MIME types
     }
 
-    // Common extension to MIME type mappings
-    let mime = match file_ext.to_lowercase().as_str() {
-        "txt" | "text" => "text/plain",
-        "pdf" => "application/pdf",
-        "jpg" | "jpeg" => "image/jpeg",
-        "png" => "image/png",
-        "gif" => "image/gif",
-        "svg" => "image/svg+xml",
-        "mp4" => "video/mp4",
-        "mp3" => "audio/mpeg",
-        "wav" => "audio/wav",
-        "zip" => "application/zip",
-        "html" | "htm" => "text/html",
-        "json" => "application/json",
-        "xml" => "application/xml",
-        "csv" => "text/csv",
-        "js" => "application/javascript",
-        "py" => "text/x-python",
-        "rs" => "text/x-rust",
-        "cpp" | "cc" => "text/x-c++",
-        "c" => "text/x-c",
-        "java" => "text/x-java",
-        "md" => "text/markdown",
-        _ => return false, // Unknown types - not recommended
-    };
-
-    mime_types.iter().any(|m| 
-        m.starts_with(mime) || 
-        m.starts_with(&format!("{}/*", mime.split('/').next().unwrap_or("")))
-    )
+    let media = file_mimes[0].split('/').next().unwrap_or("");
+    mime_types
+        .iter()
+        .filter(|m| !m.is_empty())
+        .any(|m| {
+            // "image/*" style wildcards
+            m.strip_suffix("/*").is_some_and(|t| t == media)
+                || file_mimes.contains(&crate::mime::canonical(m))
+        })
 }
 
 // Windows implementation
@@ -342,6 +631,12 @@ fn windows_apps(file_path: &str) -> Vec<OpenWithApp> {
                         exec: path.display().to_string(),
                         icon: None,
                         is_recommended,
+                        is_default: false,
+                        action: None,
+                        terminal: false,
+                        categories: Vec::new(),
+                        recent_rank: None,
+                        is_always: false,
                     });
                 }
             }
@@ -449,6 +744,12 @@ fn macos_apps(file_path: &str) -> Vec<OpenWithApp> {
                             exec: path.display().to_string(), // Store .app path
                             icon: None,
                             is_recommended,
+                            is_default: false,
+                            action: None,
+                            terminal: false,
+                            categories: Vec::new(),
+                            recent_rank: None,
+                            is_always: false,
                         });
                     }
                 }
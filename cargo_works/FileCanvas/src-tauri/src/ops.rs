@@ -0,0 +1,452 @@
+// src-tauri/src/ops.rs
+//
+// The file operations behind the Tauri commands, written against the
+// `FileSystem` trait so they run the same on disk and in memory. Paths are
+// expected to be checked already (see path_policy.rs in the app); these
+// functions only enforce what every caller needs: nothing is overwritten
+// silently and a folder is never copied into itself.
+//
+// The `*_between` variants work across two filesystems (disk ↔ SFTP) by
+// streaming file contents; a move there is a copy followed by a delete.
+
+use crate::error::{ErrorCode, FsError, IoContext};
+use crate::vfs::{FileSystem, Metadata};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+// --- Data Structures ---
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileItem {
+    pub name: String,
+    pub path: String,
+    pub is_directory: bool,
+    pub children: Option<Vec<FileItem>>,
+    pub mtime: u64,
+    pub ctime: u64,
+}
+
+// --- Helpers ---
+
+fn file_item(name: String, path: &Path, metadata: &Metadata) -> FileItem {
+    FileItem {
+        name,
+        path: path.to_string_lossy().to_string(),
+        is_directory: metadata.is_dir,
+        children: None,
+        mtime: metadata.mtime,
+        ctime: metadata.ctime,
+    }
+}
+
+/// Last component of `path`, or the whole path for a drive root
+pub fn display_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .to_string()
+}
+
+/// `rename` and `copy_file` replace an existing file without asking, so
+/// check first and let the UI offer a different name instead
+pub fn ensure_free<F: FileSystem + ?Sized>(fs: &F, path: &Path) -> Result<(), FsError> {
+    if fs.exists(path) {
+        return Err(FsError::new(ErrorCode::AlreadyExists, format!("Already exists: {}", path.display())).with_path(path));
+    }
+    Ok(())
+}
+
+fn not_a_folder(path: &Path) -> FsError {
+    FsError::new(ErrorCode::NotADirectory, format!("Not a folder: {}", path.display())).with_path(path)
+}
+
+fn require_folder<F: FileSystem + ?Sized>(fs: &F, path: &Path) -> Result<(), FsError> {
+    if fs.metadata(path)?.is_dir {
+        Ok(())
+    } else {
+        Err(not_a_folder(path))
+    }
+}
+
+/// `dest_folder/<name of src>`
+fn destination(src: &Path, dest_folder: &Path) -> PathBuf {
+    dest_folder.join(src.file_name().unwrap_or_default())
+}
+
+// --- Reading ---
+
+/// Visible entries of `path`, folders first, then by name. An unreadable
+/// folder lists as empty.
+pub fn read_dir_shallow<F: FileSystem + ?Sized>(fs: &F, path: &Path) -> Vec<FileItem> {
+    let mut items: Vec<FileItem> = fs
+        .read_dir(path)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|entry| !entry.name.starts_with('.'))
+        .map(|entry| file_item(entry.name, &entry.path, &entry.metadata))
+        .collect();
+
+    items.sort_by(|a, b| {
+        b.is_directory
+            .cmp(&a.is_directory)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    items
+}
+
+/// Total size of the files below `path`; unreadable parts count as 0
+pub fn dir_size<F: FileSystem + ?Sized>(fs: &F, path: &Path) -> u64 {
+    fs.read_dir(path)
+        .unwrap_or_default()
+        .iter()
+        .map(|entry| {
+            if entry.metadata.is_dir {
+                dir_size(fs, &entry.path)
+            } else {
+                entry.metadata.len
+            }
+        })
+        .sum()
+}
+
+/// Size of a file, or the recursive size of a folder
+pub fn item_size<F: FileSystem + ?Sized>(fs: &F, path: &Path, metadata: &Metadata) -> u64 {
+    if metadata.is_dir {
+        dir_size(fs, path)
+    } else {
+        metadata.len
+    }
+}
+
+// --- Changing ---
+
+pub fn create_folder<F: FileSystem + ?Sized>(fs: &F, path: &Path) -> Result<FileItem, FsError> {
+    fs.create_dir(path)?;
+    let metadata = fs.metadata(path)?;
+    Ok(file_item(display_name(path), path, &metadata))
+}
+
+/// Create an empty file; an existing file is never truncated
+pub fn create_file<F: FileSystem + ?Sized>(fs: &F, path: &Path) -> Result<FileItem, FsError> {
+    fs.create_file(path)?;
+    let metadata = fs.metadata(path)?;
+    Ok(file_item(display_name(path), path, &metadata))
+}
+
+/// Move a file or folder into `dest_folder`. Returns the new path.
+pub fn move_item<F: FileSystem + ?Sized>(fs: &F, src: &Path, dest_folder: &Path) -> Result<PathBuf, FsError> {
+    let dest = destination(src, dest_folder);
+    if dest.starts_with(src) {
+        return Err(FsError::new(ErrorCode::InvalidPath, "Can't move a folder into itself").with_path(dest_folder));
+    }
+    ensure_free(fs, &dest)?;
+    fs.rename(src, &dest)?;
+    Ok(dest)
+}
+
+pub fn move_folder<F: FileSystem + ?Sized>(fs: &F, src: &Path, dest_folder: &Path) -> Result<PathBuf, FsError> {
+    require_folder(fs, src)?;
+    move_item(fs, src, dest_folder)
+}
+
+pub fn copy_file<F: FileSystem + ?Sized>(fs: &F, src: &Path, dest_folder: &Path) -> Result<PathBuf, FsError> {
+    let dest = destination(src, dest_folder);
+    ensure_free(fs, &dest)?;
+    fs.copy_file(src, &dest)?;
+    Ok(dest)
+}
+
+/// Copy the contents of folder `src` into `dest`, creating it if needed.
+/// Existing files in `dest` are replaced.
+pub fn copy_dir_recursive<F: FileSystem + ?Sized>(fs: &F, src: &Path, dest: &Path) -> Result<(), FsError> {
+    fs.create_dir_all(dest)?;
+    for entry in fs.read_dir(src)? {
+        let dest_path = dest.join(&entry.name);
+        if entry.metadata.is_dir {
+            copy_dir_recursive(fs, &entry.path, &dest_path)?;
+        } else {
+            fs.copy_file(&entry.path, &dest_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Where a copy of folder `src` into `dest_folder` goes, once it's clear the
+/// copy can start. Shared with the parallel copier.
+pub(crate) fn folder_copy_destination<F: FileSystem + ?Sized>(
+    fs: &F,
+    src: &Path,
+    dest_folder: &Path,
+) -> Result<PathBuf, FsError> {
+    require_folder(fs, src)?;
+    let dest = destination(src, dest_folder);
+    // Copying a folder into itself would recurse forever
+    if dest.starts_with(src) {
+        return Err(FsError::new(ErrorCode::InvalidPath, "Can't copy a folder into itself").with_path(dest_folder));
+    }
+    ensure_free(fs, &dest)?;
+    Ok(dest)
+}
+
+pub fn copy_folder<F: FileSystem + ?Sized>(fs: &F, src: &Path, dest_folder: &Path) -> Result<PathBuf, FsError> {
+    let dest = folder_copy_destination(fs, src, dest_folder)?;
+    copy_dir_recursive(fs, src, &dest)?;
+    Ok(dest)
+}
+
+/// Delete a file or a whole folder. A symlink is removed as a link, never
+/// by following it.
+pub fn delete_item<F: FileSystem + ?Sized>(fs: &F, path: &Path) -> Result<(), FsError> {
+    let metadata = fs.symlink_metadata(path)?;
+    if metadata.is_dir && !metadata.is_symlink {
+        fs.remove_dir_all(path)
+    } else {
+        fs.remove_file(path)
+    }
+}
+
+/// Rename `path` to `new_name` in the same folder. Returns the new path.
+pub fn rename_item<F: FileSystem + ?Sized>(fs: &F, path: &Path, new_name: &str) -> Result<PathBuf, FsError> {
+    let new_path = path.with_file_name(new_name);
+    // Changing only the case must still work on case-insensitive drives
+    let same_item = new_path.to_string_lossy().to_lowercase() == path.to_string_lossy().to_lowercase();
+    if !same_item {
+        ensure_free(fs, &new_path)?;
+    }
+    fs.rename(path, &new_path)?;
+    Ok(new_path)
+}
+
+// --- Between filesystems ---
+
+/// Stream one file from `src_fs` to `dst_fs`, replacing `dest`
+pub fn stream_file(src_fs: &dyn FileSystem, src: &Path, dst_fs: &dyn FileSystem, dest: &Path) -> Result<u64, FsError> {
+    let mut reader = src_fs.open_read(src)?;
+    let mut writer = dst_fs.open_write(dest)?;
+    let copied = io::copy(&mut reader, &mut writer).at(dest)?;
+    writer.flush().at(dest)?;
+    Ok(copied)
+}
+
+fn copy_tree_between(src_fs: &dyn FileSystem, src: &Path, dst_fs: &dyn FileSystem, dest: &Path) -> Result<(), FsError> {
+    dst_fs.create_dir_all(dest)?;
+    for entry in src_fs.read_dir(src)? {
+        let dest_path = dest.join(&entry.name);
+        if entry.metadata.is_dir {
+            copy_tree_between(src_fs, &entry.path, dst_fs, &dest_path)?;
+        } else {
+            stream_file(src_fs, &entry.path, dst_fs, &dest_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy a file or folder from one filesystem into `dest_folder` on another.
+/// Returns the new path.
+pub fn copy_between(
+    src_fs: &dyn FileSystem,
+    src: &Path,
+    dst_fs: &dyn FileSystem,
+    dest_folder: &Path,
+) -> Result<PathBuf, FsError> {
+    let dest = destination(src, dest_folder);
+    ensure_free(dst_fs, &dest)?;
+    if src_fs.metadata(src)?.is_dir {
+        copy_tree_between(src_fs, src, dst_fs, &dest)?;
+    } else {
+        stream_file(src_fs, src, dst_fs, &dest)?;
+    }
+    Ok(dest)
+}
+
+/// Move across filesystems: copy, then delete the source once everything
+/// arrived. A failed copy leaves the source untouched.
+pub fn move_between(
+    src_fs: &dyn FileSystem,
+    src: &Path,
+    dst_fs: &dyn FileSystem,
+    dest_folder: &Path,
+) -> Result<PathBuf, FsError> {
+    let dest = copy_between(src_fs, src, dst_fs, dest_folder)?;
+    delete_item(src_fs, src)?;
+    Ok(dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::{DiskFs, MemoryFs};
+    use std::io::ErrorKind;
+
+    fn fs_with(files: &[&str]) -> MemoryFs {
+        let fs = MemoryFs::new();
+        for file in files {
+            fs.add_file(file, file.as_bytes()).unwrap();
+        }
+        fs
+    }
+
+    #[test]
+    fn ensure_free_refuses_existing_items() {
+        let fs = fs_with(&["/a/file.txt"]);
+        assert_eq!(ensure_free(&fs, Path::new("/a/file.txt")).unwrap_err().code, ErrorCode::AlreadyExists);
+        assert_eq!(ensure_free(&fs, Path::new("/a")).unwrap_err().code, ErrorCode::AlreadyExists);
+        assert!(ensure_free(&fs, Path::new("/a/other.txt")).is_ok());
+    }
+
+    #[test]
+    fn copy_never_overwrites() {
+        let fs = fs_with(&["/src/file.txt"]);
+        fs.add_file("/dest/file.txt", "kept").unwrap();
+        let err = copy_file(&fs, Path::new("/src/file.txt"), Path::new("/dest")).unwrap_err();
+        assert_eq!(err.code, ErrorCode::AlreadyExists);
+        assert_eq!(fs.contents("/dest/file.txt").unwrap(), b"kept");
+    }
+
+    #[test]
+    fn folders_are_not_copied_or_moved_into_themselves() {
+        let fs = fs_with(&["/a/b/file.txt"]);
+        let err = copy_folder(&fs, Path::new("/a"), Path::new("/a/b")).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidPath);
+        let err = move_folder(&fs, Path::new("/a"), Path::new("/a/b")).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidPath);
+        let err = move_item(&fs, Path::new("/a"), Path::new("/a")).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidPath);
+        assert!(!fs.exists(Path::new("/a/b/a")));
+        assert_eq!(fs.contents("/a/b/file.txt").unwrap(), b"/a/b/file.txt");
+    }
+
+    #[test]
+    fn failed_move_between_keeps_the_source() {
+        let src = fs_with(&["/docs/a.txt", "/docs/sub/b.txt"]);
+        let dst = MemoryFs::new();
+        dst.create_dir(Path::new("/backup")).unwrap();
+        dst.fail_on("/backup/docs/sub/b.txt", ErrorKind::StorageFull);
+
+        let err = move_between(&src, Path::new("/docs"), &dst, Path::new("/backup")).unwrap_err();
+        assert_eq!(err.code, ErrorCode::NoSpace);
+        assert_eq!(src.contents("/docs/a.txt").unwrap(), b"/docs/a.txt");
+        assert_eq!(src.contents("/docs/sub/b.txt").unwrap(), b"/docs/sub/b.txt");
+
+        let moved = move_between(&src, Path::new("/docs/a.txt"), &dst, Path::new("/backup")).unwrap();
+        assert_eq!(moved, Path::new("/backup/a.txt"));
+        assert!(!src.exists(Path::new("/docs/a.txt")));
+    }
+
+    #[test]
+    fn copy_between_copies_folder_trees_and_keeps_the_source() {
+        let src = fs_with(&["/docs/a.txt", "/docs/sub/b.txt", "/docs/sub/deeper/c.txt"]);
+        src.create_dir(Path::new("/docs/empty")).unwrap();
+        let dst = MemoryFs::new();
+        dst.create_dir(Path::new("/backup")).unwrap();
+
+        let copied = copy_between(&src, Path::new("/docs"), &dst, Path::new("/backup")).unwrap();
+        assert_eq!(copied, Path::new("/backup/docs"));
+        assert_eq!(dst.contents("/backup/docs/a.txt").unwrap(), b"/docs/a.txt");
+        assert_eq!(dst.contents("/backup/docs/sub/deeper/c.txt").unwrap(), b"/docs/sub/deeper/c.txt");
+        assert!(dst.metadata(Path::new("/backup/docs/empty")).unwrap().is_dir);
+        assert_eq!(src.contents("/docs/sub/b.txt").unwrap(), b"/docs/sub/b.txt");
+
+        // A second copy would land on the first one
+        let err = copy_between(&src, Path::new("/docs"), &dst, Path::new("/backup")).unwrap_err();
+        assert_eq!(err.code, ErrorCode::AlreadyExists);
+    }
+
+    #[test]
+    fn copy_between_memory_and_disk_round_trips() {
+        let base = std::env::temp_dir().join(format!("filecanvas-between-{}", std::process::id()));
+        std::fs::create_dir_all(&base).unwrap();
+        let mem = fs_with(&["/docs/a.txt", "/docs/sub/b.txt"]);
+
+        let on_disk = copy_between(&mem, Path::new("/docs"), &DiskFs, &base).unwrap();
+        assert_eq!(std::fs::read(on_disk.join("sub/b.txt")).unwrap(), b"/docs/sub/b.txt");
+
+        let back = MemoryFs::new();
+        back.create_dir(Path::new("/restore")).unwrap();
+        let restored = move_between(&DiskFs, &on_disk, &back, Path::new("/restore")).unwrap();
+        assert_eq!(restored, Path::new("/restore/docs"));
+        assert_eq!(back.contents("/restore/docs/a.txt").unwrap(), b"/docs/a.txt");
+        assert!(!on_disk.exists());
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn stream_file_copies_more_than_one_buffer_and_replaces_dest() {
+        let data: Vec<u8> = (0..(1 << 20) + 17).map(|i| (i % 251) as u8).collect();
+        let src = MemoryFs::new();
+        src.add_file("/big.bin", data.clone()).unwrap();
+        let dst = fs_with(&["/big.bin"]);
+
+        let copied = stream_file(&src, Path::new("/big.bin"), &dst, Path::new("/big.bin")).unwrap();
+        assert_eq!(copied, data.len() as u64);
+        assert_eq!(dst.contents("/big.bin").unwrap(), data);
+    }
+
+    #[test]
+    fn a_write_failing_mid_copy_is_reported_and_keeps_the_source() {
+        let src = MemoryFs::new();
+        src.add_file("/video.mp4", vec![7u8; 100_000]).unwrap();
+        let dst = MemoryFs::new();
+        dst.create_dir(Path::new("/usb")).unwrap();
+        dst.fail_after("/usb/video.mp4", 40_000, ErrorKind::StorageFull);
+
+        let err = move_between(&src, Path::new("/video.mp4"), &dst, Path::new("/usb")).unwrap_err();
+        assert_eq!(err.code, ErrorCode::NoSpace);
+        assert_eq!(err.path.as_deref(), Some("/usb/video.mp4"));
+        assert_eq!(dst.contents("/usb/video.mp4").unwrap().len(), 40_000);
+        assert_eq!(src.contents("/video.mp4").unwrap().len(), 100_000);
+    }
+
+    #[test]
+    fn read_errors_are_reported_and_keep_the_source() {
+        let src = fs_with(&["/docs/a.txt", "/docs/secret.txt"]);
+        src.fail_on("/docs/secret.txt", ErrorKind::PermissionDenied);
+        let dst = MemoryFs::new();
+        dst.create_dir(Path::new("/backup")).unwrap();
+
+        let err = move_between(&src, Path::new("/docs"), &dst, Path::new("/backup")).unwrap_err();
+        assert_eq!(err.code, ErrorCode::PermissionDenied);
+        assert_eq!(err.path.as_deref(), Some("/docs/secret.txt"));
+        assert_eq!(src.contents("/docs/a.txt").unwrap(), b"/docs/a.txt");
+    }
+
+    #[test]
+    fn rename_changes_case_and_refuses_taken_names() {
+        let fs = fs_with(&["/notes.txt", "/todo.txt"]);
+        let renamed = rename_item(&fs, Path::new("/notes.txt"), "Notes.txt").unwrap();
+        assert_eq!(renamed, Path::new("/Notes.txt"));
+        assert_eq!(fs.contents("/Notes.txt").unwrap(), b"/notes.txt");
+
+        let err = rename_item(&fs, Path::new("/Notes.txt"), "todo.txt").unwrap_err();
+        assert_eq!(err.code, ErrorCode::AlreadyExists);
+        assert_eq!(fs.contents("/todo.txt").unwrap(), b"/todo.txt");
+    }
+
+    #[test]
+    fn delete_item_removes_files_and_folders() {
+        let fs = fs_with(&["/a/b/file.txt", "/c.txt"]);
+        delete_item(&fs, Path::new("/c.txt")).unwrap();
+        delete_item(&fs, Path::new("/a")).unwrap();
+        assert!(!fs.exists(Path::new("/c.txt")));
+        assert!(!fs.exists(Path::new("/a/b/file.txt")));
+        assert_eq!(delete_item(&fs, Path::new("/a")).unwrap_err().code, ErrorCode::NotFound);
+    }
+
+    // MemoryFs has no symlinks, so this one runs on the disk
+    #[cfg(unix)]
+    #[test]
+    fn delete_item_removes_a_symlink_not_its_target() {
+        let base = std::env::temp_dir().join(format!("filecanvas-ops-{}", std::process::id()));
+        let target = base.join("target");
+        std::fs::create_dir_all(&target).unwrap();
+        std::fs::write(target.join("keep.txt"), "keep").unwrap();
+        let link = base.join("link");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        delete_item(&DiskFs, &link).unwrap();
+        assert!(std::fs::symlink_metadata(&link).is_err());
+        assert_eq!(std::fs::read_to_string(target.join("keep.txt")).unwrap(), "keep");
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
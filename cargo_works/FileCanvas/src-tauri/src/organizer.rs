@@ -0,0 +1,718 @@
+use crate::activity::{ActivityEntry, ActivityLog, Operation, Via};
+use crate::filters::{CompiledFilter, FilterRules};
+use crate::store::{self, now_ms};
+use chrono::{DateTime, Local};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tauri::async_runtime::spawn_blocking;
+use tauri::{Manager, Runtime};
+use walkdir::WalkDir;
+
+const RUN_LOG_FILE: &str = "organizer_runs.json";
+/// Files replaced with `CollisionPolicy::Overwrite` are kept here, per run,
+/// so undo can put them back. Hidden, so later runs don't organize them.
+const TRASH_DIR: &str = ".organizer-trash";
+
+// Files that are still being written by a browser or scanner.
+const PARTIAL_EXTENSIONS: &[&str] = &["part", "crdownload", "download", "partial"];
+
+const CATEGORIES: &[(&str, &[&str])] = &[
+    ("Documents", &["pdf", "doc", "docx", "odt", "rtf", "txt", "md", "pages", "epub"]),
+    ("Spreadsheets", &["xls", "xlsx", "xlsm", "ods", "csv", "tsv", "numbers"]),
+    ("Presentations", &["ppt", "pptx", "odp", "key"]),
+    ("Images", &["jpg", "jpeg", "png", "gif", "bmp", "webp", "svg", "tif", "tiff", "heic", "raw"]),
+    ("Audio", &["mp3", "wav", "flac", "ogg", "m4a", "aac", "opus"]),
+    ("Video", &["mp4", "mkv", "avi", "mov", "wmv", "webm", "m4v"]),
+    ("Archives", &["zip", "rar", "7z", "tar", "gz", "bz2", "xz", "tgz"]),
+    ("Installers", &["exe", "msi", "deb", "rpm", "appimage", "dmg", "pkg", "apk"]),
+    ("Code", &["rs", "py", "js", "ts", "html", "css", "json", "xml", "sh", "c", "cpp", "java"]),
+];
+
+// --- Data Structures ---
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Sort into a folder named after the type category (Documents, Images, …).
+    Category,
+    /// Folder built from the file's modification date, e.g. `{year}/{month}`.
+    /// Also understands `{day}`, `{category}` and `{ext}`.
+    DatePattern { pattern: String },
+    /// Regex on the file name; `target` may use captures such as `Invoices/$1`.
+    Regex { pattern: String, target: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizeRule {
+    #[serde(flatten)]
+    pub action: RuleAction,
+    /// Optional extra conditions, same rules as connection filters.
+    #[serde(default)]
+    pub when: FilterRules,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionPolicy {
+    /// Keep both: `report.pdf` becomes `report (1).pdf`.
+    #[default]
+    Rename,
+    Skip,
+    Overwrite,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrganizeOptions {
+    pub dry_run: bool,
+    pub recursive: bool,
+    pub collision: CollisionPolicy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizeMove {
+    pub from: String,
+    pub to: String,
+    pub rule_index: usize,
+    pub renamed: bool,
+    /// Where the file that was at `to` went when it was overwritten
+    #[serde(default)]
+    pub replaced: Option<String>,
+    /// Already moved back by an undo; a retried undo leaves it alone
+    #[serde(default)]
+    pub reverted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizeRun {
+    pub id: String,
+    pub folder: String,
+    pub started_at: u64,
+    pub dry_run: bool,
+    pub moves: Vec<OrganizeMove>,
+    pub skipped: Vec<String>,
+    pub errors: Vec<String>,
+    /// Folders this run created, removed again on undo when empty.
+    pub created_dirs: Vec<String>,
+    pub undone: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UndoResult {
+    pub restored: usize,
+    pub errors: Vec<String>,
+}
+
+struct CompiledRule {
+    action: CompiledAction,
+    when: CompiledFilter,
+}
+
+enum CompiledAction {
+    Category,
+    DatePattern(String),
+    Regex(Regex, String),
+}
+
+// --- Helpers ---
+
+fn run_log_path<R: Runtime>(app_handle: &tauri::AppHandle<R>) -> Result<PathBuf, String> {
+    let dir = app_handle.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(RUN_LOG_FILE))
+}
+
+fn load_runs(path: &Path) -> Vec<OrganizeRun> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+pub fn category_for(ext: &str) -> Option<&'static str> {
+    let ext = ext.to_lowercase();
+    CATEGORIES
+        .iter()
+        .find(|(_, exts)| exts.contains(&ext.as_str()))
+        .map(|(name, _)| *name)
+}
+
+fn compile_rules(rules: &[OrganizeRule]) -> Result<Vec<CompiledRule>, String> {
+    rules
+        .iter()
+        .enumerate()
+        .map(|(i, rule)| {
+            let action = match &rule.action {
+                RuleAction::Category => CompiledAction::Category,
+                RuleAction::DatePattern { pattern } => CompiledAction::DatePattern(pattern.clone()),
+                RuleAction::Regex { pattern, target } => {
+                    let re = Regex::new(pattern)
+                        .map_err(|e| format!("Rule {}: invalid regex: {}", i + 1, e))?;
+                    CompiledAction::Regex(re, target.clone())
+                }
+            };
+            let when = rule.when.compile().map_err(|e| format!("Rule {}: {}", i + 1, e))?;
+            Ok(CompiledRule { action, when })
+        })
+        .collect()
+}
+
+/// Resolves the sub folder (relative to the organized folder) for a file, or
+/// `None` if this rule doesn't apply.
+fn target_folder(action: &CompiledAction, name: &str, metadata: &fs::Metadata) -> Option<String> {
+    let ext = Path::new(name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match action {
+        CompiledAction::Category => category_for(&ext).map(str::to_string),
+        CompiledAction::DatePattern(pattern) => {
+            let modified: DateTime<Local> = metadata.modified().ok()?.into();
+            let folder = pattern
+                .replace("{year}", &modified.format("%Y").to_string())
+                .replace("{month}", &modified.format("%m").to_string())
+                .replace("{day}", &modified.format("%d").to_string())
+                .replace("{category}", category_for(&ext).unwrap_or("Other"))
+                .replace("{ext}", if ext.is_empty() { "no-extension" } else { &ext });
+            Some(folder)
+        }
+        CompiledAction::Regex(re, target) => {
+            let caps = re.captures(name)?;
+            let mut folder = String::new();
+            caps.expand(target, &mut folder);
+            Some(folder)
+        }
+    }
+}
+
+/// Turns a rule's folder template into a path inside `root`, dropping any
+/// component that could escape it (`..`, absolute prefixes, empty parts).
+fn safe_join(root: &Path, folder: &str) -> Option<PathBuf> {
+    let parts: Vec<&str> = folder
+        .split(['/', '\\'])
+        .map(str::trim)
+        .filter(|p| !p.is_empty() && *p != "." && *p != "..")
+        .collect();
+    if parts.is_empty() {
+        return None;
+    }
+    Some(parts.iter().fold(root.to_path_buf(), |p, part| p.join(part)))
+}
+
+/// Where `resolve_collision` puts a file
+struct Destination {
+    path: PathBuf,
+    renamed: bool,
+    /// A file already on disk at `path` gets replaced
+    replaces: bool,
+}
+
+/// Picks the final destination for `name` inside `dir` according to the
+/// collision policy. `taken` holds destinations already claimed in this run
+/// (needed for dry runs, where nothing is written to disk). Overwriting only
+/// replaces files that were there before the run; two files of the same run
+/// never overwrite each other, the later one is renamed instead.
+fn resolve_collision(dir: &Path, name: &str, policy: CollisionPolicy, taken: &[PathBuf]) -> Option<Destination> {
+    let candidate = dir.join(name);
+    let claimed = |p: &Path| taken.iter().any(|t| t == p);
+    let occupied = |p: &Path| p.exists() || claimed(p);
+
+    if !occupied(&candidate) {
+        return Some(Destination { path: candidate, renamed: false, replaces: false });
+    }
+
+    match policy {
+        CollisionPolicy::Skip => None,
+        CollisionPolicy::Overwrite if !claimed(&candidate) => Some(Destination {
+            path: candidate,
+            renamed: false,
+            replaces: true,
+        }),
+        CollisionPolicy::Overwrite | CollisionPolicy::Rename => {
+            let path = Path::new(name);
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            let ext = path.extension().map(|e| format!(".{}", e.to_string_lossy()));
+            (1..).map(|n| dir.join(format!("{} ({}){}", stem, n, ext.as_deref().unwrap_or(""))))
+                .find(|p| !occupied(p))
+                .map(|p| Destination { path: p, renamed: true, replaces: false })
+        }
+    }
+}
+
+/// Where an overwritten `dest` inside `root` is kept for run `run_id`
+fn trash_path(root: &Path, run_id: &str, dest: &Path) -> PathBuf {
+    let rel = dest.strip_prefix(root).unwrap_or(dest);
+    root.join(TRASH_DIR).join(run_id).join(rel)
+}
+
+/// Move `path` into the trash, logged
+fn move_to_trash(path: &Path, trashed: &Path, log: &ActivityLog) -> Result<(), String> {
+    if let Some(parent) = trashed.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let entry = ActivityEntry::new(Operation::Move, Via::Organizer)
+        .from(path.to_string_lossy())
+        .to(trashed.to_string_lossy());
+    log.record(entry, move_file(path, trashed))
+}
+
+/// Remove the folders between `path` and `stop` that are empty now
+fn remove_empty_parents(path: &Path, stop: &Path) {
+    let mut current = path.parent();
+    while let Some(dir) = current {
+        if !dir.starts_with(stop) || fs::remove_dir(dir).is_err() {
+            break;
+        }
+        current = dir.parent();
+    }
+}
+
+fn is_partial(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .map(|e| PARTIAL_EXTENSIONS.contains(&e.to_string_lossy().to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+fn candidate_files(root: &Path, recursive: bool) -> Vec<PathBuf> {
+    let max_depth = if recursive { usize::MAX } else { 1 };
+    WalkDir::new(root)
+        .min_depth(1)
+        .max_depth(max_depth)
+        .into_iter()
+        .filter_entry(|e| !e.file_name().to_string_lossy().starts_with('.'))
+        .flatten()
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .collect()
+}
+
+fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    match fs::rename(from, to) {
+        // rename can't cross filesystems, copy + delete instead
+        Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+            fs::copy(from, to).map_err(|e| e.to_string())?;
+            fs::remove_file(from).map_err(|e| e.to_string())
+        }
+        result => result.map_err(|e| e.to_string()),
+    }
+}
+
+fn organize(
+    root: &Path,
+    rules: &[OrganizeRule],
+    options: &OrganizeOptions,
+    log: &ActivityLog,
+) -> Result<OrganizeRun, String> {
+    if !root.is_dir() {
+        return Err("Folder does not exist".into());
+    }
+    let compiled = compile_rules(rules)?;
+    let started_at = now_ms();
+
+    let mut run = OrganizeRun {
+        id: format!("run-{}", started_at),
+        folder: root.to_string_lossy().to_string(),
+        started_at,
+        dry_run: options.dry_run,
+        moves: Vec::new(),
+        skipped: Vec::new(),
+        errors: Vec::new(),
+        created_dirs: Vec::new(),
+        undone: false,
+    };
+    let mut taken: Vec<PathBuf> = Vec::new();
+
+    for file in candidate_files(root, options.recursive) {
+        let name = file.file_name().unwrap_or_default().to_string_lossy().to_string();
+        if is_partial(&name) {
+            run.skipped.push(file.to_string_lossy().to_string());
+            continue;
+        }
+        let Ok(metadata) = fs::metadata(&file) else { continue };
+        let rel = file.strip_prefix(root).unwrap_or(&file).to_string_lossy().replace('\\', "/");
+
+        // First matching rule wins.
+        let matched = compiled.iter().enumerate().find_map(|(i, rule)| {
+            if rule.when.check_file(&name, &rel, &metadata).is_some() {
+                return None;
+            }
+            target_folder(&rule.action, &name, &metadata)
+                .and_then(|folder| safe_join(root, &folder))
+                .map(|dir| (i, dir))
+        });
+        let Some((rule_index, dir)) = matched else { continue };
+
+        // Already where the rule wants it.
+        if file.parent() == Some(dir.as_path()) {
+            continue;
+        }
+
+        let Some(Destination { path: dest, renamed, replaces }) = resolve_collision(&dir, &name, options.collision, &taken)
+        else {
+            run.skipped.push(file.to_string_lossy().to_string());
+            continue;
+        };
+        let mut replaced = None;
+
+        if !options.dry_run {
+            if !dir.exists() {
+                // remember every level we create so undo can clean them up
+                let mut missing = Vec::new();
+                let mut current = dir.as_path();
+                while !current.exists() && current.starts_with(root) {
+                    missing.push(current.to_string_lossy().to_string());
+                    match current.parent() {
+                        Some(parent) => current = parent,
+                        None => break,
+                    }
+                }
+                if let Err(e) = fs::create_dir_all(&dir) {
+                    run.errors.push(format!("{}: {}", dir.display(), e));
+                    continue;
+                }
+                run.created_dirs.extend(missing);
+            }
+            if replaces {
+                let trashed = trash_path(root, &run.id, &dest);
+                if let Err(e) = move_to_trash(&dest, &trashed, log) {
+                    run.errors.push(format!("{}: {}", dest.display(), e));
+                    continue;
+                }
+                replaced = Some(trashed.to_string_lossy().to_string());
+            }
+            let entry = ActivityEntry::new(Operation::Move, Via::Organizer)
+                .from(file.to_string_lossy())
+                .to(dest.to_string_lossy())
+                .bytes(metadata.len());
+            if let Err(e) = log.record(entry, move_file(&file, &dest)) {
+                run.errors.push(format!("{}: {}", file.display(), e));
+                // Put the file that was about to be replaced back
+                if let Some(trashed) = &replaced {
+                    let _ = move_file(Path::new(trashed), &dest);
+                }
+                continue;
+            }
+        }
+
+        taken.push(dest.clone());
+        run.moves.push(OrganizeMove {
+            from: file.to_string_lossy().to_string(),
+            to: dest.to_string_lossy().to_string(),
+            rule_index,
+            renamed,
+            replaced,
+            reverted: false,
+        });
+    }
+
+    Ok(run)
+}
+
+/// Moves the files of `run` back, and the files they replaced too. Each move
+/// that's reverted is marked, so undoing again only retries the failures.
+fn undo(run: &mut OrganizeRun, log: &ActivityLog) -> UndoResult {
+    let mut result = UndoResult { restored: 0, errors: Vec::new() };
+    let trash = Path::new(&run.folder).join(TRASH_DIR).join(&run.id);
+
+    for m in run.moves.iter_mut().rev().filter(|m| !m.reverted) {
+        let (from, to) = (Path::new(&m.from), Path::new(&m.to));
+        if !to.exists() {
+            result.errors.push(format!("{}: no longer exists", m.to));
+            continue;
+        }
+        if from.exists() {
+            result.errors.push(format!("{}: original location is occupied", m.from));
+            continue;
+        }
+        if let Some(parent) = from.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let entry = ActivityEntry::new(Operation::Move, Via::OrganizerUndo)
+            .from(&m.to)
+            .to(&m.from);
+        if let Err(e) = log.record(entry, move_file(to, from)) {
+            result.errors.push(format!("{}: {}", m.to, e));
+            continue;
+        }
+        if let Some(replaced) = &m.replaced {
+            let entry = ActivityEntry::new(Operation::Move, Via::OrganizerUndo)
+                .from(replaced)
+                .to(&m.to);
+            if let Err(e) = log.record(entry, move_file(Path::new(replaced), to)) {
+                result.errors.push(format!("{}: the file it replaced is still at {}: {}", m.to, replaced, e));
+                // The moved file is back; what's left to undo is moving the
+                // replaced one from the trash to where it was
+                let (original, trashed) = (m.to.clone(), replaced.clone());
+                m.from = original;
+                m.to = trashed;
+                m.replaced = None;
+                continue;
+            }
+            remove_empty_parents(Path::new(replaced), &trash);
+        }
+        m.reverted = true;
+        result.restored += 1;
+    }
+
+    // deepest first; remove_dir only succeeds on folders that are empty again
+    let mut dirs = run.created_dirs.clone();
+    dirs.sort_by_key(|d| std::cmp::Reverse(d.len()));
+    for dir in dirs {
+        let _ = fs::remove_dir(dir);
+    }
+
+    result
+}
+
+// --- Commands ---
+
+#[tauri::command]
+pub async fn organize_folder<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    folder: String,
+    rules: Vec<OrganizeRule>,
+    options: Option<OrganizeOptions>,
+) -> Result<OrganizeRun, String> {
+    let log_path = run_log_path(&app_handle)?;
+    let activity = ActivityLog::from_app(&app_handle)?;
+    let folder = crate::path_policy::existing(&app_handle, &folder)?;
+    spawn_blocking(move || {
+        let options = options.unwrap_or_default();
+        let run = organize(&folder, &rules, &options, &activity)?;
+
+        if !options.dry_run && !run.moves.is_empty() {
+            let mut runs = load_runs(&log_path);
+            runs.push(run.clone());
+            store::save_json_atomic(&log_path, &runs)?;
+        }
+
+        Ok(run)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn list_organize_runs<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    folder: Option<String>,
+) -> Result<Vec<OrganizeRun>, String> {
+    let log_path = run_log_path(&app_handle)?;
+    let mut runs = load_runs(&log_path);
+    if let Some(folder) = folder {
+        runs.retain(|r| r.folder == folder);
+    }
+    runs.reverse(); // newest first
+    Ok(runs)
+}
+
+#[tauri::command]
+pub async fn undo_organize_run<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    run_id: String,
+) -> Result<UndoResult, String> {
+    let log_path = run_log_path(&app_handle)?;
+    let activity = ActivityLog::from_app(&app_handle)?;
+    spawn_blocking(move || {
+        let mut runs = load_runs(&log_path);
+        let run = runs
+            .iter_mut()
+            .find(|r| r.id == run_id)
+            .ok_or("Organizer run not found")?;
+        if run.undone {
+            return Err("This run has already been undone".into());
+        }
+
+        let result = undo(run, &activity);
+        // Failed moves stay in the run so undo can be tried again
+        run.undone = result.errors.is_empty();
+        store::save_json_atomic(&log_path, &runs)?;
+        Ok(result)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty folder of its own for each test, plus an activity log outside it
+    fn scratch(name: &str) -> (PathBuf, ActivityLog) {
+        let base = std::env::temp_dir().join(format!("filecanvas-organizer-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&base);
+        fs::create_dir_all(base.join("folder")).unwrap();
+        let log = ActivityLog::at(base.join("activity.csv"));
+        (base.join("folder"), log)
+    }
+
+    fn write(root: &Path, rel: &str, contents: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn read(root: &Path, rel: &str) -> String {
+        fs::read_to_string(root.join(rel)).unwrap()
+    }
+
+    fn rule(action: RuleAction) -> OrganizeRule {
+        OrganizeRule { action, when: FilterRules::default() }
+    }
+
+    fn options(collision: CollisionPolicy) -> OrganizeOptions {
+        OrganizeOptions { dry_run: false, recursive: false, collision }
+    }
+
+    fn cleanup(root: &Path) {
+        fs::remove_dir_all(root.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn category_rules_sort_by_type_and_skip_partial_downloads() {
+        let (root, log) = scratch("category");
+        write(&root, "report.PDF", "pdf");
+        write(&root, "photo.jpg", "jpg");
+        write(&root, "unknown.xyz", "xyz");
+        write(&root, "movie.mp4.part", "part");
+
+        let run = organize(&root, &[rule(RuleAction::Category)], &options(CollisionPolicy::Rename), &log).unwrap();
+        assert_eq!(run.moves.len(), 2);
+        assert_eq!(read(&root, "Documents/report.PDF"), "pdf");
+        assert_eq!(read(&root, "Images/photo.jpg"), "jpg");
+        assert!(root.join("unknown.xyz").exists());
+        assert_eq!(run.skipped, [root.join("movie.mp4.part").to_string_lossy()]);
+        cleanup(&root);
+    }
+
+    #[test]
+    fn date_rules_use_the_modification_time() {
+        let (root, log) = scratch("date");
+        write(&root, "notes.txt", "notes");
+        let modified: DateTime<Local> = fs::metadata(root.join("notes.txt")).unwrap().modified().unwrap().into();
+        let pattern = "{year}/{month}/{category}-{ext}".to_string();
+
+        organize(&root, &[rule(RuleAction::DatePattern { pattern })], &options(CollisionPolicy::Rename), &log).unwrap();
+        let expected = format!("{}/Documents-txt/notes.txt", modified.format("%Y/%m"));
+        assert_eq!(read(&root, &expected), "notes");
+        cleanup(&root);
+    }
+
+    #[test]
+    fn regex_rules_expand_captures_and_stay_inside_the_folder() {
+        let (root, log) = scratch("regex");
+        write(&root, "invoice-2024-03.pdf", "march");
+        write(&root, "other.pdf", "other");
+        let rules = [
+            rule(RuleAction::Regex { pattern: r"^invoice-(\d+)-".into(), target: "Invoices/$1".into() }),
+            rule(RuleAction::Regex { pattern: r"^other".into(), target: "../../escaped".into() }),
+        ];
+
+        let run = organize(&root, &rules, &options(CollisionPolicy::Rename), &log).unwrap();
+        assert_eq!(read(&root, "Invoices/2024/invoice-2024-03.pdf"), "march");
+        assert_eq!(read(&root, "escaped/other.pdf"), "other");
+        assert_eq!(run.moves[0].rule_index + run.moves[1].rule_index, 1);
+
+        let bad = [rule(RuleAction::Regex { pattern: "(".into(), target: "x".into() })];
+        assert!(organize(&root, &bad, &options(CollisionPolicy::Rename), &log).is_err());
+        cleanup(&root);
+    }
+
+    #[test]
+    fn dry_runs_plan_the_same_moves_without_touching_files() {
+        let (root, log) = scratch("dry-run");
+        write(&root, "a.pdf", "a");
+        write(&root, "b.png", "b");
+        let rules = [rule(RuleAction::Category)];
+
+        let dry_run = OrganizeOptions { dry_run: true, ..options(CollisionPolicy::Rename) };
+        let planned = organize(&root, &rules, &dry_run, &log).unwrap();
+        assert!(planned.dry_run);
+        assert!(root.join("a.pdf").exists() && !root.join("Documents").exists());
+        assert!(planned.created_dirs.is_empty());
+
+        let done = organize(&root, &rules, &options(CollisionPolicy::Rename), &log).unwrap();
+        let targets = |run: &OrganizeRun| {
+            let mut to: Vec<String> = run.moves.iter().map(|m| m.to.clone()).collect();
+            to.sort();
+            to
+        };
+        assert_eq!(targets(&planned), targets(&done));
+        assert!(root.join("Documents/a.pdf").exists());
+        cleanup(&root);
+    }
+
+    #[test]
+    fn collisions_follow_the_policy() {
+        let rules = [rule(RuleAction::Category)];
+        for (policy, name) in [
+            (CollisionPolicy::Skip, "skip"),
+            (CollisionPolicy::Rename, "rename"),
+            (CollisionPolicy::Overwrite, "overwrite"),
+        ] {
+            let (root, log) = scratch(name);
+            write(&root, "a.pdf", "new");
+            write(&root, "Documents/a.pdf", "old");
+
+            let run = organize(&root, &rules, &options(policy), &log).unwrap();
+            match policy {
+                CollisionPolicy::Skip => {
+                    assert_eq!(run.skipped.len(), 1);
+                    assert_eq!(read(&root, "a.pdf"), "new");
+                    assert_eq!(read(&root, "Documents/a.pdf"), "old");
+                }
+                CollisionPolicy::Rename => {
+                    assert!(run.moves[0].renamed);
+                    assert_eq!(read(&root, "Documents/a (1).pdf"), "new");
+                    assert_eq!(read(&root, "Documents/a.pdf"), "old");
+                }
+                CollisionPolicy::Overwrite => {
+                    assert_eq!(read(&root, "Documents/a.pdf"), "new");
+                    let trashed = run.moves[0].replaced.as_deref().unwrap();
+                    assert!(trashed.contains(TRASH_DIR));
+                    assert_eq!(fs::read_to_string(trashed).unwrap(), "old");
+                }
+            }
+            cleanup(&root);
+        }
+    }
+
+    #[test]
+    fn undo_restores_moved_and_overwritten_files() {
+        let (root, log) = scratch("undo");
+        write(&root, "a.pdf", "new");
+        write(&root, "b.png", "b");
+        write(&root, "Documents/a.pdf", "old");
+
+        let rules = [rule(RuleAction::Category)];
+        let mut run = organize(&root, &rules, &options(CollisionPolicy::Overwrite), &log).unwrap();
+        assert_eq!(run.created_dirs, [root.join("Images").to_string_lossy()]);
+
+        let result = undo(&mut run, &log);
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        assert_eq!(result.restored, 2);
+        assert_eq!(read(&root, "a.pdf"), "new");
+        assert_eq!(read(&root, "b.png"), "b");
+        assert_eq!(read(&root, "Documents/a.pdf"), "old");
+        assert!(!root.join("Images").exists());
+        assert!(!root.join(TRASH_DIR).join(&run.id).exists());
+
+        // Nothing is moved twice
+        assert_eq!(undo(&mut run, &log).restored, 0);
+        cleanup(&root);
+    }
+
+    #[test]
+    fn move_file_reports_rename_errors() {
+        let (root, _) = scratch("move-file");
+        let err = move_file(&root.join("missing.txt"), &root.join("there.txt")).unwrap_err();
+        assert!(!err.is_empty());
+        assert!(!root.join("there.txt").exists());
+        cleanup(&root);
+    }
+}
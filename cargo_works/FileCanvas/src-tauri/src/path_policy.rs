@@ -0,0 +1,248 @@
+// src-tauri/src/path_policy.rs
+//
+// Keeps filesystem commands inside folders the user picked. Every folder
+// chosen through `pick_folder` becomes an approved root (saved in
+// `approved_roots.json` in the app config dir); paths coming from the webview
+// are canonicalized (`..` and symlinks resolved) and rejected unless they
+// land inside one of those roots.
+//
+//   existing(path)      a file or folder to read, open or copy into
+//   entry(path)         an item to delete, rename or move; its own symlink is
+//                       not followed and a root itself is refused
+//   child(parent, name) a new item; `name` must be a plain name
+//   target(path)        a path that may not exist yet (destination roots)
+
+use crate::error::{ErrorCode, FsError, IoContext};
+use crate::store;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{Manager, Runtime};
+
+const ROOTS_FILE: &str = "approved_roots.json";
+
+// --- Data Structures ---
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct RootsFile {
+    roots: Vec<PathBuf>,
+}
+
+/// Approved roots, loaded from disk on first use and shared by all commands
+#[derive(Default)]
+pub struct PathPolicy {
+    roots: Mutex<Option<Vec<PathBuf>>>,
+}
+
+// --- Helpers ---
+
+fn roots_path<R: Runtime>(app_handle: &tauri::AppHandle<R>) -> Result<PathBuf, FsError> {
+    let dir = app_handle.path().app_config_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(ROOTS_FILE))
+}
+
+fn load_roots(path: &Path) -> Vec<PathBuf> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str::<RootsFile>(&s).ok())
+        .map(|f| f.roots)
+        .unwrap_or_default()
+}
+
+fn save_roots(path: &Path, roots: &[PathBuf]) -> Result<(), String> {
+    let file = RootsFile { roots: roots.to_vec() };
+    store::save_json_atomic(path, &file)
+}
+
+/// Run `f` on the approved roots, loading them on first use
+fn with_roots<R: Runtime, T>(
+    app_handle: &tauri::AppHandle<R>,
+    f: impl FnOnce(&mut Vec<PathBuf>) -> T,
+) -> Result<T, FsError> {
+    let policy = app_handle.state::<PathPolicy>();
+    let mut roots = policy.roots.lock().map_err(|e| FsError::from(e.to_string()))?;
+    if roots.is_none() {
+        *roots = Some(load_roots(&roots_path(app_handle)?));
+    }
+    Ok(f(roots.as_mut().expect("roots loaded above")))
+}
+
+/// `canonicalize` on Windows returns `\\?\C:\…`; give the UI `C:\…` back
+fn simplify(path: PathBuf) -> PathBuf {
+    #[cfg(windows)]
+    {
+        let s = path.to_string_lossy();
+        if let Some(unc) = s.strip_prefix(r"\\?\UNC\") {
+            return PathBuf::from(format!(r"\\{}", unc));
+        }
+        if let Some(rest) = s.strip_prefix(r"\\?\") {
+            return PathBuf::from(rest);
+        }
+    }
+    path
+}
+
+fn canonical(path: &Path) -> Result<PathBuf, FsError> {
+    if !path.is_absolute() {
+        return Err(FsError::new(ErrorCode::InvalidPath, format!("Not an absolute path: {}", path.display()))
+            .with_path(path));
+    }
+    path.canonicalize().map(simplify).at(path)
+}
+
+fn invalid_path(path: &Path) -> FsError {
+    FsError::new(ErrorCode::InvalidPath, format!("Invalid path: {}", path.display())).with_path(path)
+}
+
+fn inside_root<R: Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    path: &Path,
+    allow_root: bool,
+) -> Result<(), FsError> {
+    let allowed = with_roots(app_handle, |roots| {
+        roots
+            .iter()
+            .any(|root| path.starts_with(root) && (allow_root || path != root))
+    })?;
+    if allowed {
+        Ok(())
+    } else if !allow_root && with_roots(app_handle, |roots| roots.iter().any(|r| r == path))? {
+        Err(FsError::new(
+            ErrorCode::ProtectedRoot,
+            format!("{} is a root folder and can't be changed here", path.display()),
+        )
+        .with_path(path))
+    } else {
+        Err(FsError::new(
+            ErrorCode::OutsideRoot,
+            format!("{} is outside the folders you opened", path.display()),
+        )
+        .with_path(path))
+    }
+}
+
+/// A single path component: not empty, not `.`/`..`, no separators or NUL
+pub fn validate_name(name: &str) -> Result<(), FsError> {
+    let mut components = Path::new(name).components();
+    let single = matches!((components.next(), components.next()), (Some(Component::Normal(_)), None));
+    let bad = !single || name.contains(['/', '\\', '\0']);
+    if bad {
+        return Err(FsError::new(ErrorCode::InvalidName, format!("Invalid name: \"{}\"", name)));
+    }
+    Ok(())
+}
+
+// --- Checks ---
+
+/// Canonical form of an existing path inside an approved root (roots included)
+pub fn existing<R: Runtime>(app_handle: &tauri::AppHandle<R>, path: &str) -> Result<PathBuf, FsError> {
+    let path = canonical(Path::new(path))?;
+    inside_root(app_handle, &path, true)?;
+    Ok(path)
+}
+
+/// An existing item to delete, rename or move. The parent is canonicalized
+/// but the item itself is not, so a symlink is handled as the link and not
+/// as whatever it points to. Approved roots themselves are refused.
+pub fn entry<R: Runtime>(app_handle: &tauri::AppHandle<R>, path: &str) -> Result<PathBuf, FsError> {
+    let path = Path::new(path);
+    let name = path
+        .file_name()
+        .ok_or_else(|| invalid_path(path))?;
+    let parent = path
+        .parent()
+        .ok_or_else(|| invalid_path(path))?;
+    let resolved = canonical(parent)?.join(name);
+    fs::symlink_metadata(&resolved).at(&resolved)?;
+    inside_root(app_handle, &resolved, false)?;
+    Ok(resolved)
+}
+
+/// Where a new item called `name` goes inside the existing folder `parent`
+pub fn child<R: Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    parent: &str,
+    name: &str,
+) -> Result<PathBuf, FsError> {
+    validate_name(name)?;
+    Ok(existing(app_handle, parent)?.join(name))
+}
+
+/// A path that may not exist yet: the nearest existing ancestor is
+/// canonicalized and the rest must be plain names
+pub fn target<R: Runtime>(app_handle: &tauri::AppHandle<R>, path: &str) -> Result<PathBuf, FsError> {
+    let path = Path::new(path);
+    let mut missing = Vec::new();
+    let mut base = path;
+    while fs::symlink_metadata(base).is_err() {
+        let name = base
+            .file_name()
+            .ok_or_else(|| invalid_path(path))?;
+        validate_name(&name.to_string_lossy())?;
+        missing.push(name.to_os_string());
+        base = base
+            .parent()
+            .ok_or_else(|| invalid_path(path))?;
+    }
+    let mut resolved = canonical(base)?;
+    inside_root(app_handle, &resolved, true)?;
+    resolved.extend(missing.iter().rev());
+    Ok(resolved)
+}
+
+/// Add a folder the user picked. Folders inside an existing root are
+/// already covered and not stored again. Returns the canonical path.
+pub fn approve<R: Runtime>(app_handle: &tauri::AppHandle<R>, folder: &Path) -> Result<PathBuf, FsError> {
+    let folder = canonical(folder)?;
+    let file = roots_path(app_handle)?;
+    with_roots(app_handle, |roots| {
+        if roots.iter().any(|r| folder.starts_with(r)) {
+            return Ok(());
+        }
+        // A new parent replaces the roots it contains
+        roots.retain(|r| !r.starts_with(&folder));
+        roots.push(folder.clone());
+        save_roots(&file, roots)
+    })?
+    .map_err(FsError::from)?;
+    Ok(folder)
+}
+
+// --- Commands ---
+
+#[tauri::command]
+pub fn list_approved_roots<R: Runtime>(app_handle: tauri::AppHandle<R>) -> Result<Vec<String>, FsError> {
+    with_roots(&app_handle, |roots| {
+        roots.iter().map(|r| r.to_string_lossy().to_string()).collect()
+    })
+}
+
+/// Stop allowing access to `root`; it has to be picked again to be used.
+/// The path is matched in canonical form; a root that no longer exists is
+/// matched as given.
+#[tauri::command]
+pub fn revoke_root<R: Runtime>(app_handle: tauri::AppHandle<R>, root: String) -> Result<(), FsError> {
+    let given = PathBuf::from(root);
+    let root = given.canonicalize().map(simplify).unwrap_or_else(|_| simplify(given.clone()));
+    let file = roots_path(&app_handle)?;
+    let removed = with_roots(&app_handle, |roots| {
+        let before = roots.len();
+        roots.retain(|r| *r != root);
+        if roots.len() == before {
+            return Ok(false);
+        }
+        save_roots(&file, roots).map(|_| true)
+    })?
+    .map_err(FsError::from)?;
+    if !removed {
+        return Err(FsError::new(
+            ErrorCode::NotFound,
+            format!("{} is not one of the folders you opened", given.display()),
+        )
+        .with_path(&given));
+    }
+    Ok(())
+}
@@ -0,0 +1,440 @@
+// src-tauri/src/remote.rs
+//
+// Remote folders for panels. A panel path like `sftp://user@host/path`,
+// `s3://host/bucket/prefix` or `davs://user@host/path` is served by a session
+// kept open in `RemoteSessions`; everything else is a local path checked by
+// path_policy. `existing`, `entry` and `child` mirror
+// the path_policy checks and return a `Location` the file commands can run
+// `ops` against, whichever side it's on.
+//
+// Credentials are saved in the OS keyring (Keychain, Credential Manager,
+// Secret Service) under "FileCanvas" / `user@host:port` (SFTP),
+// `s3://host:port` (S3) or `davs://user@host:port` (WebDAV), only when the
+// user asks to remember them. Host keys
+// the user confirmed go to `known_hosts` in the app config dir. Without saved
+// credentials, reconnecting tries ssh-agent, or the AWS_* environment
+// variables for S3.
+
+use crate::error::{ErrorCode, FsError};
+use crate::path_policy;
+use filecanvas_lib::s3::{self, AccessKey, S3Fs, S3Target};
+use filecanvas_lib::sftp::{self, Auth, HostKeys, SftpFs, SftpTarget};
+use filecanvas_lib::vfs::{DiskFs, FileSystem, RemoteFs};
+use filecanvas_lib::webdav::{self, DavFs, DavTarget};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::async_runtime::spawn_blocking;
+use tauri::{Manager, Runtime};
+
+const KEYRING_SERVICE: &str = "FileCanvas";
+const KNOWN_HOSTS_FILE: &str = "known_hosts";
+
+// --- Data Structures ---
+
+/// Open sessions by server key (`user@host:port`, `s3://host:port`,
+/// `davs://user@host:port`)
+type Sessions = HashMap<String, Arc<dyn RemoteFs>>;
+
+#[derive(Default)]
+pub struct RemoteSessions {
+    sessions: Mutex<Sessions>,
+}
+
+/// What's saved in the keyring for a server
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Credentials {
+    password: Option<String>,
+    key_path: Option<PathBuf>,
+    passphrase: Option<String>,
+}
+
+/// A checked path and the filesystem it lives on
+pub enum Location {
+    Local(PathBuf),
+    Remote { fs: Arc<dyn RemoteFs>, path: PathBuf },
+}
+
+// --- Helpers ---
+
+impl Credentials {
+    fn auth(&self) -> Auth {
+        match (&self.key_path, &self.password) {
+            (Some(path), _) => Auth::KeyFile {
+                path: path.clone(),
+                passphrase: self.passphrase.clone(),
+            },
+            (None, Some(password)) => Auth::Password(password.clone()),
+            (None, None) => Auth::Agent,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.password.is_none() && self.key_path.is_none()
+    }
+}
+
+fn keyring_entry(server: &str) -> Result<keyring::Entry, FsError> {
+    keyring::Entry::new(KEYRING_SERVICE, server)
+        .map_err(|e| FsError::new(ErrorCode::Unsupported, format!("No credential store available: {}", e)))
+}
+
+/// What's saved for `server`, as SFTP `Credentials` or an S3 `AccessKey`
+fn load_credentials<T: serde::de::DeserializeOwned>(server: &str) -> Option<T> {
+    keyring_entry(server)
+        .ok()
+        .and_then(|entry| entry.get_password().ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+}
+
+fn save_credentials<T: Serialize>(server: &str, credentials: &T) -> Result<(), FsError> {
+    let json = serde_json::to_string(credentials).map_err(|e| FsError::from(e.to_string()))?;
+    keyring_entry(server)?
+        .set_password(&json)
+        .map_err(|e| FsError::from(format!("Could not save credentials: {}", e)))
+}
+
+fn known_hosts_path<R: Runtime>(app_handle: &tauri::AppHandle<R>) -> Result<PathBuf, FsError> {
+    let dir = app_handle.path().app_config_dir().map_err(|e| FsError::from(e.to_string()))?;
+    fs::create_dir_all(&dir).map_err(|e| FsError::io(&e, &dir))?;
+    Ok(dir.join(KNOWN_HOSTS_FILE))
+}
+
+/// `~/.ssh/id_ed25519` as typed in the dialog; libssh2 doesn't expand `~`
+fn expand_home<R: Runtime>(app_handle: &tauri::AppHandle<R>, path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), app_handle.path().home_dir()) {
+        (Some(rest), Ok(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn lock_sessions<R: Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+) -> Result<std::sync::MutexGuard<'_, Sessions>, FsError> {
+    app_handle
+        .state::<RemoteSessions>()
+        .inner()
+        .sessions
+        .lock()
+        .map_err(|e| FsError::from(e.to_string()))
+}
+
+/// The open session for `server`, or a new one from `connect` if there is
+/// none or the connection dropped
+fn session<R: Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    server: String,
+    connect: impl FnOnce() -> Result<Arc<dyn RemoteFs>, FsError>,
+) -> Result<Arc<dyn RemoteFs>, FsError> {
+    if let Some(fs) = lock_sessions(app_handle)?.get(&server) {
+        if !fs.is_broken() {
+            return Ok(Arc::clone(fs));
+        }
+    }
+    let fs = connect()?;
+    lock_sessions(app_handle)?.insert(server, Arc::clone(&fs));
+    Ok(fs)
+}
+
+/// Reconnect to an SFTP server with saved credentials, or ssh-agent
+fn reconnect_sftp<R: Runtime>(app_handle: &tauri::AppHandle<R>, target: &SftpTarget) -> Result<Arc<dyn RemoteFs>, FsError> {
+    let known_hosts = known_hosts_path(app_handle)?;
+    let keys = HostKeys {
+        file: &known_hosts,
+        trust: None,
+    };
+    let credentials: Credentials = load_credentials(&target.key()).unwrap_or_default();
+    Ok(Arc::new(SftpFs::connect(target, &credentials.auth(), &keys)?))
+}
+
+/// Reconnect to an S3 endpoint with the saved access key, or AWS_*
+fn reconnect_s3(target: &S3Target, path: &Path) -> Result<Arc<dyn RemoteFs>, FsError> {
+    let key = load_credentials(&target.key())
+        .or_else(AccessKey::from_env)
+        .ok_or_else(|| {
+            FsError::new(ErrorCode::AuthFailed, format!("No access key saved for {}", target.key()))
+                .with_path(target.url(path))
+        })?;
+    Ok(Arc::new(S3Fs::connect(target, &key, path)?))
+}
+
+/// Reconnect to a WebDAV server with the saved password, or without a login
+fn reconnect_dav(target: &DavTarget, path: &Path) -> Result<Arc<dyn RemoteFs>, FsError> {
+    let credentials: Credentials = load_credentials(&target.key()).unwrap_or_default();
+    Ok(Arc::new(DavFs::connect(target, credentials.password.as_deref(), path)?))
+}
+
+fn remote<R: Runtime>(app_handle: &tauri::AppHandle<R>, url: &str) -> Result<Location, FsError> {
+    if webdav::is_url(url) {
+        let (target, path) = webdav::parse_url(url)?;
+        let fs = session(app_handle, target.key(), || reconnect_dav(&target, &path))?;
+        return Ok(Location::Remote { fs, path });
+    }
+    if s3::is_url(url) {
+        let (target, path) = s3::parse_url(url)?;
+        let fs = session(app_handle, target.key(), || reconnect_s3(&target, &path))?;
+        return Ok(Location::Remote { fs, path });
+    }
+    let (target, path) = sftp::parse_url(url)?;
+    let fs = session(app_handle, target.key(), || reconnect_sftp(app_handle, &target))?;
+    Ok(Location::Remote { fs, path })
+}
+
+/// Session and keyring key of the server `url` points at
+fn server_key(url: &str) -> Result<String, FsError> {
+    if webdav::is_url(url) {
+        Ok(webdav::parse_url(url)?.0.key())
+    } else if s3::is_url(url) {
+        Ok(s3::parse_url(url)?.0.key())
+    } else {
+        Ok(sftp::parse_url(url)?.0.key())
+    }
+}
+
+pub fn is_remote(path: &str) -> bool {
+    sftp::is_url(path) || s3::is_url(path) || webdav::is_url(path)
+}
+
+impl Location {
+    pub fn fs(&self) -> &dyn FileSystem {
+        match self {
+            Location::Local(_) => &DiskFs,
+            Location::Remote { fs, .. } => fs.as_ref() as &dyn FileSystem,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            Location::Local(path) | Location::Remote { path, .. } => path,
+        }
+    }
+
+    /// How the UI refers to `path` on the same filesystem as `self`
+    pub fn url(&self, path: &Path) -> String {
+        match self {
+            Location::Local(_) => path.to_string_lossy().to_string(),
+            Location::Remote { fs, .. } => fs.url(path),
+        }
+    }
+
+    /// Another path on the same filesystem
+    pub fn with_path(&self, path: PathBuf) -> Location {
+        match self {
+            Location::Local(_) => Location::Local(path),
+            Location::Remote { fs, .. } => Location::Remote {
+                fs: Arc::clone(fs),
+                path,
+            },
+        }
+    }
+
+    /// Both on the local disk, or both in the same remote session
+    pub fn same_fs(&self, other: &Location) -> bool {
+        match (self, other) {
+            (Location::Local(_), Location::Local(_)) => true,
+            (Location::Remote { fs: a, .. }, Location::Remote { fs: b, .. }) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+// --- Checks ---
+
+/// An existing file or folder (see `path_policy::existing`)
+pub fn existing<R: Runtime>(app_handle: &tauri::AppHandle<R>, path: &str) -> Result<Location, FsError> {
+    if !is_remote(path) {
+        return path_policy::existing(app_handle, path).map(Location::Local);
+    }
+    let location = remote(app_handle, path)?;
+    location.fs().metadata(location.path())?;
+    Ok(location)
+}
+
+/// An item to delete, rename or move (see `path_policy::entry`). On a server
+/// the filesystem root can't be changed.
+pub fn entry<R: Runtime>(app_handle: &tauri::AppHandle<R>, path: &str) -> Result<Location, FsError> {
+    if !is_remote(path) {
+        return path_policy::entry(app_handle, path).map(Location::Local);
+    }
+    let location = remote(app_handle, path)?;
+    if location.path().parent().is_none() {
+        return Err(FsError::new(
+            ErrorCode::ProtectedRoot,
+            format!("{} is a root folder and can't be changed here", path),
+        )
+        .with_path(path));
+    }
+    location.fs().symlink_metadata(location.path())?;
+    Ok(location)
+}
+
+/// Where a new item called `name` goes inside `parent` (see `path_policy::child`)
+pub fn child<R: Runtime>(app_handle: &tauri::AppHandle<R>, parent: &str, name: &str) -> Result<Location, FsError> {
+    if !is_remote(parent) {
+        return path_policy::child(app_handle, parent, name).map(Location::Local);
+    }
+    path_policy::validate_name(name)?;
+    let location = existing(app_handle, parent)?;
+    Ok(location.with_path(location.path().join(name)))
+}
+
+// --- Commands ---
+
+/// Open a session for an `sftp://` URL and return the URL of the folder.
+///
+/// `password` or `key_path` (+ `passphrase`) log in; without them saved
+/// credentials or ssh-agent are used. `remember` saves what was given to the
+/// keyring. `trust` is the fingerprint from an `unknown_host` error the user
+/// accepted.
+#[tauri::command]
+pub async fn connect_remote<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    url: String,
+    password: Option<String>,
+    key_path: Option<String>,
+    passphrase: Option<String>,
+    remember: bool,
+    trust: Option<String>,
+) -> Result<String, FsError> {
+    spawn_blocking(move || {
+        let (target, path) = sftp::parse_url(&url)?;
+        let given = Credentials {
+            password: password.filter(|p| !p.is_empty()),
+            key_path: key_path.filter(|p| !p.is_empty()).map(|p| expand_home(&app_handle, &p)),
+            passphrase: passphrase.filter(|p| !p.is_empty()),
+        };
+        let credentials = if given.is_empty() {
+            load_credentials(&target.key()).unwrap_or_default()
+        } else {
+            given
+        };
+
+        let known_hosts = known_hosts_path(&app_handle)?;
+        let keys = HostKeys {
+            file: &known_hosts,
+            trust: trust.as_deref(),
+        };
+        let fs = SftpFs::connect(&target, &credentials.auth(), &keys)?;
+        if !fs.metadata(&path)?.is_dir {
+            return Err(FsError::new(ErrorCode::NotADirectory, format!("Not a folder: {}", url)).with_path(&url));
+        }
+
+        if remember && !credentials.is_empty() {
+            // Connected either way; a missing keyring only means asking again next time
+            if let Err(e) = save_credentials(&target.key(), &credentials) {
+                eprintln!("{}", e);
+            }
+        }
+        lock_sessions(&app_handle)?.insert(target.key(), Arc::new(fs) as Arc<dyn RemoteFs>);
+        Ok(target.url(&path))
+    })
+    .await
+    .map_err(|e| FsError::from(e.to_string()))?
+}
+
+/// Open a session for an `s3://` (or `s3+http://`) URL and return the URL
+/// of the folder.
+///
+/// Without `access_key` the saved key or the AWS_* environment variables are
+/// used. `remember` saves the given key to the keyring.
+#[tauri::command]
+pub async fn connect_s3<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    url: String,
+    access_key: Option<String>,
+    secret_key: Option<String>,
+    region: Option<String>,
+    remember: bool,
+) -> Result<String, FsError> {
+    spawn_blocking(move || {
+        let (target, path) = s3::parse_url(&url)?;
+        let given = match (access_key.filter(|k| !k.is_empty()), secret_key) {
+            (Some(id), Some(secret)) => Some(AccessKey {
+                id,
+                secret,
+                region: region.filter(|r| !r.is_empty()),
+            }),
+            _ => None,
+        };
+        let Some(key) = given.clone().or_else(|| load_credentials(&target.key())).or_else(AccessKey::from_env) else {
+            return Err(FsError::new(ErrorCode::AuthFailed, "Enter an access key and secret").with_path(&url));
+        };
+        let fs = S3Fs::connect(&target, &key, &path)?;
+        if !fs.metadata(&path)?.is_dir {
+            return Err(FsError::new(ErrorCode::NotADirectory, format!("Not a folder: {}", url)).with_path(&url));
+        }
+
+        if let (true, Some(key)) = (remember, &given) {
+            // Connected either way; a missing keyring only means asking again next time
+            if let Err(e) = save_credentials(&target.key(), key) {
+                eprintln!("{}", e);
+            }
+        }
+        lock_sessions(&app_handle)?.insert(target.key(), Arc::new(fs) as Arc<dyn RemoteFs>);
+        Ok(target.url(&path))
+    })
+    .await
+    .map_err(|e| FsError::from(e.to_string()))?
+}
+
+/// Open a session for a `davs://` (or `dav://`) URL and return the URL of
+/// the folder.
+///
+/// Without `password` the saved one is used, or no login at all.
+/// `remember` saves the given password to the keyring.
+#[tauri::command]
+pub async fn connect_webdav<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    url: String,
+    password: Option<String>,
+    remember: bool,
+) -> Result<String, FsError> {
+    spawn_blocking(move || {
+        let (target, path) = webdav::parse_url(&url)?;
+        let given = Credentials {
+            password: password.filter(|p| !p.is_empty()),
+            ..Credentials::default()
+        };
+        let credentials = if given.is_empty() {
+            load_credentials(&target.key()).unwrap_or_default()
+        } else {
+            given
+        };
+        let fs = DavFs::connect(&target, credentials.password.as_deref(), &path)?;
+        if !fs.metadata(&path)?.is_dir {
+            return Err(FsError::new(ErrorCode::NotADirectory, format!("Not a folder: {}", url)).with_path(&url));
+        }
+
+        if remember && !credentials.is_empty() {
+            // Connected either way; a missing keyring only means asking again next time
+            if let Err(e) = save_credentials(&target.key(), &credentials) {
+                eprintln!("{}", e);
+            }
+        }
+        lock_sessions(&app_handle)?.insert(target.key(), Arc::new(fs) as Arc<dyn RemoteFs>);
+        Ok(target.url(&path))
+    })
+    .await
+    .map_err(|e| FsError::from(e.to_string()))?
+}
+
+/// Close the session for the server of `url`
+#[tauri::command]
+pub fn disconnect_remote<R: Runtime>(app_handle: tauri::AppHandle<R>, url: String) -> Result<(), FsError> {
+    lock_sessions(&app_handle)?.remove(&server_key(&url)?);
+    Ok(())
+}
+
+/// Disconnect and delete the saved credentials for the server of `url`
+#[tauri::command]
+pub fn forget_remote<R: Runtime>(app_handle: tauri::AppHandle<R>, url: String) -> Result<(), FsError> {
+    let server = server_key(&url)?;
+    lock_sessions(&app_handle)?.remove(&server);
+    match keyring_entry(&server)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(FsError::from(format!("Could not remove credentials: {}", e))),
+    }
+}
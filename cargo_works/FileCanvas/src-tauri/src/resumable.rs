@@ -0,0 +1,367 @@
+// src-tauri/src/resumable.rs
+//
+// Copies of large files that survive being interrupted (a USB drive pulled,
+// a crash, a full disk freed up again). The data goes to a hidden part file
+// next to the destination in `CHUNK`-sized pieces; after each piece is on
+// disk a small resume record notes the source (path, size, mtime) and the
+// SHA-256 of every piece written so far.
+//
+// Copying the same file again finds the record, rereads the part file and
+// keeps the longest prefix whose pieces still match, then continues from
+// there. A changed source or a damaged record starts over. Once everything
+// is written the part file is renamed onto the destination in one step, so
+// the destination is either absent, the old file, or the complete copy.
+//
+// Only copies to another volume take this path; within one volume a reflink
+// or an in-kernel copy is faster than hashing every piece. A folder copy
+// leaves a marker in its destination until it's done, so copying the same
+// folder again continues into the partial tree instead of refusing it. Part
+// files nobody came back for are removed after `ABANDONED_AFTER`.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Files from this size on are copied resumably; smaller ones are quicker
+/// to copy again than to verify
+pub const RESUMABLE_FROM: u64 = 64 * 1024 * 1024;
+/// Bytes per piece: the most that's copied again after an interruption
+#[cfg(not(test))]
+pub const CHUNK: u64 = 16 * 1024 * 1024;
+/// Small pieces, so tests can interrupt a copy between them
+#[cfg(test)]
+pub const CHUNK: u64 = 1024;
+/// Unfinished copies older than this are given up and their files removed
+pub const ABANDONED_AFTER: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const PART_SUFFIX: &str = ".filecanvas-part";
+const RECORD_SUFFIX: &str = ".filecanvas-resume";
+/// In the destination of a folder copy that hasn't finished; holds the source
+const FOLDER_MARKER: &str = ".filecanvas-copy";
+
+// --- Data Structures ---
+
+/// What's stored next to the part file
+#[derive(Debug, Serialize, Deserialize)]
+struct ResumeRecord {
+    source: PathBuf,
+    len: u64,
+    /// Unix milliseconds
+    mtime: u64,
+    chunk_size: u64,
+    /// SHA-256 (hex) of each piece in the part file, in order
+    chunks: Vec<String>,
+}
+
+// --- Helpers ---
+
+/// `<folder>/.<name><suffix>`
+fn hidden_sibling(dest: &Path, suffix: &str) -> PathBuf {
+    let name = dest.file_name().unwrap_or_default().to_string_lossy();
+    dest.with_file_name(format!(".{}{}", name, suffix))
+}
+
+/// Where the data of an unfinished copy to `dest` is written
+pub fn part_path(dest: &Path) -> PathBuf {
+    hidden_sibling(dest, PART_SUFFIX)
+}
+
+/// The resume record of an unfinished copy to `dest`
+pub fn record_path(dest: &Path) -> PathBuf {
+    hidden_sibling(dest, RECORD_SUFFIX)
+}
+
+/// Copy `from` (of `len` bytes) to `to` resumably: large and going to
+/// another volume. A volume that can't be told counts as another one.
+pub fn wanted(from: &Path, len: u64, to: &Path) -> bool {
+    len >= RESUMABLE_FROM && !crate::space::same_volume(from, to)
+}
+
+/// Remove part files and records in `folder` that weren't touched for
+/// `ABANDONED_AFTER`. Best effort: errors are ignored.
+pub fn remove_abandoned(folder: &Path) {
+    let Ok(entries) = fs::read_dir(folder) else { return };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let ours = name.starts_with('.')
+            && [PART_SUFFIX, RECORD_SUFFIX].iter().any(|s| name.ends_with(s) || name.ends_with(&format!("{}.tmp", s)));
+        let abandoned = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.elapsed().ok())
+            .is_some_and(|age| age >= ABANDONED_AFTER);
+        if ours && abandoned {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// Source of the unfinished folder copy that `dest` is, if it is one
+pub fn unfinished_folder_copy(dest: &Path) -> Option<PathBuf> {
+    fs::read_to_string(dest.join(FOLDER_MARKER)).ok().map(PathBuf::from)
+}
+
+/// Create `dest` and mark it as an unfinished copy of `src`
+pub fn start_folder_copy(src: &Path, dest: &Path) -> io::Result<()> {
+    fs::create_dir_all(dest)?;
+    fs::write(dest.join(FOLDER_MARKER), src.as_os_str().as_encoded_bytes())
+}
+
+/// `dest` is complete; a marker that can't be removed only means the next
+/// copy into it may continue instead of refusing
+pub fn finish_folder_copy(dest: &Path) {
+    let _ = fs::remove_file(dest.join(FOLDER_MARKER));
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn mtime_ms(metadata: &fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Length of piece `index` of a file of `len` bytes
+fn chunk_len(index: usize, len: u64) -> u64 {
+    len.saturating_sub(index as u64 * CHUNK).min(CHUNK)
+}
+
+/// Fill `buf` as far as `reader` goes; short only at the end
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// The record for copying `source` (with `metadata`), if one was left and
+/// still describes the same source
+fn load_record(path: &Path, source: &Path, metadata: &fs::Metadata) -> Option<ResumeRecord> {
+    let content = fs::read_to_string(path).ok()?;
+    let record: ResumeRecord = serde_json::from_str(&content).ok()?;
+    let same = record.source == source
+        && record.len == metadata.len()
+        && record.mtime == mtime_ms(metadata)
+        && record.chunk_size == CHUNK;
+    same.then_some(record)
+}
+
+/// Written aside and renamed, so a crash leaves the old record or the new one
+fn save_record(path: &Path, record: &ResumeRecord) -> io::Result<()> {
+    let json = serde_json::to_string(record).map_err(io::Error::other)?;
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    fs::write(&temp, json)?;
+    fs::rename(&temp, path)
+}
+
+/// How many of `chunks` the part file still holds unchanged
+fn verified_chunks(part: &mut File, chunks: &[String], len: u64, buf: &mut [u8]) -> io::Result<usize> {
+    part.seek(SeekFrom::Start(0))?;
+    for (index, expected) in chunks.iter().enumerate() {
+        let want = chunk_len(index, len) as usize;
+        if want == 0 || read_full(part, &mut buf[..want])? < want || hex(&Sha256::digest(&buf[..want])) != *expected {
+            return Ok(index);
+        }
+    }
+    Ok(chunks.len())
+}
+
+// --- Copying ---
+
+/// Copy `from` to `to` (replacing it) through a part file and a resume
+/// record, continuing an earlier interrupted copy where possible. Keeps the
+/// permissions, like `fs::copy`. `on_bytes` is called as data arrives,
+/// starting with the prefix taken over. Returns the bytes copied.
+pub fn copy_file(from: &Path, to: &Path, on_bytes: &mut dyn FnMut(u64)) -> io::Result<u64> {
+    let mut src = File::open(from)?;
+    let metadata = src.metadata()?;
+    let len = metadata.len();
+    let part_path = part_path(to);
+    let record_path = record_path(to);
+    if let Some(folder) = to.parent() {
+        remove_abandoned(folder);
+    }
+
+    let mut part = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&part_path)?;
+    let mut buf = vec![0u8; CHUNK as usize];
+
+    let mut record = load_record(&record_path, from, &metadata).unwrap_or_else(|| ResumeRecord {
+        source: from.to_path_buf(),
+        len,
+        mtime: mtime_ms(&metadata),
+        chunk_size: CHUNK,
+        chunks: Vec::new(),
+    });
+    let kept = verified_chunks(&mut part, &record.chunks, len, &mut buf)?;
+    record.chunks.truncate(kept);
+
+    let mut copied = (kept as u64 * CHUNK).min(len);
+    part.set_len(copied)?;
+    part.seek(SeekFrom::Start(copied))?;
+    src.seek(SeekFrom::Start(copied))?;
+    if copied > 0 {
+        on_bytes(copied);
+    }
+
+    loop {
+        let n = read_full(&mut src, &mut buf)?;
+        if n == 0 {
+            break;
+        }
+        part.write_all(&buf[..n])?;
+        // The record may only name data that's really on the disk
+        part.sync_data()?;
+        record.chunks.push(hex(&Sha256::digest(&buf[..n])));
+        save_record(&record_path, &record)?;
+        copied += n as u64;
+        on_bytes(n as u64);
+        if (n as u64) < CHUNK {
+            break;
+        }
+    }
+
+    part.set_permissions(metadata.permissions())?;
+    drop(part);
+    fs::rename(&part_path, to)?;
+    // The copy is complete; a record left behind is ignored next time
+    let _ = fs::remove_file(&record_path);
+    Ok(copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 5½ pieces of data that differ from piece to piece
+    const LEN: u64 = 5 * CHUNK + CHUNK / 2;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("filecanvas-resumable-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn source(dir: &Path) -> (PathBuf, Vec<u8>) {
+        let data: Vec<u8> = (0..LEN).map(|i| (i % 251) as u8).collect();
+        let path = dir.join("source.bin");
+        fs::write(&path, &data).unwrap();
+        (path, data)
+    }
+
+    /// Leave what a copy stopped after `pieces` pieces leaves behind
+    fn interrupted(from: &Path, to: &Path, pieces: usize) {
+        let data = fs::read(from).unwrap();
+        let metadata = fs::metadata(from).unwrap();
+        let written = &data[..(pieces as u64 * CHUNK) as usize];
+        fs::write(part_path(to), written).unwrap();
+        let record = ResumeRecord {
+            source: from.to_path_buf(),
+            len: metadata.len(),
+            mtime: mtime_ms(&metadata),
+            chunk_size: CHUNK,
+            chunks: written.chunks(CHUNK as usize).map(|c| hex(&Sha256::digest(c))).collect(),
+        };
+        save_record(&record_path(to), &record).unwrap();
+    }
+
+    /// Copy and return what `on_bytes` was told, in order
+    fn copy(from: &Path, to: &Path) -> Vec<u64> {
+        let mut reported = Vec::new();
+        let copied = copy_file(from, to, &mut |n| reported.push(n)).unwrap();
+        assert_eq!(copied, LEN);
+        assert!(!part_path(to).exists());
+        assert!(!record_path(to).exists());
+        reported
+    }
+
+    /// `taken_over` bytes reported at once, then the rest piece by piece
+    fn resumed_from(taken_over: u64) -> Vec<u64> {
+        let mut expected = vec![taken_over];
+        expected.retain(|&n| n > 0);
+        let mut at = taken_over;
+        while at < LEN {
+            expected.push(CHUNK.min(LEN - at));
+            at += CHUNK;
+        }
+        expected
+    }
+
+    #[test]
+    fn a_fresh_copy_writes_every_piece() {
+        let dir = scratch("fresh");
+        let (from, data) = source(&dir);
+        let to = dir.join("copy.bin");
+        fs::write(&to, "old").unwrap();
+
+        assert_eq!(copy(&from, &to), resumed_from(0));
+        assert_eq!(fs::read(&to).unwrap(), data);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_truncated_part_file_resumes_after_the_last_whole_piece() {
+        let dir = scratch("truncated");
+        let (from, data) = source(&dir);
+        let to = dir.join("copy.bin");
+        interrupted(&from, &to, 3);
+        // The drive went away halfway through writing the third piece
+        let part = fs::OpenOptions::new().write(true).open(part_path(&to)).unwrap();
+        part.set_len(2 * CHUNK + CHUNK / 2).unwrap();
+        drop(part);
+
+        assert_eq!(copy(&from, &to), resumed_from(2 * CHUNK));
+        assert_eq!(fs::read(&to).unwrap(), data);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_damaged_piece_is_copied_again_with_everything_after_it() {
+        let dir = scratch("damaged");
+        let (from, data) = source(&dir);
+        let to = dir.join("copy.bin");
+        interrupted(&from, &to, 4);
+        let mut part = fs::read(part_path(&to)).unwrap();
+        part[(CHUNK + 10) as usize] ^= 0xff;
+        fs::write(part_path(&to), part).unwrap();
+
+        assert_eq!(copy(&from, &to), resumed_from(CHUNK));
+        assert_eq!(fs::read(&to).unwrap(), data);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_changed_source_starts_over() {
+        let dir = scratch("changed");
+        let (from, data) = source(&dir);
+        let to = dir.join("copy.bin");
+        interrupted(&from, &to, 3);
+        let later = fs::metadata(&from).unwrap().modified().unwrap() + Duration::from_secs(60);
+        File::options().write(true).open(&from).unwrap().set_modified(later).unwrap();
+
+        assert_eq!(copy(&from, &to), resumed_from(0));
+        assert_eq!(fs::read(&to).unwrap(), data);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
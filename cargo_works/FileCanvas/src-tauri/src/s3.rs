@@ -0,0 +1,981 @@
+// src-tauri/src/s3.rs
+//
+// `FileSystem` over an S3-compatible object store (AWS, MinIO, …), so a panel
+// can show `s3://host[:port]/bucket/prefix` and connections can copy or move
+// objects to and from it. `s3+http://` is the same without TLS, for a local
+// MinIO.
+//
+// The root lists buckets, and below a bucket every `/` in an object key is a
+// folder: `photos/2024/a.jpg` shows up as folders `photos` and `2024` holding
+// `a.jpg`. An empty folder is a zero-byte `photos/2024/` marker object, the
+// way the AWS console creates them. Objects have no creation time, so
+// `ctime` is their LastModified like `mtime`.
+//
+// Requests use path-style addressing and are signed with AWS Signature V4.
+// Renames are server-side copies followed by deletes; uploads larger than
+// `PART_SIZE` go up as multipart uploads.
+
+use crate::error::{ErrorCode, FsError};
+use crate::vfs::{url_path, DirEntry, FileSystem, Metadata, RemoteFs};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{self, ErrorKind, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const SCHEME: &str = "s3://";
+/// Same as `s3://` but over plain HTTP
+pub const PLAIN_SCHEME: &str = "s3+http://";
+pub const DEFAULT_REGION: &str = "us-east-1";
+/// Uploads up to this size are a single PUT; larger ones are sent in parts
+/// of this size (S3 wants at least 5 MiB per part)
+pub const PART_SIZE: usize = 8 * 1024 * 1024;
+/// Largest object a single server-side copy can create
+const MAX_COPY_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const IO_TIMEOUT: Duration = Duration::from_secs(60);
+const EMPTY_SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+/// Characters SigV4 leaves unencoded: A-Z a-z 0-9 - _ . ~
+const URI_UNRESERVED: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'_').remove(b'.').remove(b'~');
+
+// --- Data Structures ---
+
+/// Which endpoint to talk to; the part of an `s3://` URL before the path
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct S3Target {
+    pub host: String,
+    pub port: u16,
+    pub https: bool,
+}
+
+/// An access key pair and the region to sign for
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccessKey {
+    pub id: String,
+    pub secret: String,
+    /// `us-east-1` when empty; MinIO accepts anything
+    #[serde(default)]
+    pub region: Option<String>,
+}
+
+pub struct S3Fs {
+    client: Arc<Client>,
+}
+
+struct Client {
+    target: S3Target,
+    key: AccessKey,
+    agent: ureq::Agent,
+}
+
+/// Where a path points inside the store
+enum Place {
+    /// The list of buckets
+    Root,
+    Bucket(String),
+    Object { bucket: String, key: String },
+}
+
+/// One object from a listing
+struct Object {
+    key: String,
+    size: u64,
+    mtime: u64,
+}
+
+#[derive(Default)]
+struct Listing {
+    /// `prefix/sub/` for every folder directly below the listed prefix
+    folders: Vec<String>,
+    objects: Vec<Object>,
+}
+
+/// Why a request failed, before it's tied to a path
+#[derive(Debug)]
+enum Failure {
+    /// The server answered with an error status and S3 error `code`
+    Status { status: u16, code: String, message: String },
+    /// No answer: DNS, connection refused, TLS, timeout
+    Transport(String),
+}
+
+/// An upload in progress from `open_write`
+struct Upload {
+    client: Arc<Client>,
+    bucket: String,
+    key: String,
+    buffer: Vec<u8>,
+    /// Set once the object is too big for one PUT
+    upload_id: Option<String>,
+    etags: Vec<String>,
+    finished: bool,
+}
+
+// --- Helpers ---
+
+impl S3Target {
+    fn scheme(&self) -> &'static str {
+        if self.https {
+            SCHEME
+        } else {
+            PLAIN_SCHEME
+        }
+    }
+
+    fn default_port(&self) -> u16 {
+        if self.https {
+            443
+        } else {
+            80
+        }
+    }
+
+    /// `host[:port]` as sent in the Host header and shown in URLs
+    fn authority(&self) -> String {
+        let host = if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        };
+        if self.port == self.default_port() {
+            host
+        } else {
+            format!("{}:{}", host, self.port)
+        }
+    }
+
+    /// `s3://host:port`, used as the key for sessions and stored credentials
+    pub fn key(&self) -> String {
+        format!("{}{}:{}", self.scheme(), self.host, self.port)
+    }
+
+    /// URL for `path` in this store
+    pub fn url(&self, path: &Path) -> String {
+        format!("{}{}{}", self.scheme(), self.authority(), path.to_string_lossy())
+    }
+
+    fn endpoint(&self) -> String {
+        let scheme = if self.https { "https" } else { "http" };
+        format!("{}://{}", scheme, self.authority())
+    }
+}
+
+impl AccessKey {
+    /// `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and `AWS_REGION`, like
+    /// the AWS command line tools
+    pub fn from_env() -> Option<AccessKey> {
+        Some(AccessKey {
+            id: std::env::var("AWS_ACCESS_KEY_ID").ok()?,
+            secret: std::env::var("AWS_SECRET_ACCESS_KEY").ok()?,
+            region: std::env::var("AWS_REGION").ok(),
+        })
+    }
+
+    fn region(&self) -> &str {
+        self.region.as_deref().filter(|r| !r.is_empty()).unwrap_or(DEFAULT_REGION)
+    }
+}
+
+pub fn is_url(path: &str) -> bool {
+    path.starts_with(SCHEME) || path.starts_with(PLAIN_SCHEME)
+}
+
+/// Split `s3://host[:port][/bucket/key]` into target and absolute path
+pub fn parse_url(url: &str) -> Result<(S3Target, PathBuf), FsError> {
+    let invalid = || FsError::new(ErrorCode::InvalidPath, format!("Not an s3://host/bucket URL: {}", url)).with_path(url);
+    let (https, rest) = match (url.strip_prefix(SCHEME), url.strip_prefix(PLAIN_SCHEME)) {
+        (Some(rest), _) => (true, rest),
+        (None, Some(rest)) => (false, rest),
+        (None, None) => return Err(invalid()),
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let (host, port) = if let Some(v6) = authority.strip_prefix('[') {
+        let (host, after) = v6.split_once(']').ok_or_else(invalid)?;
+        (host, after.strip_prefix(':'))
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        }
+    };
+    if host.is_empty() {
+        return Err(invalid());
+    }
+    let mut target = S3Target {
+        host: host.to_string(),
+        port: 0,
+        https,
+    };
+    target.port = match port {
+        Some(p) => p.parse().map_err(|_| invalid())?,
+        None => target.default_port(),
+    };
+    Ok((target, url_path(path, url)?))
+}
+
+fn place(path: &Path) -> Place {
+    let mut parts = path.components().filter_map(|c| match c {
+        Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+        _ => None,
+    });
+    match parts.next() {
+        None => Place::Root,
+        Some(bucket) => {
+            let key = parts.collect::<Vec<_>>().join("/");
+            if key.is_empty() {
+                Place::Bucket(bucket)
+            } else {
+                Place::Object { bucket, key }
+            }
+        }
+    }
+}
+
+fn encode(value: &str) -> String {
+    utf8_percent_encode(value, URI_UNRESERVED).to_string()
+}
+
+/// `/bucket/key` with every segment encoded, as SigV4 wants it
+fn object_uri(bucket: &str, key: &str) -> String {
+    let mut uri = format!("/{}", encode(bucket));
+    if !key.is_empty() {
+        uri.push('/');
+        uri.push_str(&key.split('/').map(encode).collect::<Vec<_>>().join("/"));
+    }
+    uri
+}
+
+/// Sorted, encoded `a=1&b=2`, used both in the URL and for signing
+fn canonical_query(query: &[(&str, &str)]) -> String {
+    let mut pairs: Vec<(String, String)> = query.iter().map(|(k, v)| (encode(k), encode(v))).collect();
+    pairs.sort();
+    pairs
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// SigV4 `Authorization` header. `headers` are the signed headers with
+/// lowercase names, sorted, including host, x-amz-date and
+/// x-amz-content-sha256.
+fn authorization(
+    key: &AccessKey,
+    method: &str,
+    uri: &str,
+    query: &str,
+    headers: &[(String, String)],
+    payload_hash: &str,
+    amz_date: &str,
+) -> String {
+    let date = &amz_date[..8];
+    let scope = format!("{}/{}/s3/aws4_request", date, key.region());
+    let canonical_headers: String = headers.iter().map(|(k, v)| format!("{}:{}\n", k, v.trim())).collect();
+    let signed_headers = headers.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>().join(";");
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method, uri, query, canonical_headers, signed_headers, payload_hash
+    );
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        sha256_hex(canonical_request.as_bytes())
+    );
+    let mut signing_key = hmac(format!("AWS4{}", key.secret).as_bytes(), date);
+    for part in [key.region(), "s3", "aws4_request"] {
+        signing_key = hmac(&signing_key, part);
+    }
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        key.id,
+        scope,
+        signed_headers,
+        hex(&hmac(&signing_key, &string_to_sign))
+    )
+}
+
+/// Text of the first `<name>` element anywhere in `xml`
+fn xml_text(doc: &roxmltree::Document, name: &str) -> Option<String> {
+    doc.descendants()
+        .find(|n| n.tag_name().name() == name)
+        .and_then(|n| n.text())
+        .map(str::to_string)
+}
+
+fn child_text(node: roxmltree::Node, name: &str) -> Option<String> {
+    node.children()
+        .find(|n| n.tag_name().name() == name)
+        .and_then(|n| n.text())
+        .map(str::to_string)
+}
+
+fn parse_xml(body: &str) -> Result<roxmltree::Document<'_>, Failure> {
+    roxmltree::Document::parse(body).map_err(|e| Failure::Transport(format!("Unreadable answer from the server: {}", e)))
+}
+
+/// `2024-05-01T10:00:00.000Z` in listings, `Wed, 01 May 2024 10:00:00 GMT`
+/// in headers; Unix milliseconds or 0
+fn parse_time(text: &str) -> u64 {
+    DateTime::parse_from_rfc3339(text)
+        .or_else(|_| DateTime::parse_from_rfc2822(text))
+        .map(|t| t.timestamp_millis().max(0) as u64)
+        .unwrap_or(0)
+}
+
+fn folder_metadata(mtime: u64) -> Metadata {
+    Metadata {
+        is_dir: true,
+        is_symlink: false,
+        len: 0,
+        mtime,
+        ctime: mtime,
+    }
+}
+
+fn file_metadata(len: u64, mtime: u64) -> Metadata {
+    Metadata {
+        is_dir: false,
+        is_symlink: false,
+        len,
+        mtime,
+        ctime: mtime,
+    }
+}
+
+fn is_a_folder(path: &Path) -> FsError {
+    FsError::io(&io::Error::from(ErrorKind::IsADirectory), path)
+}
+
+impl Failure {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Failure::Transport(_) => ErrorKind::NotConnected,
+            Failure::Status { status, code, .. } => match (status, code.as_str()) {
+                (_, "NoSuchKey" | "NoSuchBucket" | "NoSuchUpload") | (404, _) => ErrorKind::NotFound,
+                (_, "BucketAlreadyOwnedByYou" | "BucketAlreadyExists") => ErrorKind::AlreadyExists,
+                (_, "BucketNotEmpty") => ErrorKind::DirectoryNotEmpty,
+                (_, "InvalidBucketName" | "KeyTooLongError") => ErrorKind::InvalidFilename,
+                (_, "XMinioStorageFull" | "EntityTooLarge") => ErrorKind::StorageFull,
+                (403, _) => ErrorKind::PermissionDenied,
+                (501, _) => ErrorKind::Unsupported,
+                (503, _) => ErrorKind::ResourceBusy,
+                _ => ErrorKind::Other,
+            },
+        }
+    }
+
+    /// The keys themselves were refused, not just this request
+    fn is_auth(&self) -> bool {
+        matches!(
+            self,
+            Failure::Status { code, .. }
+                if matches!(code.as_str(), "InvalidAccessKeyId" | "SignatureDoesNotMatch" | "InvalidToken")
+        )
+    }
+
+    fn is_not_found(&self) -> bool {
+        self.kind() == ErrorKind::NotFound
+    }
+
+    fn message(&self) -> String {
+        match self {
+            Failure::Transport(message) => message.clone(),
+            Failure::Status { status, code, message } if message.is_empty() && code.is_empty() => {
+                format!("HTTP {}", status)
+            }
+            Failure::Status { code, message, .. } => format!("{}: {}", code, message),
+        }
+    }
+
+    fn into_io(self) -> io::Error {
+        io::Error::new(self.kind(), self.message())
+    }
+
+    /// As an `FsError` about `path`
+    fn at(self, target: &S3Target, path: &Path) -> FsError {
+        match self {
+            Failure::Transport(message) => FsError::new(
+                ErrorCode::ConnectionFailed,
+                format!("Can't reach {}: {}", target.authority(), message),
+            )
+            .with_path(target.url(path)),
+            failure if failure.is_auth() => FsError::new(
+                ErrorCode::AuthFailed,
+                format!("{} rejected the access key: {}", target.authority(), failure.message()),
+            )
+            .with_path(target.url(path)),
+            failure => FsError::io(&failure.into_io(), path),
+        }
+    }
+}
+
+// --- Requests ---
+
+impl Client {
+    /// Send a signed request for `uri` (see `object_uri`)
+    fn send(
+        &self,
+        method: &str,
+        uri: &str,
+        query: &[(&str, &str)],
+        extra_headers: &[(&str, String)],
+        body: &[u8],
+    ) -> Result<ureq::Response, Failure> {
+        let query = canonical_query(query);
+        let payload_hash = if body.is_empty() {
+            EMPTY_SHA256.to_string()
+        } else {
+            sha256_hex(body)
+        };
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+        let mut headers: Vec<(String, String)> = vec![
+            ("host".to_string(), self.target.authority()),
+            ("x-amz-content-sha256".to_string(), payload_hash.clone()),
+            ("x-amz-date".to_string(), amz_date.clone()),
+        ];
+        headers.extend(extra_headers.iter().map(|(k, v)| (k.to_lowercase(), v.clone())));
+        headers.sort();
+        let auth = authorization(&self.key, method, uri, &query, &headers, &payload_hash, &amz_date);
+
+        let url = if query.is_empty() {
+            format!("{}{}", self.target.endpoint(), uri)
+        } else {
+            format!("{}{}?{}", self.target.endpoint(), uri, query)
+        };
+        let mut request = self.agent.request(method, &url).set("Authorization", &auth);
+        for (name, value) in headers.iter().filter(|(name, _)| name != "host") {
+            request = request.set(name, value);
+        }
+        match request.send_bytes(body) {
+            Ok(response) => Ok(response),
+            Err(ureq::Error::Status(status, response)) => {
+                let body = response.into_string().unwrap_or_default();
+                let doc = roxmltree::Document::parse(&body).ok();
+                let field = |name| doc.as_ref().and_then(|d| xml_text(d, name)).unwrap_or_default();
+                Err(Failure::Status {
+                    status,
+                    code: field("Code"),
+                    message: field("Message"),
+                })
+            }
+            Err(ureq::Error::Transport(e)) => Err(Failure::Transport(e.to_string())),
+        }
+    }
+
+    fn read_body(response: ureq::Response) -> Result<String, Failure> {
+        response.into_string().map_err(|e| Failure::Transport(e.to_string()))
+    }
+
+    fn list_buckets(&self) -> Result<Vec<(String, u64)>, Failure> {
+        let body = Self::read_body(self.send("GET", "/", &[], &[], &[])?)?;
+        let doc = parse_xml(&body)?;
+        Ok(doc
+            .descendants()
+            .filter(|n| n.tag_name().name() == "Bucket")
+            .filter_map(|n| {
+                let name = child_text(n, "Name")?;
+                let created = child_text(n, "CreationDate").map(|t| parse_time(&t)).unwrap_or(0);
+                Some((name, created))
+            })
+            .collect())
+    }
+
+    /// Objects below `prefix`; with `folders` only one level deep, with the
+    /// next level of prefixes in `Listing::folders`. `limit` stops early.
+    fn list(&self, bucket: &str, prefix: &str, folders: bool, limit: Option<usize>) -> Result<Listing, Failure> {
+        let uri = object_uri(bucket, "");
+        let mut listing = Listing::default();
+        let mut token: Option<String> = None;
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", prefix)];
+            if folders {
+                query.push(("delimiter", "/"));
+            }
+            if let Some(token) = &token {
+                query.push(("continuation-token", token));
+            }
+            let body = Self::read_body(self.send("GET", &uri, &query, &[], &[])?)?;
+            let doc = parse_xml(&body)?;
+            for node in doc.descendants() {
+                match node.tag_name().name() {
+                    "Contents" => listing.objects.push(Object {
+                        key: child_text(node, "Key").unwrap_or_default(),
+                        size: child_text(node, "Size").and_then(|s| s.parse().ok()).unwrap_or(0),
+                        mtime: child_text(node, "LastModified").map(|t| parse_time(&t)).unwrap_or(0),
+                    }),
+                    "CommonPrefixes" => listing.folders.extend(child_text(node, "Prefix")),
+                    _ => {}
+                }
+            }
+            let found = listing.objects.len() + listing.folders.len();
+            token = match xml_text(&doc, "IsTruncated").as_deref() {
+                Some("true") if limit.is_none_or(|limit| found < limit) => xml_text(&doc, "NextContinuationToken"),
+                _ => None,
+            };
+            if token.is_none() {
+                return Ok(listing);
+            }
+        }
+    }
+
+    /// Size and LastModified of an object
+    fn head(&self, bucket: &str, key: &str) -> Result<Metadata, Failure> {
+        let response = self.send("HEAD", &object_uri(bucket, key), &[], &[], &[])?;
+        let len = response.header("Content-Length").and_then(|v| v.parse().ok()).unwrap_or(0);
+        let mtime = response.header("Last-Modified").map(parse_time).unwrap_or(0);
+        Ok(file_metadata(len, mtime))
+    }
+
+    fn put(&self, bucket: &str, key: &str, body: &[u8]) -> Result<(), Failure> {
+        self.send("PUT", &object_uri(bucket, key), &[], &[], body).map(|_| ())
+    }
+
+    fn delete(&self, bucket: &str, key: &str) -> Result<(), Failure> {
+        self.send("DELETE", &object_uri(bucket, key), &[], &[], &[]).map(|_| ())
+    }
+
+    /// Server-side copy of one object
+    fn copy(&self, from_bucket: &str, from_key: &str, to_bucket: &str, to_key: &str) -> Result<(), Failure> {
+        let source = object_uri(from_bucket, from_key);
+        let response = self.send(
+            "PUT",
+            &object_uri(to_bucket, to_key),
+            &[],
+            &[("x-amz-copy-source", source)],
+            &[],
+        )?;
+        // A copy can fail after the 200 has been sent; the body says so
+        let body = Self::read_body(response)?;
+        check_completed(&body)
+    }
+
+    fn create_bucket(&self, bucket: &str) -> Result<(), Failure> {
+        let body = match self.key.region() {
+            DEFAULT_REGION => String::new(),
+            region => format!(
+                "<CreateBucketConfiguration><LocationConstraint>{}</LocationConstraint></CreateBucketConfiguration>",
+                region
+            ),
+        };
+        self.send("PUT", &object_uri(bucket, ""), &[], &[], body.as_bytes()).map(|_| ())
+    }
+
+    /// Delete every object whose key starts with `prefix`. Returns how many.
+    fn delete_prefix(&self, bucket: &str, prefix: &str) -> Result<usize, Failure> {
+        let objects = self.list(bucket, prefix, false, None)?.objects;
+        for object in &objects {
+            self.delete(bucket, &object.key)?;
+        }
+        Ok(objects.len())
+    }
+}
+
+/// CompleteMultipartUpload and CopyObject answer 200 and put errors in the body
+fn check_completed(body: &str) -> Result<(), Failure> {
+    let Ok(doc) = roxmltree::Document::parse(body) else {
+        return Ok(());
+    };
+    if doc.root_element().tag_name().name() != "Error" {
+        return Ok(());
+    }
+    Err(Failure::Status {
+        status: 500,
+        code: xml_text(&doc, "Code").unwrap_or_default(),
+        message: xml_text(&doc, "Message").unwrap_or_default(),
+    })
+}
+
+// --- Uploads ---
+
+impl Upload {
+    fn start_multipart(&mut self) -> Result<String, Failure> {
+        let uri = object_uri(&self.bucket, &self.key);
+        let response = self.client.send("POST", &uri, &[("uploads", "")], &[], &[])?;
+        let body = Client::read_body(response)?;
+        let doc = parse_xml(&body)?;
+        xml_text(&doc, "UploadId").ok_or_else(|| Failure::Transport("The server returned no upload id".to_string()))
+    }
+
+    fn send_part(&mut self, part: &[u8]) -> Result<(), Failure> {
+        let upload_id = match &self.upload_id {
+            Some(id) => id.clone(),
+            None => {
+                let id = self.start_multipart()?;
+                self.upload_id = Some(id.clone());
+                id
+            }
+        };
+        let number = (self.etags.len() + 1).to_string();
+        let uri = object_uri(&self.bucket, &self.key);
+        let query = [("partNumber", number.as_str()), ("uploadId", upload_id.as_str())];
+        let response = self.client.send("PUT", &uri, &query, &[], part)?;
+        let etag = response.header("ETag").unwrap_or_default().to_string();
+        self.etags.push(etag);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Failure> {
+        let Some(upload_id) = self.upload_id.clone() else {
+            return self.client.put(&self.bucket, &self.key, &self.buffer);
+        };
+        if !self.buffer.is_empty() {
+            let last = std::mem::take(&mut self.buffer);
+            self.send_part(&last)?;
+        }
+        let parts: String = self
+            .etags
+            .iter()
+            .enumerate()
+            .map(|(i, etag)| format!("<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>", i + 1, etag))
+            .collect();
+        let body = format!("<CompleteMultipartUpload>{}</CompleteMultipartUpload>", parts);
+        let uri = object_uri(&self.bucket, &self.key);
+        let response = self
+            .client
+            .send("POST", &uri, &[("uploadId", upload_id.as_str())], &[], body.as_bytes())?;
+        check_completed(&Client::read_body(response)?)
+    }
+}
+
+impl Write for Upload {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.finished {
+            return Err(io::Error::other("The upload was already completed"));
+        }
+        self.buffer.extend_from_slice(buf);
+        while self.buffer.len() >= PART_SIZE {
+            let part: Vec<u8> = self.buffer.drain(..PART_SIZE).collect();
+            self.send_part(&part).map_err(Failure::into_io)?;
+        }
+        Ok(buf.len())
+    }
+
+    /// Completes the object; nothing is visible in the bucket before this
+    fn flush(&mut self) -> io::Result<()> {
+        if self.finished {
+            return Ok(());
+        }
+        self.finish().map_err(Failure::into_io)?;
+        self.finished = true;
+        Ok(())
+    }
+}
+
+impl Drop for Upload {
+    /// An upload that was never flushed is abandoned, so the parts sent so
+    /// far don't keep taking up (billed) space
+    fn drop(&mut self) {
+        if let (false, Some(upload_id)) = (self.finished, &self.upload_id) {
+            let uri = object_uri(&self.bucket, &self.key);
+            let _ = self.client.send("DELETE", &uri, &[("uploadId", upload_id.as_str())], &[], &[]);
+        }
+    }
+}
+
+// --- Connecting ---
+
+impl S3Fs {
+    /// Check the keys against the store: list the buckets, or look at the
+    /// bucket in `path` when there is one (keys limited to a bucket often
+    /// can't list the others)
+    pub fn connect(target: &S3Target, key: &AccessKey, path: &Path) -> Result<S3Fs, FsError> {
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(CONNECT_TIMEOUT)
+            .timeout_read(IO_TIMEOUT)
+            .timeout_write(IO_TIMEOUT)
+            .build();
+        let client = Client {
+            target: target.clone(),
+            key: key.clone(),
+            agent,
+        };
+        let checked = match place(path) {
+            Place::Root => client.list_buckets().map(|_| ()),
+            Place::Bucket(bucket) | Place::Object { bucket, .. } => {
+                client.send("HEAD", &object_uri(&bucket, ""), &[], &[], &[]).map(|_| ())
+            }
+        };
+        checked.map_err(|failure| failure.at(target, path))?;
+        Ok(S3Fs {
+            client: Arc::new(client),
+        })
+    }
+
+    pub fn target(&self) -> &S3Target {
+        &self.client.target
+    }
+
+    fn fail(&self, failure: Failure, path: &Path) -> FsError {
+        failure.at(&self.client.target, path)
+    }
+
+    /// Whether any object lives below `bucket/key/`
+    fn has_children(&self, bucket: &str, key: &str, path: &Path) -> Result<bool, FsError> {
+        let listing = self
+            .client
+            .list(bucket, &format!("{}/", key), true, Some(1))
+            .map_err(|f| self.fail(f, path))?;
+        Ok(!listing.objects.is_empty() || !listing.folders.is_empty())
+    }
+
+    /// Copy everything below `from` to `to`, same relative keys
+    fn copy_prefix(&self, bucket: &str, from: &str, to_bucket: &str, to: &str, path: &Path) -> Result<(), FsError> {
+        let objects = self
+            .client
+            .list(bucket, &format!("{}/", from), false, None)
+            .map_err(|f| self.fail(f, path))?
+            .objects;
+        for object in objects {
+            let rest = &object.key[from.len()..];
+            self.client
+                .copy(bucket, &object.key, to_bucket, &format!("{}{}", to, rest))
+                .map_err(|f| self.fail(f, path))?;
+        }
+        Ok(())
+    }
+
+    /// Copy by streaming through this machine, for objects too large for a
+    /// server-side copy
+    fn stream_copy(&self, from: &Path, to: &Path) -> Result<u64, FsError> {
+        let mut reader = self.open_read(from)?;
+        let mut writer = self.open_write(to)?;
+        let copied = io::copy(&mut reader, &mut writer).map_err(|e| FsError::io(&e, to))?;
+        writer.flush().map_err(|e| FsError::io(&e, to))?;
+        Ok(copied)
+    }
+}
+
+impl FileSystem for S3Fs {
+    fn metadata(&self, path: &Path) -> Result<Metadata, FsError> {
+        match place(path) {
+            Place::Root => Ok(folder_metadata(0)),
+            Place::Bucket(bucket) => self
+                .client
+                .send("HEAD", &object_uri(&bucket, ""), &[], &[], &[])
+                .map(|_| folder_metadata(0))
+                .map_err(|f| self.fail(f, path)),
+            Place::Object { bucket, key } => match self.client.head(&bucket, &key) {
+                Ok(metadata) => Ok(metadata),
+                Err(f) if f.is_not_found() => {
+                    if self.has_children(&bucket, &key, path)? {
+                        Ok(folder_metadata(0))
+                    } else {
+                        Err(self.fail(f, path))
+                    }
+                }
+                Err(f) => Err(self.fail(f, path)),
+            },
+        }
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> Result<Metadata, FsError> {
+        self.metadata(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> Result<Vec<DirEntry>, FsError> {
+        let (bucket, prefix) = match place(path) {
+            Place::Root => {
+                let buckets = self.client.list_buckets().map_err(|f| self.fail(f, path))?;
+                return Ok(buckets
+                    .into_iter()
+                    .map(|(name, created)| DirEntry {
+                        path: path.join(&name),
+                        name,
+                        metadata: folder_metadata(created),
+                    })
+                    .collect());
+            }
+            Place::Bucket(bucket) => (bucket, String::new()),
+            Place::Object { bucket, key } => (bucket, format!("{}/", key)),
+        };
+        let listing = self
+            .client
+            .list(&bucket, &prefix, true, None)
+            .map_err(|f| self.fail(f, path))?;
+        if listing.objects.is_empty() && listing.folders.is_empty() && !prefix.is_empty() {
+            // Nothing below it: a missing folder, or a file
+            if !self.metadata(path)?.is_dir {
+                return Err(FsError::io(&io::Error::from(ErrorKind::NotADirectory), path));
+            }
+        }
+
+        let folders = listing.folders.into_iter().filter_map(|folder| {
+            let name = folder[prefix.len()..].trim_end_matches('/').to_string();
+            (!name.is_empty()).then(|| DirEntry {
+                path: path.join(&name),
+                name,
+                metadata: folder_metadata(0),
+            })
+        });
+        let files = listing.objects.into_iter().filter_map(|object| {
+            // The folder's own `prefix/` marker isn't an entry
+            let name = object.key[prefix.len()..].to_string();
+            (!name.is_empty()).then(|| DirEntry {
+                path: path.join(&name),
+                name,
+                metadata: file_metadata(object.size, object.mtime),
+            })
+        });
+        Ok(folders.chain(files).collect())
+    }
+
+    fn create_dir(&self, path: &Path) -> Result<(), FsError> {
+        match place(path) {
+            Place::Root => Err(FsError::io(&io::Error::from(ErrorKind::AlreadyExists), path)),
+            Place::Bucket(bucket) => self.client.create_bucket(&bucket).map_err(|f| self.fail(f, path)),
+            Place::Object { bucket, key } => {
+                if self.exists(path) {
+                    return Err(FsError::io(&io::Error::from(ErrorKind::AlreadyExists), path));
+                }
+                self.client
+                    .put(&bucket, &format!("{}/", key), &[])
+                    .map_err(|f| self.fail(f, path))
+            }
+        }
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<(), FsError> {
+        match self.metadata(path) {
+            Ok(metadata) if metadata.is_dir => return Ok(()),
+            Ok(_) => return Err(FsError::io(&io::Error::from(ErrorKind::NotADirectory), path)),
+            Err(_) => {}
+        }
+        // Folders between the bucket and `path` exist as soon as the marker does
+        if let Place::Object { bucket, .. } = place(path) {
+            self.create_dir_all(Path::new("/").join(bucket).as_path())?;
+        }
+        self.create_dir(path)
+    }
+
+    fn create_file(&self, path: &Path) -> Result<(), FsError> {
+        let Place::Object { bucket, key } = place(path) else {
+            return Err(FsError::io(&io::Error::from(ErrorKind::AlreadyExists), path));
+        };
+        if self.exists(path) {
+            return Err(FsError::io(&io::Error::from(ErrorKind::AlreadyExists), path));
+        }
+        self.client.put(&bucket, &key, &[]).map_err(|f| self.fail(f, path))
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), FsError> {
+        let Place::Object { bucket, key } = place(path) else {
+            return Err(is_a_folder(path));
+        };
+        self.client.delete(&bucket, &key).map_err(|f| self.fail(f, path))
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> Result<(), FsError> {
+        match place(path) {
+            Place::Root => Err(FsError::new(
+                ErrorCode::ProtectedRoot,
+                "The list of buckets can't be deleted",
+            )
+            .with_path(path)),
+            // Emptying a whole bucket is one click too far from a panel
+            Place::Bucket(_) => Err(FsError::new(
+                ErrorCode::ProtectedRoot,
+                "Buckets can't be deleted from here; delete the files inside instead",
+            )
+            .with_path(path)),
+            Place::Object { bucket, key } => {
+                let deleted = self
+                    .client
+                    .delete_prefix(&bucket, &format!("{}/", key))
+                    .map_err(|f| self.fail(f, path))?;
+                if deleted == 0 {
+                    return Err(FsError::io(&io::Error::from(ErrorKind::NotFound), path));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), FsError> {
+        let (Place::Object { bucket, key }, Place::Object { bucket: to_bucket, key: to_key }) = (place(from), place(to))
+        else {
+            return Err(FsError::new(ErrorCode::Unsupported, "Buckets can't be renamed").with_path(from));
+        };
+        if self.metadata(from)?.is_dir {
+            self.copy_prefix(&bucket, &key, &to_bucket, &to_key, from)?;
+            self.client
+                .delete_prefix(&bucket, &format!("{}/", key))
+                .map_err(|f| self.fail(f, from))?;
+        } else {
+            self.copy_file(from, to)?;
+            self.client.delete(&bucket, &key).map_err(|f| self.fail(f, from))?;
+        }
+        Ok(())
+    }
+
+    fn open_read(&self, path: &Path) -> Result<Box<dyn Read + Send>, FsError> {
+        let Place::Object { bucket, key } = place(path) else {
+            return Err(is_a_folder(path));
+        };
+        let response = self
+            .client
+            .send("GET", &object_uri(&bucket, &key), &[], &[], &[])
+            .map_err(|f| self.fail(f, path))?;
+        Ok(response.into_reader())
+    }
+
+    fn open_write(&self, path: &Path) -> Result<Box<dyn Write + Send>, FsError> {
+        let Place::Object { bucket, key } = place(path) else {
+            return Err(is_a_folder(path));
+        };
+        Ok(Box::new(Upload {
+            client: Arc::clone(&self.client),
+            bucket,
+            key,
+            buffer: Vec::new(),
+            upload_id: None,
+            etags: Vec::new(),
+            finished: false,
+        }))
+    }
+
+    /// Server-side copy, so the data never passes through this machine
+    fn copy_file(&self, from: &Path, to: &Path) -> Result<u64, FsError> {
+        let (Place::Object { bucket, key }, Place::Object { bucket: to_bucket, key: to_key }) = (place(from), place(to))
+        else {
+            return Err(is_a_folder(from));
+        };
+        let len = self.client.head(&bucket, &key).map_err(|f| self.fail(f, from))?.len;
+        if len > MAX_COPY_SIZE {
+            return self.stream_copy(from, to);
+        }
+        self.client
+            .copy(&bucket, &key, &to_bucket, &to_key)
+            .map_err(|f| self.fail(f, to))?;
+        Ok(len)
+    }
+}
+
+impl RemoteFs for S3Fs {
+    fn url(&self, path: &Path) -> String {
+        self.client.target.url(path)
+    }
+}
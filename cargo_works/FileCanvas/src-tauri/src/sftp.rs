@@ -0,0 +1,432 @@
+// src-tauri/src/sftp.rs
+//
+// `FileSystem` over SFTP, so a panel can show `sftp://user@host/path` and
+// connections can copy or move between the local disk and a server.
+//
+// Host keys are checked before authenticating: keys in the user's
+// `~/.ssh/known_hosts` and in the app's own known hosts file are accepted,
+// a changed key is refused (`host_key_changed`), and an unknown one fails
+// with `unknown_host` and its SHA256 fingerprint in `detail`. Passing that
+// fingerprint back as `trust` saves the key to the app's file and connects.
+//
+// Paths in URLs are taken literally (no percent-decoding), so whatever a
+// listing returns can be handed back unchanged.
+
+use crate::error::{ErrorCode, FsError};
+use crate::vfs::{url_path, DirEntry, FileSystem, Metadata, RemoteFs};
+use base64::Engine;
+use ssh2::{
+    CheckResult, ErrorCode as SshCode, FileStat, HashType, KnownHostFileKind, OpenFlags, OpenType,
+    Session, Sftp,
+};
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+pub const SCHEME: &str = "sftp://";
+const DEFAULT_PORT: u16 = 22;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Per-request timeout once connected
+const IO_TIMEOUT_MS: u32 = 30_000;
+
+// libssh2 SFTP status codes (LIBSSH2_FX_*)
+const FX_NO_SUCH_FILE: i32 = 2;
+const FX_PERMISSION_DENIED: i32 = 3;
+const FX_OP_UNSUPPORTED: i32 = 8;
+const FX_NO_SUCH_PATH: i32 = 10;
+const FX_FILE_ALREADY_EXISTS: i32 = 11;
+const FX_WRITE_PROTECT: i32 = 12;
+const FX_NO_SPACE_ON_FILESYSTEM: i32 = 14;
+const FX_QUOTA_EXCEEDED: i32 = 15;
+const FX_DIR_NOT_EMPTY: i32 = 18;
+const FX_NOT_A_DIRECTORY: i32 = 19;
+const FX_INVALID_FILENAME: i32 = 20;
+
+// --- Data Structures ---
+
+/// Who to connect to; the part of an `sftp://` URL before the path
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SftpTarget {
+    pub user: String,
+    pub host: String,
+    pub port: u16,
+}
+
+pub enum Auth {
+    Password(String),
+    KeyFile {
+        path: PathBuf,
+        passphrase: Option<String>,
+    },
+    /// Keys offered by a running ssh-agent
+    Agent,
+}
+
+/// Where trusted host keys are saved, and a fingerprint the user just
+/// confirmed for a host that isn't in it yet
+pub struct HostKeys<'a> {
+    pub file: &'a Path,
+    pub trust: Option<&'a str>,
+}
+
+pub struct SftpFs {
+    target: SftpTarget,
+    // Keeps the connection open for `sftp`
+    _session: Session,
+    sftp: Sftp,
+    broken: AtomicBool,
+}
+
+// --- Helpers ---
+
+impl SftpTarget {
+    /// `user@host:port`, used as the key for sessions and stored credentials
+    pub fn key(&self) -> String {
+        format!("{}@{}:{}", self.user, self.host, self.port)
+    }
+
+    /// URL for `path` on this server
+    pub fn url(&self, path: &Path) -> String {
+        let host = if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        };
+        let port = if self.port == DEFAULT_PORT {
+            String::new()
+        } else {
+            format!(":{}", self.port)
+        };
+        format!("{}{}@{}{}{}", SCHEME, self.user, host, port, path.to_string_lossy())
+    }
+
+    /// Name in known_hosts format: `host`, or `[host]:port` off port 22
+    fn known_hosts_name(&self) -> String {
+        if self.port == DEFAULT_PORT {
+            self.host.clone()
+        } else {
+            format!("[{}]:{}", self.host, self.port)
+        }
+    }
+}
+
+pub fn is_url(path: &str) -> bool {
+    path.starts_with(SCHEME)
+}
+
+/// Split `sftp://user@host[:port][/path]` into target and absolute path
+pub fn parse_url(url: &str) -> Result<(SftpTarget, PathBuf), FsError> {
+    let invalid = || FsError::new(ErrorCode::InvalidPath, format!("Not an sftp://user@host/path URL: {}", url)).with_path(url);
+    let rest = url.strip_prefix(SCHEME).ok_or_else(invalid)?;
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let (user, host_port) = authority.rsplit_once('@').ok_or_else(invalid)?;
+    let (host, port) = if let Some(v6) = host_port.strip_prefix('[') {
+        let (host, after) = v6.split_once(']').ok_or_else(invalid)?;
+        (host, after.strip_prefix(':'))
+    } else {
+        match host_port.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (host_port, None),
+        }
+    };
+    let port = match port {
+        Some(p) => p.parse().map_err(|_| invalid())?,
+        None => DEFAULT_PORT,
+    };
+    if user.is_empty() || host.is_empty() {
+        return Err(invalid());
+    }
+    Ok((
+        SftpTarget {
+            user: user.to_string(),
+            host: host.to_string(),
+            port,
+        },
+        url_path(path, url)?,
+    ))
+}
+
+fn kind_for(code: SshCode) -> ErrorKind {
+    match code {
+        SshCode::SFTP(FX_NO_SUCH_FILE) | SshCode::SFTP(FX_NO_SUCH_PATH) => ErrorKind::NotFound,
+        SshCode::SFTP(FX_PERMISSION_DENIED) => ErrorKind::PermissionDenied,
+        SshCode::SFTP(FX_FILE_ALREADY_EXISTS) => ErrorKind::AlreadyExists,
+        SshCode::SFTP(FX_WRITE_PROTECT) => ErrorKind::ReadOnlyFilesystem,
+        SshCode::SFTP(FX_NO_SPACE_ON_FILESYSTEM) => ErrorKind::StorageFull,
+        SshCode::SFTP(FX_QUOTA_EXCEEDED) => ErrorKind::QuotaExceeded,
+        SshCode::SFTP(FX_DIR_NOT_EMPTY) => ErrorKind::DirectoryNotEmpty,
+        SshCode::SFTP(FX_NOT_A_DIRECTORY) => ErrorKind::NotADirectory,
+        SshCode::SFTP(FX_INVALID_FILENAME) => ErrorKind::InvalidFilename,
+        SshCode::SFTP(FX_OP_UNSUPPORTED) => ErrorKind::Unsupported,
+        SshCode::SFTP(_) => ErrorKind::Other,
+        // Anything below SFTP is the connection itself
+        SshCode::Session(_) => ErrorKind::ConnectionAborted,
+    }
+}
+
+fn ssh_error(err: &ssh2::Error, path: &Path) -> FsError {
+    FsError::io(&io::Error::new(kind_for(err.code()), err.message().to_string()), path)
+}
+
+fn connect_error(target: &SftpTarget, err: impl std::fmt::Display) -> FsError {
+    FsError::new(
+        ErrorCode::ConnectionFailed,
+        format!("Can't connect to {}: {}", target.key(), err),
+    )
+    .with_path(target.url(Path::new("/")))
+}
+
+fn stat_metadata(stat: &FileStat) -> Metadata {
+    let mtime = stat.mtime.unwrap_or(0) * 1000;
+    Metadata {
+        is_dir: stat.is_dir(),
+        is_symlink: stat.file_type().is_symlink(),
+        len: stat.size.unwrap_or(0),
+        mtime,
+        // SFTP v3 has no creation time
+        ctime: mtime,
+    }
+}
+
+/// `SHA256:…` as printed by `ssh-keygen -l`
+fn fingerprint(session: &Session) -> Option<String> {
+    let hash = session.host_key_hash(HashType::Sha256)?;
+    Some(format!("SHA256:{}", base64::engine::general_purpose::STANDARD_NO_PAD.encode(hash)))
+}
+
+fn check_host_key(session: &Session, target: &SftpTarget, keys: &HostKeys) -> Result<(), FsError> {
+    let (key, key_type) = session
+        .host_key()
+        .ok_or_else(|| connect_error(target, "the server sent no host key"))?;
+    let fingerprint = fingerprint(session).unwrap_or_default();
+    let mut known = session.known_hosts().map_err(|e| connect_error(target, e))?;
+    if let Some(home) = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")) {
+        let _ = known.read_file(&Path::new(&home).join(".ssh/known_hosts"), KnownHostFileKind::OpenSSH);
+    }
+    let _ = known.read_file(keys.file, KnownHostFileKind::OpenSSH);
+
+    match known.check_port(&target.host, target.port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::Mismatch => Err(FsError::new(
+            ErrorCode::HostKeyChanged,
+            format!(
+                "The host key of {} has changed (now {}). Someone may be intercepting the connection.",
+                target.host, fingerprint
+            ),
+        )
+        .with_path(target.url(Path::new("/")))
+        .with_detail(fingerprint)),
+        CheckResult::NotFound | CheckResult::Failure if keys.trust == Some(fingerprint.as_str()) => {
+            // Only the app's own file is rewritten, never ~/.ssh/known_hosts
+            let mut saved = session.known_hosts().map_err(|e| connect_error(target, e))?;
+            let _ = saved.read_file(keys.file, KnownHostFileKind::OpenSSH);
+            saved
+                .add(&target.known_hosts_name(), key, "added by FileCanvas", key_type.into())
+                .and_then(|_| saved.write_file(keys.file, KnownHostFileKind::OpenSSH))
+                .map_err(|e| connect_error(target, e))
+        }
+        CheckResult::NotFound | CheckResult::Failure => Err(FsError::new(
+            ErrorCode::UnknownHost,
+            format!("{} is not a known host. Its key fingerprint is {}.", target.host, fingerprint),
+        )
+        .with_path(target.url(Path::new("/")))
+        .with_detail(fingerprint)),
+    }
+}
+
+// --- Connecting ---
+
+impl SftpFs {
+    pub fn connect(target: &SftpTarget, auth: &Auth, keys: &HostKeys) -> Result<SftpFs, FsError> {
+        let addrs: Vec<_> = (target.host.as_str(), target.port)
+            .to_socket_addrs()
+            .map_err(|e| connect_error(target, e))?
+            .collect();
+        let mut last_error = None;
+        let tcp = addrs
+            .iter()
+            .find_map(|addr| {
+                TcpStream::connect_timeout(addr, CONNECT_TIMEOUT)
+                    .map_err(|e| last_error = Some(e))
+                    .ok()
+            })
+            .ok_or_else(|| match last_error {
+                Some(e) => connect_error(target, e),
+                None => connect_error(target, "no address found"),
+            })?;
+
+        let mut session = Session::new().map_err(|e| connect_error(target, e))?;
+        session.set_tcp_stream(tcp);
+        session.set_timeout(IO_TIMEOUT_MS);
+        session.handshake().map_err(|e| connect_error(target, e))?;
+        check_host_key(&session, target, keys)?;
+
+        let authenticated = match auth {
+            Auth::Password(password) => session.userauth_password(&target.user, password),
+            Auth::KeyFile { path, passphrase } => {
+                session.userauth_pubkey_file(&target.user, None, path, passphrase.as_deref())
+            }
+            Auth::Agent => session.userauth_agent(&target.user),
+        };
+        let refused = match authenticated {
+            Err(e) => Some(e.message().to_string()),
+            Ok(()) if !session.authenticated() => Some("authentication incomplete".to_string()),
+            Ok(()) => None,
+        };
+        if let Some(reason) = refused {
+            return Err(FsError::new(
+                ErrorCode::AuthFailed,
+                format!("{} rejected the login: {}", target.key(), reason),
+            )
+            .with_path(target.url(Path::new("/"))));
+        }
+
+        let sftp = session.sftp().map_err(|e| connect_error(target, e))?;
+        Ok(SftpFs {
+            target: target.clone(),
+            _session: session,
+            sftp,
+            broken: AtomicBool::new(false),
+        })
+    }
+
+    pub fn target(&self) -> &SftpTarget {
+        &self.target
+    }
+
+    /// True once a request failed below the SFTP layer; the session should
+    /// be replaced by a new connection
+    pub fn is_broken(&self) -> bool {
+        self.broken.load(Ordering::Relaxed)
+    }
+
+    /// Convert an ssh2 error about `path`, noting a dead connection
+    fn error(&self, err: ssh2::Error, path: &Path) -> FsError {
+        if matches!(err.code(), SshCode::Session(_)) {
+            self.broken.store(true, Ordering::Relaxed);
+        }
+        ssh_error(&err, path)
+    }
+
+    fn remove_tree(&self, path: &Path) -> Result<(), FsError> {
+        for (child, stat) in self.sftp.readdir(path).map_err(|e| self.error(e, path))? {
+            if stat.is_dir() {
+                self.remove_tree(&child)?;
+            } else {
+                self.sftp.unlink(&child).map_err(|e| self.error(e, &child))?;
+            }
+        }
+        self.sftp.rmdir(path).map_err(|e| self.error(e, path))
+    }
+}
+
+impl FileSystem for SftpFs {
+    fn metadata(&self, path: &Path) -> Result<Metadata, FsError> {
+        let stat = self.sftp.stat(path).map_err(|e| self.error(e, path))?;
+        Ok(stat_metadata(&stat))
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> Result<Metadata, FsError> {
+        let stat = self.sftp.lstat(path).map_err(|e| self.error(e, path))?;
+        Ok(stat_metadata(&stat))
+    }
+
+    fn read_dir(&self, path: &Path) -> Result<Vec<DirEntry>, FsError> {
+        let entries = self.sftp.readdir(path).map_err(|e| self.error(e, path))?;
+        Ok(entries
+            .into_iter()
+            .filter_map(|(child, stat)| {
+                // readdir doesn't follow symlinks; stat them like DiskFs does
+                let stat = if stat.file_type().is_symlink() {
+                    self.sftp.stat(&child).ok()?
+                } else {
+                    stat
+                };
+                Some(DirEntry {
+                    name: child.file_name()?.to_string_lossy().to_string(),
+                    metadata: stat_metadata(&stat),
+                    path: child,
+                })
+            })
+            .collect())
+    }
+
+    fn create_dir(&self, path: &Path) -> Result<(), FsError> {
+        self.sftp.mkdir(path, 0o755).map_err(|e| self.error(e, path))
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<(), FsError> {
+        match self.sftp.stat(path) {
+            Ok(stat) if stat.is_dir() => return Ok(()),
+            Ok(_) => return Err(FsError::io(&io::Error::from(ErrorKind::NotADirectory), path)),
+            Err(_) => {}
+        }
+        if let Some(parent) = path.parent() {
+            self.create_dir_all(parent)?;
+        }
+        self.create_dir(path)
+    }
+
+    fn create_file(&self, path: &Path) -> Result<(), FsError> {
+        let flags = OpenFlags::WRITE | OpenFlags::CREATE | OpenFlags::EXCLUSIVE;
+        self.sftp
+            .open_mode(path, flags, 0o644, OpenType::File)
+            .map(|_| ())
+            .map_err(|e| self.error(e, path))
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), FsError> {
+        self.sftp.unlink(path).map_err(|e| self.error(e, path))
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> Result<(), FsError> {
+        self.remove_tree(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), FsError> {
+        self.sftp.rename(from, to, None).map_err(|e| self.error(e, from))
+    }
+
+    fn open_read(&self, path: &Path) -> Result<Box<dyn Read + Send>, FsError> {
+        let file = self.sftp.open(path).map_err(|e| self.error(e, path))?;
+        Ok(Box::new(file))
+    }
+
+    fn open_write(&self, path: &Path) -> Result<Box<dyn Write + Send>, FsError> {
+        let flags = OpenFlags::WRITE | OpenFlags::CREATE | OpenFlags::TRUNCATE;
+        let file = self
+            .sftp
+            .open_mode(path, flags, 0o644, OpenType::File)
+            .map_err(|e| self.error(e, path))?;
+        Ok(Box::new(file))
+    }
+
+    fn set_mtime(&self, path: &Path, mtime: u64) -> Result<(), FsError> {
+        // SFTP sets access and modification time together
+        let secs = mtime / 1000;
+        let stat = FileStat {
+            size: None,
+            uid: None,
+            gid: None,
+            perm: None,
+            atime: Some(secs),
+            mtime: Some(secs),
+        };
+        self.sftp.setstat(path, stat).map_err(|e| self.error(e, path))
+    }
+}
+
+impl RemoteFs for SftpFs {
+    fn url(&self, path: &Path) -> String {
+        self.target.url(path)
+    }
+
+    fn is_broken(&self) -> bool {
+        SftpFs::is_broken(self)
+    }
+}
@@ -0,0 +1,167 @@
+// src-tauri/src/space.rs
+//
+// Free space on the local volumes a transfer writes to. Planning asks here
+// before anything is written, so a copy to a nearly full drive is refused up
+// front instead of failing at 90% with half a tree left behind. A move within
+// one volume is a rename and needs no space at all.
+//
+// Space is read with statvfs and counts what an unprivileged user may still
+// write (blocks reserved for root are left out). Where it can't be read
+// (Windows, remote folders) checks are skipped rather than guessed.
+
+use crate::error::{ErrorCode, FsError};
+use serde::Serialize;
+use std::io;
+use std::path::Path;
+
+/// Below this share of the volume left free afterwards, a transfer that
+/// fits is still worth a warning
+pub const LOW_SPACE_PERCENT: u64 = 5;
+
+// --- Data Structures ---
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub struct VolumeSpace {
+    /// Bytes the current user can still write
+    pub free: u64,
+    pub total: u64,
+}
+
+/// Whether `needed` bytes fit on the volume of `path`
+#[derive(Debug, Clone, Serialize)]
+pub struct SpaceCheck {
+    /// The folder written to
+    pub path: String,
+    pub needed: u64,
+    pub free: u64,
+    pub total: u64,
+    /// `needed` fits into `free`
+    pub enough: bool,
+    /// It fits but leaves less than `LOW_SPACE_PERCENT` of the volume free
+    pub low: bool,
+}
+
+// --- Helpers ---
+
+/// 1.5 GB, 230 MB, 12 KB, … for messages
+pub fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["bytes", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// `path`, or its closest ancestor that exists (a destination that's only
+/// created by the transfer lives on its parent's volume)
+fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|p| p.exists())
+}
+
+#[cfg(unix)]
+fn statvfs(path: &Path) -> io::Result<VolumeSpace> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).map_err(io::Error::other)?;
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `c_path` is NUL-terminated and `stats` is a valid out pointer
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stats) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let block = stats.f_frsize as u64;
+    Ok(VolumeSpace {
+        free: stats.f_bavail as u64 * block,
+        total: stats.f_blocks as u64 * block,
+    })
+}
+
+#[cfg(not(unix))]
+fn statvfs(_path: &Path) -> io::Result<VolumeSpace> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// Identifies the volume `path` is on
+#[cfg(unix)]
+fn device(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(existing_ancestor(path)?).ok().map(|m| m.dev())
+}
+
+#[cfg(not(unix))]
+fn device(_path: &Path) -> Option<u64> {
+    None
+}
+
+// --- Queries ---
+
+/// Free and total space of the volume holding `path`
+pub fn volume_space(path: &Path) -> io::Result<VolumeSpace> {
+    let existing = existing_ancestor(path).ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+    statvfs(existing)
+}
+
+/// The volume `path` is on, for grouping transfers; `None` when unknown
+pub fn volume_id(path: &Path) -> Option<u64> {
+    device(path)
+}
+
+/// Both paths are known to be on the same volume
+pub fn same_volume(a: &Path, b: &Path) -> bool {
+    matches!((device(a), device(b)), (Some(a), Some(b)) if a == b)
+}
+
+/// Bytes a transfer of `size` bytes from `src` into `dest_folder` takes on
+/// the destination: none for a move within one volume
+pub fn bytes_needed(src: &Path, dest_folder: &Path, size: u64, moving: bool) -> u64 {
+    if moving && same_volume(src, dest_folder) {
+        0
+    } else {
+        size
+    }
+}
+
+/// Compare `needed` with the space at `dest`. `None` when the free space
+/// can't be read.
+pub fn check(dest: &Path, needed: u64) -> Option<SpaceCheck> {
+    let space = volume_space(dest).ok()?;
+    let left = space.free.saturating_sub(needed);
+    Some(SpaceCheck {
+        path: dest.to_string_lossy().to_string(),
+        needed,
+        free: space.free,
+        total: space.total,
+        enough: needed <= space.free,
+        low: needed > 0 && left.saturating_mul(100) < space.total.saturating_mul(LOW_SPACE_PERCENT),
+    })
+}
+
+/// Refuse with `no_space` when `needed` bytes don't fit at `dest`
+pub fn ensure(dest: &Path, needed: u64) -> Result<(), FsError> {
+    match check(dest, needed) {
+        Some(check) if !check.enough => Err(check.error()),
+        _ => Ok(()),
+    }
+}
+
+impl SpaceCheck {
+    pub fn error(&self) -> FsError {
+        FsError::new(
+            ErrorCode::NoSpace,
+            format!(
+                "Not enough space in {}: {} needed, {} free",
+                self.path,
+                human_bytes(self.needed),
+                human_bytes(self.free)
+            ),
+        )
+        .with_path(&self.path)
+    }
+}
@@ -0,0 +1,26 @@
+// src-tauri/src/store.rs
+//
+// The JSON files the app keeps in its data folder (rules, settings, run logs,
+// sync state) are all written the same way, and the times in them are all
+// milliseconds since the epoch.
+
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// Write `value` to `path` as pretty JSON. It goes to a temp file first and
+/// is renamed over the old one, so a crash never leaves half a file behind.
+pub fn save_json_atomic<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+/// Milliseconds since the epoch, as stored in those files
+pub fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
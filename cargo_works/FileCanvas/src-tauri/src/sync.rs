@@ -0,0 +1,887 @@
+use crate::activity::{ActivityEntry, ActivityLog, Operation, Via};
+use crate::error::{ErrorCode, FsError};
+use crate::filters::{CompiledFilter, FilterRules};
+use crate::ops;
+use crate::remote::{self, Location};
+use crate::store;
+use filecanvas_lib::space::{self, SpaceCheck};
+use filecanvas_lib::vfs::FileSystem;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+use tauri::async_runtime::spawn_blocking;
+use tauri::{Manager, Runtime};
+
+// FAT/exFAT only store modification times with 2 second precision, so two
+// timestamps closer than this are treated as equal.
+const MTIME_TOLERANCE_MS: u64 = 2000;
+
+const STATE_FILE: &str = "sync_state.json";
+
+// --- Data Structures ---
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncMode {
+    /// Make the destination look like the source.
+    Mirror,
+    /// Propagate changes in both directions using the stored state.
+    TwoWay,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncOptions {
+    /// Mirror only: remove destination files that no longer exist in the source.
+    pub delete_extras: bool,
+    /// Compare file contents when size matches but mtime differs.
+    pub use_hash: bool,
+    /// Files filtered out are ignored on both sides: never copied, never deleted.
+    pub filters: FilterRules,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EntryState {
+    pub size: u64,
+    pub mtime: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncActionKind {
+    CreateDir,
+    /// Two-way only: a folder that's new in the destination
+    CreateDirInSource,
+    CopyToDest,
+    CopyToSource,
+    DeleteFromDest,
+    DeleteFromSource,
+    Conflict,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncAction {
+    pub kind: SyncActionKind,
+    /// Path relative to the synced folders, always `/` separated.
+    pub path: String,
+    pub is_directory: bool,
+    pub size: u64,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncPlan {
+    pub mode: SyncMode,
+    pub source: String,
+    pub destination: String,
+    pub actions: Vec<SyncAction>,
+    pub conflicts: Vec<SyncAction>,
+    pub unchanged: usize,
+    pub bytes_to_transfer: u64,
+    /// Room on each local side that receives files
+    pub space: Vec<SpaceCheck>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncReport {
+    pub destination: String,
+    pub copied: usize,
+    pub deleted: usize,
+    pub created_dirs: usize,
+    pub bytes_transferred: u64,
+    pub conflicts: Vec<SyncAction>,
+    pub errors: Vec<String>,
+}
+
+/// Last known state of every synced pair, keyed by `source => destination`
+/// (paths, or URLs for folders on a server).
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StateDb {
+    pairs: HashMap<String, BTreeMap<String, EntryState>>,
+    /// Folders that existed on both sides after the last run, per pair
+    #[serde(default)]
+    dirs: HashMap<String, BTreeSet<String>>,
+}
+
+struct Snapshot {
+    files: BTreeMap<String, EntryState>,
+    dirs: BTreeSet<String>,
+}
+
+// --- Helpers ---
+
+/// The path of a sync root, or its URL when it's on a server
+fn root_url(root: &Location) -> String {
+    root.url(root.path())
+}
+
+fn pair_key(src: &Location, dest: &Location) -> String {
+    format!("{} => {}", root_url(src), root_url(dest))
+}
+
+fn state_path<R: Runtime>(app_handle: &tauri::AppHandle<R>) -> Result<PathBuf, String> {
+    let dir = app_handle.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(STATE_FILE))
+}
+
+fn load_state(path: &Path) -> StateDb {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn join_key(root: &Path, key: &str) -> PathBuf {
+    key.split('/').fold(root.to_path_buf(), |p, part| p.join(part))
+}
+
+fn scan(root: &Location, filter: &CompiledFilter) -> Snapshot {
+    let mut snapshot = Snapshot {
+        files: BTreeMap::new(),
+        dirs: BTreeSet::new(),
+    };
+    if root.fs().metadata(root.path()).is_ok_and(|m| m.is_dir) {
+        let skip_links = matches!(root, Location::Local(_));
+        scan_dir(root.fs(), root.path(), "", skip_links, filter, &mut snapshot);
+    }
+    snapshot
+}
+
+/// Adds everything below `dir` to `snapshot`, keyed relative to the root
+/// (`prefix` is the key of `dir` itself). Symbolic links on the local disk
+/// are skipped rather than followed.
+fn scan_dir(
+    fs: &dyn FileSystem,
+    dir: &Path,
+    prefix: &str,
+    skip_links: bool,
+    filter: &CompiledFilter,
+    snapshot: &mut Snapshot,
+) {
+    let Ok(entries) = fs.read_dir(dir) else {
+        return;
+    };
+    for entry in entries {
+        let key = if prefix.is_empty() {
+            entry.name.clone()
+        } else {
+            format!("{}/{}", prefix, entry.name)
+        };
+        let metadata = &entry.metadata;
+        if metadata.is_symlink || (skip_links && fs.symlink_metadata(&entry.path).map_or(true, |m| m.is_symlink)) {
+            continue;
+        }
+        if metadata.is_dir {
+            if filter.check_dir(&entry.name, &key).is_some() {
+                continue;
+            }
+            scan_dir(fs, &entry.path, &key, skip_links, filter, snapshot);
+            snapshot.dirs.insert(key);
+        } else {
+            let modified = (metadata.mtime > 0).then(|| UNIX_EPOCH + Duration::from_millis(metadata.mtime));
+            if filter.check_entry(&entry.name, &key, metadata.len, modified).is_none() {
+                snapshot.files.insert(
+                    key,
+                    EntryState {
+                        size: metadata.len,
+                        mtime: metadata.mtime,
+                    },
+                );
+            }
+        }
+    }
+}
+
+fn hash_file(fs: &dyn FileSystem, path: &Path) -> Option<Vec<u8>> {
+    let mut file = fs.open_read(path).ok()?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).ok()?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Some(hasher.finalize().to_vec())
+}
+
+fn same_state(a: &EntryState, b: &EntryState) -> bool {
+    a.size == b.size && a.mtime.abs_diff(b.mtime) <= MTIME_TOLERANCE_MS
+}
+
+/// Compares a file on both sides by size and mtime, falling back to the
+/// content hash when requested and only the timestamps disagree.
+fn same_file(a: &EntryState, b: &EntryState, src_root: &Location, dest_root: &Location, key: &str, use_hash: bool) -> bool {
+    if same_state(a, b) {
+        return true;
+    }
+    if use_hash && a.size == b.size {
+        let src_hash = hash_file(src_root.fs(), &join_key(src_root.path(), key));
+        let dest_hash = hash_file(dest_root.fs(), &join_key(dest_root.path(), key));
+        return matches!((src_hash, dest_hash), (Some(x), Some(y)) if x == y);
+    }
+    false
+}
+
+fn action(kind: SyncActionKind, path: &str, is_directory: bool, size: u64, reason: Option<&str>) -> SyncAction {
+    SyncAction {
+        kind,
+        path: path.to_string(),
+        is_directory,
+        size,
+        reason: reason.map(|r| r.to_string()),
+    }
+}
+
+/// Resolves a connection (source item → destination folder) to the two roots
+/// that are kept in sync, mirroring how `copy_folder` names the target.
+/// Either side may be a folder on a server.
+fn resolve_roots(src: Location, dest_folder: Location) -> Result<(Location, Location), String> {
+    let is_dir = |location: &Location| location.fs().metadata(location.path()).is_ok_and(|m| m.is_dir);
+    if !is_dir(&src) {
+        return Err("Sync requires a folder as the source".into());
+    }
+    if !is_dir(&dest_folder) {
+        return Err("Destination is not a folder".into());
+    }
+    let folder_name = src.path().file_name().ok_or("Invalid source path")?;
+    let dest_root = dest_folder.with_path(dest_folder.path().join(folder_name));
+    if src.same_fs(&dest_root) && dest_root.path().starts_with(src.path()) {
+        return Err("Cannot sync a folder into its own subfolder".into());
+    }
+    Ok((src, dest_root))
+}
+
+// --- Planning ---
+
+fn plan_mirror(
+    src_root: &Location,
+    dest_root: &Location,
+    options: &SyncOptions,
+    filter: &CompiledFilter,
+) -> (Vec<SyncAction>, usize) {
+    let src = scan(src_root, filter);
+    let dest = scan(dest_root, filter);
+    let mut actions = Vec::new();
+    let mut unchanged = 0;
+
+    for dir in &src.dirs {
+        if !dest.dirs.contains(dir) {
+            actions.push(action(SyncActionKind::CreateDir, dir, true, 0, None));
+        }
+    }
+
+    for (key, s) in &src.files {
+        match dest.files.get(key) {
+            Some(d) if same_file(s, d, src_root, dest_root, key, options.use_hash) => {
+                unchanged += 1;
+            }
+            Some(_) => actions.push(action(SyncActionKind::CopyToDest, key, false, s.size, Some("changed"))),
+            None => actions.push(action(SyncActionKind::CopyToDest, key, false, s.size, Some("new"))),
+        }
+    }
+
+    if options.delete_extras {
+        for (key, d) in &dest.files {
+            if !src.files.contains_key(key) {
+                actions.push(action(SyncActionKind::DeleteFromDest, key, false, d.size, Some("not in source")));
+            }
+        }
+        // Only the top-most extra folder needs removing; its children go with it.
+        let extra_dirs: Vec<&String> = dest.dirs.iter().filter(|d| !src.dirs.contains(*d)).collect();
+        for dir in &extra_dirs {
+            let nested = extra_dirs
+                .iter()
+                .any(|other| other != dir && dir.starts_with(&format!("{}/", other)));
+            if !nested {
+                actions.push(action(SyncActionKind::DeleteFromDest, dir, true, 0, Some("not in source")));
+            }
+        }
+    }
+
+    (actions, unchanged)
+}
+
+fn plan_two_way(
+    src_root: &Location,
+    dest_root: &Location,
+    prev: &BTreeMap<String, EntryState>,
+    prev_dirs: &BTreeSet<String>,
+    options: &SyncOptions,
+    filter: &CompiledFilter,
+) -> (Vec<SyncAction>, Vec<SyncAction>, usize) {
+    let src = scan(src_root, filter);
+    let dest = scan(dest_root, filter);
+    let mut actions = Vec::new();
+    let mut conflicts = Vec::new();
+    let mut unchanged = 0;
+
+    let keys: BTreeSet<&String> = src.files.keys().chain(dest.files.keys()).chain(prev.keys()).collect();
+
+    for key in keys {
+        let s = src.files.get(key);
+        let d = dest.files.get(key);
+        let p = prev.get(key);
+
+        let changed = |cur: Option<&EntryState>| match (cur, p) {
+            (Some(c), Some(p)) => !same_state(c, p),
+            (None, None) => false,
+            _ => true,
+        };
+        let src_changed = changed(s);
+        let dest_changed = changed(d);
+
+        // Without an earlier state the timestamps say nothing about which
+        // side changed, so matching sizes are settled by content
+        let use_hash = options.use_hash || p.is_none();
+
+        match (s, d) {
+            (Some(a), Some(b)) if same_file(a, b, src_root, dest_root, key, use_hash) => {
+                unchanged += 1;
+            }
+            (None, None) => {}
+            _ if !src_changed && !dest_changed => unchanged += 1,
+            (Some(a), _) if src_changed && !dest_changed => {
+                actions.push(action(SyncActionKind::CopyToDest, key, false, a.size, None));
+            }
+            (_, Some(b)) if dest_changed && !src_changed => {
+                actions.push(action(SyncActionKind::CopyToSource, key, false, b.size, None));
+            }
+            (None, Some(b)) if src_changed && !dest_changed => {
+                actions.push(action(SyncActionKind::DeleteFromDest, key, false, b.size, Some("deleted in source")));
+            }
+            (Some(a), None) if dest_changed && !src_changed => {
+                actions.push(action(SyncActionKind::DeleteFromSource, key, false, a.size, Some("deleted in destination")));
+            }
+            (Some(a), Some(_)) => {
+                conflicts.push(action(SyncActionKind::Conflict, key, false, a.size, Some("modified on both sides")));
+            }
+            (Some(a), None) => {
+                conflicts.push(action(SyncActionKind::Conflict, key, false, a.size, Some("modified in source, deleted in destination")));
+            }
+            (None, Some(b)) => {
+                conflicts.push(action(SyncActionKind::Conflict, key, false, b.size, Some("deleted in source, modified in destination")));
+            }
+        }
+    }
+
+    let dir_actions = plan_two_way_dirs(&src, &dest, prev_dirs, &actions);
+    actions.extend(dir_actions);
+    (actions, conflicts, unchanged)
+}
+
+/// Folders only on one side: created on the other when they're new, removed
+/// when the other side deleted them. A deleted folder is only removed if
+/// everything in it goes too; otherwise it's created again so nothing new
+/// or changed inside is lost.
+fn plan_two_way_dirs(
+    src: &Snapshot,
+    dest: &Snapshot,
+    prev_dirs: &BTreeSet<String>,
+    file_actions: &[SyncAction],
+) -> Vec<SyncAction> {
+    let deleted_files = |kind: SyncActionKind| -> BTreeSet<&str> {
+        file_actions.iter().filter(|a| a.kind == kind).map(|a| a.path.as_str()).collect()
+    };
+    let sides = [
+        (src, dest, SyncActionKind::CreateDir, SyncActionKind::DeleteFromSource),
+        (dest, src, SyncActionKind::CreateDirInSource, SyncActionKind::DeleteFromDest),
+    ];
+
+    let mut actions = Vec::new();
+    for (here, there, create, delete) in sides {
+        let deleted = deleted_files(delete);
+        let only_here: Vec<&String> = here.dirs.iter().filter(|d| !there.dirs.contains(*d)).collect();
+        let removable = |dir: &str| {
+            let inside = format!("{}/", dir);
+            prev_dirs.contains(dir)
+                && here
+                    .files
+                    .keys()
+                    .filter(|k| k.starts_with(&inside))
+                    .all(|k| deleted.contains(k.as_str()))
+                && here
+                    .dirs
+                    .iter()
+                    .filter(|d| d.starts_with(&inside))
+                    .all(|d| prev_dirs.contains(d))
+        };
+        for dir in &only_here {
+            if !removable(dir) {
+                actions.push(action(create, dir, true, 0, None));
+                continue;
+            }
+            // Only the top-most removed folder needs deleting
+            let nested = only_here
+                .iter()
+                .any(|other| other != dir && dir.starts_with(&format!("{}/", other)) && removable(other));
+            if !nested {
+                let reason = if delete == SyncActionKind::DeleteFromSource {
+                    "deleted in destination"
+                } else {
+                    "deleted in source"
+                };
+                actions.push(action(delete, dir, true, 0, Some(reason)));
+            }
+        }
+    }
+    actions
+}
+
+fn build_plan(
+    src_root: &Location,
+    dest_root: &Location,
+    mode: SyncMode,
+    options: &SyncOptions,
+    state: &StateDb,
+) -> Result<SyncPlan, String> {
+    let filter = options.filters.compile()?;
+    let (actions, conflicts, unchanged) = match mode {
+        SyncMode::Mirror => {
+            let (actions, unchanged) = plan_mirror(src_root, dest_root, options, &filter);
+            (actions, Vec::new(), unchanged)
+        }
+        SyncMode::TwoWay => {
+            let key = pair_key(src_root, dest_root);
+            let (empty, no_dirs) = (BTreeMap::new(), BTreeSet::new());
+            let prev = state.pairs.get(&key).unwrap_or(&empty);
+            let prev_dirs = state.dirs.get(&key).unwrap_or(&no_dirs);
+            plan_two_way(src_root, dest_root, prev, prev_dirs, options, &filter)
+        }
+    };
+
+    let bytes_to = |kind: SyncActionKind| -> u64 { actions.iter().filter(|a| a.kind == kind).map(|a| a.size).sum() };
+    let (to_dest, to_source) = (bytes_to(SyncActionKind::CopyToDest), bytes_to(SyncActionKind::CopyToSource));
+    // Replaced files count in full; large ones are written next to the old
+    // copy before it's swapped out
+    let space = [(dest_root, to_dest), (src_root, to_source)]
+        .into_iter()
+        .filter_map(|(root, needed)| match root {
+            Location::Local(path) if needed > 0 => space::check(path, needed),
+            _ => None,
+        })
+        .collect();
+
+    Ok(SyncPlan {
+        mode,
+        source: root_url(src_root),
+        destination: root_url(dest_root),
+        actions,
+        conflicts,
+        unchanged,
+        bytes_to_transfer: to_dest + to_source,
+        space,
+    })
+}
+
+// --- Execution ---
+
+/// Copies a single file and carries the modification time over, so the next
+/// comparison by size and mtime sees both sides as equal. Servers that keep
+/// their own times get the copy as is.
+fn copy_preserving_mtime(from: &Location, from_path: &Path, to: &Location, to_path: &Path) -> Result<u64, FsError> {
+    if let Some(parent) = to_path.parent() {
+        to.fs().create_dir_all(parent)?;
+    }
+    let bytes = if from.same_fs(to) {
+        from.fs().copy_file(from_path, to_path)?
+    } else {
+        ops::stream_file(from.fs(), from_path, to.fs(), to_path)?
+    };
+    let mtime = from.fs().metadata(from_path)?.mtime;
+    match to.fs().set_mtime(to_path, mtime) {
+        Err(e) if e.code == ErrorCode::Unsupported => {}
+        result => result?,
+    }
+    Ok(bytes)
+}
+
+fn remove_path(root: &Location, path: &Path, is_directory: bool) -> Result<(), FsError> {
+    if is_directory {
+        root.fs().remove_dir_all(path)
+    } else {
+        root.fs().remove_file(path)
+    }
+}
+
+fn execute_plan(src_root: &Location, dest_root: &Location, plan: &SyncPlan, log: &ActivityLog) -> SyncReport {
+    let mut report = SyncReport {
+        destination: plan.destination.clone(),
+        copied: 0,
+        deleted: 0,
+        created_dirs: 0,
+        bytes_transferred: 0,
+        conflicts: plan.conflicts.clone(),
+        errors: Vec::new(),
+    };
+
+    if let Err(e) = dest_root.fs().create_dir_all(dest_root.path()) {
+        report.errors.push(format!("{}: {}", plan.destination, e));
+        return report;
+    }
+
+    for a in &plan.actions {
+        let src_path = join_key(src_root.path(), &a.path);
+        let dest_path = join_key(dest_root.path(), &a.path);
+
+        let result = match a.kind {
+            SyncActionKind::CreateDir => dest_root
+                .fs()
+                .create_dir_all(&dest_path)
+                .map(|_| report.created_dirs += 1),
+            SyncActionKind::CreateDirInSource => src_root
+                .fs()
+                .create_dir_all(&src_path)
+                .map(|_| report.created_dirs += 1),
+            SyncActionKind::CopyToDest => {
+                copy_preserving_mtime(src_root, &src_path, dest_root, &dest_path).map(|bytes| {
+                    report.copied += 1;
+                    report.bytes_transferred += bytes;
+                })
+            }
+            SyncActionKind::CopyToSource => {
+                copy_preserving_mtime(dest_root, &dest_path, src_root, &src_path).map(|bytes| {
+                    report.copied += 1;
+                    report.bytes_transferred += bytes;
+                })
+            }
+            SyncActionKind::DeleteFromDest => {
+                remove_path(dest_root, &dest_path, a.is_directory).map(|_| report.deleted += 1)
+            }
+            SyncActionKind::DeleteFromSource => {
+                remove_path(src_root, &src_path, a.is_directory).map(|_| report.deleted += 1)
+            }
+            SyncActionKind::Conflict => Ok(()),
+        };
+
+        let (src_url, dest_url) = (src_root.url(&src_path), dest_root.url(&dest_path));
+        let entry = match a.kind {
+            SyncActionKind::CreateDir => ActivityEntry::new(Operation::CreateFolder, Via::Sync).to(dest_url),
+            SyncActionKind::CreateDirInSource => ActivityEntry::new(Operation::CreateFolder, Via::Sync).to(src_url),
+            SyncActionKind::CopyToDest => ActivityEntry::new(Operation::Copy, Via::Sync).from(src_url).to(dest_url),
+            SyncActionKind::CopyToSource => ActivityEntry::new(Operation::Copy, Via::Sync).from(dest_url).to(src_url),
+            SyncActionKind::DeleteFromDest => ActivityEntry::new(Operation::Delete, Via::Sync).from(dest_url),
+            SyncActionKind::DeleteFromSource => ActivityEntry::new(Operation::Delete, Via::Sync).from(src_url),
+            SyncActionKind::Conflict => continue,
+        };
+        let result = log.record(entry.bytes(a.size), result);
+
+        if let Err(e) = result {
+            report.errors.push(format!("{}: {}", a.path, e));
+        }
+    }
+
+    report
+}
+
+/// Records the files that now match on both sides, and the folders both
+/// sides have. Conflicted paths keep their previous entry so they are
+/// reported again until resolved.
+fn update_state(
+    state: &mut StateDb,
+    src_root: &Location,
+    dest_root: &Location,
+    conflicts: &[SyncAction],
+    filter: &CompiledFilter,
+) {
+    let key = pair_key(src_root, dest_root);
+    let src = scan(src_root, filter);
+    let dest = scan(dest_root, filter);
+    let prev = state.pairs.get(&key);
+    let mut next = BTreeMap::new();
+    let dirs = src.dirs.intersection(&dest.dirs).cloned().collect();
+
+    for (key, s) in &src.files {
+        if let Some(d) = dest.files.get(key) {
+            if same_state(s, d) {
+                next.insert(key.clone(), s.clone());
+            }
+        }
+    }
+
+    for c in conflicts {
+        next.remove(&c.path);
+        if let Some(p) = prev.and_then(|prev| prev.get(&c.path)) {
+            next.insert(c.path.clone(), p.clone());
+        }
+    }
+
+    state.pairs.insert(key.clone(), next);
+    state.dirs.insert(key, dirs);
+}
+
+// --- Commands ---
+
+#[tauri::command]
+pub async fn plan_sync<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    src: String,
+    dest_folder: String,
+    mode: SyncMode,
+    options: Option<SyncOptions>,
+) -> Result<SyncPlan, String> {
+    let state_file = state_path(&app_handle)?;
+    spawn_blocking(move || {
+        let src = remote::existing(&app_handle, &src)?;
+        let dest_folder = remote::existing(&app_handle, &dest_folder)?;
+        let (src_root, dest_root) = resolve_roots(src, dest_folder)?;
+        let options = options.unwrap_or_default();
+        let state = load_state(&state_file);
+        build_plan(&src_root, &dest_root, mode, &options, &state)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn run_sync<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    src: String,
+    dest_folder: String,
+    mode: SyncMode,
+    options: Option<SyncOptions>,
+) -> Result<SyncReport, String> {
+    let state_file = state_path(&app_handle)?;
+    let log = ActivityLog::from_app(&app_handle)?;
+    spawn_blocking(move || {
+        let src = remote::existing(&app_handle, &src)?;
+        let dest_folder = remote::existing(&app_handle, &dest_folder)?;
+        let (src_root, dest_root) = resolve_roots(src, dest_folder)?;
+        let options = options.unwrap_or_default();
+        let mut state = load_state(&state_file);
+
+        let plan = build_plan(&src_root, &dest_root, mode, &options, &state)?;
+        if let Some(space) = plan.space.iter().find(|s| !s.enough) {
+            return Err(space.error().to_string());
+        }
+        let report = execute_plan(&src_root, &dest_root, &plan, &log);
+
+        if mode == SyncMode::TwoWay {
+            let filter = options.filters.compile()?;
+            update_state(&mut state, &src_root, &dest_root, &plan.conflicts, &filter);
+            store::save_json_atomic(&state_file, &state)?;
+        }
+
+        Ok(report)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use filecanvas_lib::vfs::{MemoryFs, RemoteFs};
+    use std::sync::Arc;
+
+    /// `/src` and `/dest` on one in-memory filesystem
+    fn roots(fs: &MemoryFs) -> (Location, Location) {
+        let fs: Arc<dyn RemoteFs> = Arc::new(fs.clone());
+        let src = Location::Remote { fs, path: PathBuf::from("/src") };
+        let dest = src.with_path(PathBuf::from("/dest"));
+        (src, dest)
+    }
+
+    /// Write `contents` to `path` with a modification time of `mtime` seconds
+    fn put(fs: &MemoryFs, path: &str, contents: &str, mtime: u64) {
+        fs.add_file(path, contents).unwrap();
+        fs.set_mtime(Path::new(path), mtime * 1000).unwrap();
+    }
+
+    fn text(fs: &MemoryFs, path: &str) -> Option<String> {
+        fs.contents(path).map(|c| String::from_utf8(c).unwrap())
+    }
+
+    fn kinds(actions: &[SyncAction]) -> Vec<(SyncActionKind, &str)> {
+        actions.iter().map(|a| (a.kind, a.path.as_str())).collect()
+    }
+
+    fn log() -> ActivityLog {
+        ActivityLog::at(std::env::temp_dir().join(format!("filecanvas-sync-{}.log", std::process::id())))
+    }
+
+    /// Plan and run like `run_sync`, keeping the state in `state`
+    fn sync(fs: &MemoryFs, mode: SyncMode, options: &SyncOptions, state: &mut StateDb) -> SyncPlan {
+        let (src, dest) = roots(fs);
+        let plan = build_plan(&src, &dest, mode, options, state).unwrap();
+        let report = execute_plan(&src, &dest, &plan, &log());
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        if mode == SyncMode::TwoWay {
+            update_state(state, &src, &dest, &plan.conflicts, &options.filters.compile().unwrap());
+        }
+        plan
+    }
+
+    fn plan(fs: &MemoryFs, mode: SyncMode, options: &SyncOptions, state: &StateDb) -> SyncPlan {
+        let (src, dest) = roots(fs);
+        build_plan(&src, &dest, mode, options, state).unwrap()
+    }
+
+    /// A pair that was synced once: `a.txt` and `docs/b.txt` on both sides
+    fn synced_pair() -> (MemoryFs, StateDb) {
+        let fs = MemoryFs::new();
+        put(&fs, "/src/a.txt", "a", 100);
+        put(&fs, "/src/docs/b.txt", "b", 100);
+        let mut state = StateDb::default();
+        sync(&fs, SyncMode::TwoWay, &SyncOptions::default(), &mut state);
+        (fs, state)
+    }
+
+    #[test]
+    fn two_way_copies_changes_from_either_side() {
+        let (fs, mut state) = synced_pair();
+        assert_eq!(text(&fs, "/dest/docs/b.txt").unwrap(), "b");
+        assert_eq!(plan(&fs, SyncMode::TwoWay, &SyncOptions::default(), &state).unchanged, 2);
+
+        put(&fs, "/src/a.txt", "a, edited", 200);
+        put(&fs, "/dest/docs/b.txt", "b, edited", 200);
+        put(&fs, "/dest/docs/new.txt", "new", 200);
+        let done = sync(&fs, SyncMode::TwoWay, &SyncOptions::default(), &mut state);
+        assert_eq!(
+            kinds(&done.actions),
+            [
+                (SyncActionKind::CopyToDest, "a.txt"),
+                (SyncActionKind::CopyToSource, "docs/b.txt"),
+                (SyncActionKind::CopyToSource, "docs/new.txt"),
+            ]
+        );
+        assert_eq!(text(&fs, "/dest/a.txt").unwrap(), "a, edited");
+        assert_eq!(text(&fs, "/src/docs/b.txt").unwrap(), "b, edited");
+        assert_eq!(text(&fs, "/src/docs/new.txt").unwrap(), "new");
+
+        let again = plan(&fs, SyncMode::TwoWay, &SyncOptions::default(), &state);
+        assert!(again.actions.is_empty());
+        assert_eq!(again.unchanged, 3);
+    }
+
+    #[test]
+    fn conflicts_are_left_alone_and_reported_until_resolved() {
+        let (fs, mut state) = synced_pair();
+        put(&fs, "/src/a.txt", "source edit", 200);
+        put(&fs, "/dest/a.txt", "destination edit", 300);
+        fs.remove_file(Path::new("/dest/docs/b.txt")).unwrap();
+        put(&fs, "/src/docs/b.txt", "edited, deleted elsewhere", 200);
+
+        let done = sync(&fs, SyncMode::TwoWay, &SyncOptions::default(), &mut state);
+        assert!(done.actions.is_empty());
+        assert_eq!(
+            kinds(&done.conflicts),
+            [(SyncActionKind::Conflict, "a.txt"), (SyncActionKind::Conflict, "docs/b.txt")]
+        );
+        assert_eq!(done.conflicts[1].reason.as_deref(), Some("modified in source, deleted in destination"));
+        assert_eq!(text(&fs, "/src/a.txt").unwrap(), "source edit");
+        assert_eq!(text(&fs, "/dest/a.txt").unwrap(), "destination edit");
+
+        let again = plan(&fs, SyncMode::TwoWay, &SyncOptions::default(), &state);
+        assert_eq!(again.conflicts.len(), 2);
+
+        // Made equal by hand, the conflict is gone
+        put(&fs, "/dest/a.txt", "source edit", 200);
+        let resolved = sync(&fs, SyncMode::TwoWay, &SyncOptions::default(), &mut state);
+        assert_eq!(kinds(&resolved.conflicts), [(SyncActionKind::Conflict, "docs/b.txt")]);
+    }
+
+    #[test]
+    fn two_way_deletions_propagate() {
+        let (fs, mut state) = synced_pair();
+        fs.remove_file(Path::new("/src/a.txt")).unwrap();
+        fs.remove_dir_all(Path::new("/dest/docs")).unwrap();
+
+        let done = sync(&fs, SyncMode::TwoWay, &SyncOptions::default(), &mut state);
+        assert_eq!(
+            kinds(&done.actions),
+            [
+                (SyncActionKind::DeleteFromDest, "a.txt"),
+                (SyncActionKind::DeleteFromSource, "docs/b.txt"),
+                (SyncActionKind::DeleteFromSource, "docs"),
+            ]
+        );
+        assert!(!fs.exists(Path::new("/dest/a.txt")));
+        assert!(!fs.exists(Path::new("/src/docs")));
+    }
+
+    #[test]
+    fn a_folder_deleted_on_one_side_is_kept_when_the_other_added_to_it() {
+        let (fs, mut state) = synced_pair();
+        fs.remove_dir_all(Path::new("/dest/docs")).unwrap();
+        put(&fs, "/src/docs/added.txt", "added", 200);
+
+        let done = sync(&fs, SyncMode::TwoWay, &SyncOptions::default(), &mut state);
+        assert!(kinds(&done.actions).contains(&(SyncActionKind::CopyToDest, "docs/added.txt")));
+        assert!(kinds(&done.actions).contains(&(SyncActionKind::DeleteFromSource, "docs/b.txt")));
+        assert_eq!(text(&fs, "/dest/docs/added.txt").unwrap(), "added");
+        assert!(fs.exists(Path::new("/src/docs")));
+    }
+
+    #[test]
+    fn the_first_two_way_run_compares_contents() {
+        let fs = MemoryFs::new();
+        // Same file, copied at different times
+        put(&fs, "/src/same.txt", "same", 100);
+        put(&fs, "/dest/same.txt", "same", 500);
+        // Same size, different contents: no way to tell which side is newer
+        put(&fs, "/src/differs.txt", "aaaa", 100);
+        put(&fs, "/dest/differs.txt", "bbbb", 500);
+        put(&fs, "/src/only-here.txt", "x", 100);
+
+        let first = plan(&fs, SyncMode::TwoWay, &SyncOptions::default(), &StateDb::default());
+        assert_eq!(first.unchanged, 1);
+        assert_eq!(kinds(&first.actions), [(SyncActionKind::CopyToDest, "only-here.txt")]);
+        assert_eq!(kinds(&first.conflicts), [(SyncActionKind::Conflict, "differs.txt")]);
+    }
+
+    #[test]
+    fn mirror_copies_changes_and_deletes_extras_only_when_asked() {
+        let fs = MemoryFs::new();
+        put(&fs, "/src/a.txt", "a", 100);
+        put(&fs, "/src/changed.txt", "new", 200);
+        put(&fs, "/dest/changed.txt", "old", 100);
+        put(&fs, "/dest/extra.txt", "extra", 100);
+        put(&fs, "/dest/old/deeper/file.txt", "old", 100);
+
+        let keep = plan(&fs, SyncMode::Mirror, &SyncOptions::default(), &StateDb::default());
+        assert_eq!(
+            kinds(&keep.actions),
+            [(SyncActionKind::CopyToDest, "a.txt"), (SyncActionKind::CopyToDest, "changed.txt")]
+        );
+
+        let options = SyncOptions { delete_extras: true, ..SyncOptions::default() };
+        let done = sync(&fs, SyncMode::Mirror, &options, &mut StateDb::default());
+        assert_eq!(
+            kinds(&done.actions),
+            [
+                (SyncActionKind::CopyToDest, "a.txt"),
+                (SyncActionKind::CopyToDest, "changed.txt"),
+                (SyncActionKind::DeleteFromDest, "extra.txt"),
+                (SyncActionKind::DeleteFromDest, "old/deeper/file.txt"),
+                // Only the top-most extra folder
+                (SyncActionKind::DeleteFromDest, "old"),
+            ]
+        );
+        assert_eq!(text(&fs, "/dest/changed.txt").unwrap(), "new");
+        assert!(!fs.exists(Path::new("/dest/extra.txt")));
+        assert!(!fs.exists(Path::new("/dest/old")));
+        assert_eq!(fs.read_dir(Path::new("/dest")).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn filtered_files_are_never_deleted() {
+        let fs = MemoryFs::new();
+        put(&fs, "/src/a.txt", "a", 100);
+        put(&fs, "/dest/keep.tmp", "tmp", 100);
+        let options = SyncOptions {
+            delete_extras: true,
+            filters: FilterRules { exclude: vec!["*.tmp".into()], ..FilterRules::default() },
+            ..SyncOptions::default()
+        };
+
+        sync(&fs, SyncMode::Mirror, &options, &mut StateDb::default());
+        assert_eq!(text(&fs, "/dest/keep.tmp").unwrap(), "tmp");
+    }
+}
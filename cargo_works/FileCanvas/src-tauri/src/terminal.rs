@@ -0,0 +1,160 @@
+// src-tauri/src/terminal.rs
+//
+// Runs `Terminal=true` desktop entries (vim, htop, …) inside a terminal
+// emulator. The emulator is picked in this order:
+//   1. the user's override saved from the open-with modal
+//   2. $TERMINAL
+//   3. xdg-terminal-exec
+//   4. the first known emulator found on $PATH
+//
+// Emulators disagree on how to take a command (`-e`, `-x`, `--`, nothing),
+// so known ones carry their own prefix; unknown ones get `-e`.
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+use crate::desktop_entry::program_exists;
+use crate::store;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{Manager, Runtime};
+
+const SETTINGS_FILE: &str = "terminal.json";
+
+/// Known emulators and the arguments that come before the command to run
+const KNOWN_TERMINALS: &[(&str, &[&str])] = &[
+    ("xdg-terminal-exec", &[]),
+    ("kgx", &["--"]),
+    ("ptyxis", &["--"]),
+    ("gnome-terminal", &["--"]),
+    ("konsole", &["-e"]),
+    ("xfce4-terminal", &["-x"]),
+    ("mate-terminal", &["-x"]),
+    ("terminator", &["-x"]),
+    ("alacritty", &["-e"]),
+    ("kitty", &[]),
+    ("foot", &[]),
+    ("wezterm", &["start", "--"]),
+    ("xterm", &["-e"]),
+    ("urxvt", &["-e"]),
+    ("st", &["-e"]),
+];
+
+// --- Data Structures ---
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerminalSettings {
+    /// Program name ("kitty") or a full prefix ("wezterm start --")
+    pub command: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TerminalStatus {
+    /// The user's override, if any
+    pub configured: Option<String>,
+    /// What would be used right now
+    pub resolved: Option<String>,
+    /// Known emulators installed on this system
+    pub available: Vec<String>,
+}
+
+// --- Helpers ---
+
+fn settings_path<R: Runtime>(app_handle: &tauri::AppHandle<R>) -> Result<PathBuf, String> {
+    let dir = app_handle.path().app_config_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(SETTINGS_FILE))
+}
+
+fn load_settings(path: &Path) -> TerminalSettings {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Turn a terminal command into the argv prefix placed before the program.
+/// A bare known name gets its usual flags, a bare unknown one gets `-e`,
+/// and anything with arguments is taken literally.
+fn prefix_for(command: &str) -> Option<Vec<String>> {
+    let words: Vec<String> = command.split_whitespace().map(str::to_string).collect();
+    let program = words.first()?;
+    if !program_exists(program) {
+        return None;
+    }
+    if words.len() > 1 {
+        return Some(words);
+    }
+
+    let name = Path::new(program)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let flags: &[&str] = KNOWN_TERMINALS
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map(|(_, flags)| *flags)
+        .unwrap_or(&["-e"]);
+
+    let mut prefix = vec![program.clone()];
+    prefix.extend(flags.iter().map(|f| f.to_string()));
+    Some(prefix)
+}
+
+fn available_terminals() -> Vec<String> {
+    KNOWN_TERMINALS
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .filter(|name| program_exists(name))
+        .collect()
+}
+
+/// The terminal argv prefix to use, or None if no emulator could be found
+pub fn resolve(configured: Option<&str>) -> Option<Vec<String>> {
+    if let Some(prefix) = configured.filter(|c| !c.trim().is_empty()).and_then(prefix_for) {
+        return Some(prefix);
+    }
+    if let Some(prefix) = std::env::var("TERMINAL").ok().as_deref().and_then(prefix_for) {
+        return Some(prefix);
+    }
+    available_terminals().first().and_then(|t| prefix_for(t))
+}
+
+/// Wrap a command so it runs inside the user's terminal emulator
+pub fn wrap<R: Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    argv: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let settings = load_settings(&settings_path(app_handle)?);
+    let mut wrapped = resolve(settings.command.as_deref()).ok_or(
+        "No terminal emulator found. Install one or choose it in the Open With dialog.",
+    )?;
+    wrapped.extend(argv);
+    Ok(wrapped)
+}
+
+// --- Commands ---
+
+#[tauri::command]
+pub fn terminal_status<R: Runtime>(app_handle: tauri::AppHandle<R>) -> Result<TerminalStatus, String> {
+    let settings = load_settings(&settings_path(&app_handle)?);
+    Ok(TerminalStatus {
+        resolved: resolve(settings.command.as_deref()).map(|p| p.join(" ")),
+        configured: settings.command,
+        available: available_terminals(),
+    })
+}
+
+/// Save the terminal override; `None` or an empty string goes back to auto-detection
+#[tauri::command]
+pub fn set_terminal<R: Runtime>(
+    app_handle: tauri::AppHandle<R>,
+    command: Option<String>,
+) -> Result<TerminalStatus, String> {
+    let command = command.map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
+    if let Some(c) = &command {
+        prefix_for(c).ok_or_else(|| format!("Terminal not found: {}", c))?;
+    }
+    store::save_json_atomic(&settings_path(&app_handle)?, &TerminalSettings { command })?;
+    terminal_status(app_handle)
+}
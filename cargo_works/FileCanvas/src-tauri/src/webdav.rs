@@ -0,0 +1,658 @@
+// src-tauri/src/webdav.rs
+//
+// `FileSystem` over WebDAV, so a panel can show a Nextcloud or ownCloud share
+// (or any other WebDAV server) and connections can copy, move and sync to it.
+// `davs://user@host/remote.php/dav/files/user/Documents` uses HTTPS,
+// `dav://` plain HTTP; the path is the server path as-is.
+//
+// Listings are PROPFIND requests; files go up with PUT and down with GET,
+// folders are made with MKCOL, and MOVE/COPY/DELETE run on the server.
+// Uploads are spooled (in memory, then in a temp file past
+// `SPOOL_IN_MEMORY`) so they can be sent with a Content-Length; many PHP
+// servers refuse chunked requests. Logins use HTTP Basic auth, which is what
+// Nextcloud app passwords expect.
+
+use crate::error::{ErrorCode, FsError};
+use crate::vfs::{url_path, DirEntry, FileSystem, Metadata, RemoteFs};
+use base64::Engine;
+use chrono::DateTime;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use std::fs;
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const SCHEME: &str = "davs://";
+/// Same as `davs://` but over plain HTTP
+pub const PLAIN_SCHEME: &str = "dav://";
+/// Uploads larger than this are spooled to a temp file instead of memory
+pub const SPOOL_IN_MEMORY: usize = 8 * 1024 * 1024;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const IO_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// What PROPFIND asks for
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop><d:resourcetype/><d:getcontentlength/><d:getlastmodified/><d:creationdate/></d:prop>
+</d:propfind>"#;
+
+/// Path segments keep only unreserved characters unencoded
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'_').remove(b'.').remove(b'~');
+
+static SPOOL_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+// --- Data Structures ---
+
+/// Who to connect to; the part of a `davs://` URL before the path
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DavTarget {
+    /// Empty for servers without a login
+    pub user: String,
+    pub host: String,
+    pub port: u16,
+    pub https: bool,
+}
+
+pub struct DavFs {
+    client: Arc<Client>,
+}
+
+struct Client {
+    target: DavTarget,
+    /// `Basic …` header value, when there's a login
+    auth: Option<String>,
+    agent: ureq::Agent,
+}
+
+/// One `<d:response>` of a multistatus answer
+struct Resource {
+    /// Decoded server path without a trailing slash
+    path: String,
+    metadata: Metadata,
+}
+
+/// Why a request failed, before it's tied to a path
+#[derive(Debug)]
+enum Failure {
+    Status(u16),
+    /// A 207 answer where some of the items failed (DELETE, COPY, MOVE)
+    Partial(String),
+    /// No answer: DNS, connection refused, TLS, timeout
+    Transport(String),
+}
+
+enum Spool {
+    Memory(Vec<u8>),
+    File { file: fs::File, path: PathBuf },
+}
+
+/// An upload in progress from `open_write`
+struct Upload {
+    client: Arc<Client>,
+    path: PathBuf,
+    spool: Spool,
+    finished: bool,
+}
+
+// --- Helpers ---
+
+impl DavTarget {
+    fn scheme(&self) -> &'static str {
+        if self.https {
+            SCHEME
+        } else {
+            PLAIN_SCHEME
+        }
+    }
+
+    fn default_port(&self) -> u16 {
+        if self.https {
+            443
+        } else {
+            80
+        }
+    }
+
+    fn authority(&self) -> String {
+        let host = if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        };
+        if self.port == self.default_port() {
+            host
+        } else {
+            format!("{}:{}", host, self.port)
+        }
+    }
+
+    /// `davs://user@host:port`, used as the key for sessions and stored credentials
+    pub fn key(&self) -> String {
+        format!("{}{}@{}:{}", self.scheme(), self.user, self.host, self.port)
+    }
+
+    /// URL for `path` on this server
+    pub fn url(&self, path: &Path) -> String {
+        let user = if self.user.is_empty() {
+            String::new()
+        } else {
+            format!("{}@", self.user)
+        };
+        format!("{}{}{}{}", self.scheme(), user, self.authority(), path.to_string_lossy())
+    }
+
+    fn endpoint(&self) -> String {
+        let scheme = if self.https { "https" } else { "http" };
+        format!("{}://{}", scheme, self.authority())
+    }
+}
+
+pub fn is_url(path: &str) -> bool {
+    path.starts_with(SCHEME) || path.starts_with(PLAIN_SCHEME)
+}
+
+/// Split `davs://[user@]host[:port][/path]` into target and absolute path
+pub fn parse_url(url: &str) -> Result<(DavTarget, PathBuf), FsError> {
+    let invalid = || FsError::new(ErrorCode::InvalidPath, format!("Not a davs://user@host/path URL: {}", url)).with_path(url);
+    let (https, rest) = match (url.strip_prefix(SCHEME), url.strip_prefix(PLAIN_SCHEME)) {
+        (Some(rest), _) => (true, rest),
+        (None, Some(rest)) => (false, rest),
+        (None, None) => return Err(invalid()),
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let (user, host_port) = authority.rsplit_once('@').unwrap_or(("", authority));
+    let (host, port) = if let Some(v6) = host_port.strip_prefix('[') {
+        let (host, after) = v6.split_once(']').ok_or_else(invalid)?;
+        (host, after.strip_prefix(':'))
+    } else {
+        match host_port.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (host_port, None),
+        }
+    };
+    if host.is_empty() {
+        return Err(invalid());
+    }
+    let mut target = DavTarget {
+        user: user.to_string(),
+        host: host.to_string(),
+        port: 0,
+        https,
+    };
+    target.port = match port {
+        Some(p) => p.parse().map_err(|_| invalid())?,
+        None => target.default_port(),
+    };
+    Ok((target, url_path(path, url)?))
+}
+
+/// `/a/b c` as `/a/b%20c`, with a trailing slash for collections
+fn encode_path(path: &Path, collection: bool) -> String {
+    let mut encoded: String = path
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(format!("/{}", utf8_percent_encode(&part.to_string_lossy(), SEGMENT))),
+            _ => None,
+        })
+        .collect();
+    if encoded.is_empty() || collection {
+        encoded.push('/');
+    }
+    encoded
+}
+
+/// Server path of an href, which may be a full URL and is percent-encoded
+fn href_path(href: &str) -> String {
+    let path = match href.find("://") {
+        Some(i) => href[i + 3..].find('/').map_or("/", |j| &href[i + 3 + j..]),
+        None => href,
+    };
+    let decoded = percent_decode_str(path).decode_utf8_lossy();
+    let trimmed = decoded.trim_end_matches('/');
+    if trimmed.is_empty() {
+        "/".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// `Wed, 01 May 2024 10:00:00 GMT` (getlastmodified) or RFC 3339
+/// (creationdate); Unix milliseconds or 0
+fn parse_time(text: &str) -> u64 {
+    DateTime::parse_from_rfc2822(text)
+        .or_else(|_| DateTime::parse_from_rfc3339(text))
+        .map(|t| t.timestamp_millis().max(0) as u64)
+        .unwrap_or(0)
+}
+
+fn named<'a, 'i>(node: roxmltree::Node<'a, 'i>, name: &str) -> Option<roxmltree::Node<'a, 'i>> {
+    node.children().find(|n| n.tag_name().name() == name)
+}
+
+fn is_ok_status(node: roxmltree::Node) -> bool {
+    named(node, "status")
+        .and_then(|s| s.text())
+        .and_then(|s| s.split_whitespace().nth(1))
+        .is_some_and(|code| code.starts_with('2'))
+}
+
+/// Every `<d:response>` with the properties the server could return
+fn parse_multistatus(body: &str) -> Result<Vec<Resource>, Failure> {
+    let doc = roxmltree::Document::parse(body)
+        .map_err(|e| Failure::Transport(format!("Unreadable answer from the server: {}", e)))?;
+    let resources = doc
+        .descendants()
+        .filter(|n| n.tag_name().name() == "response")
+        .filter_map(|response| {
+            let href = named(response, "href")?.text()?;
+            let mut metadata = Metadata {
+                is_dir: false,
+                is_symlink: false,
+                len: 0,
+                mtime: 0,
+                ctime: 0,
+            };
+            let props = response
+                .children()
+                .filter(|n| n.tag_name().name() == "propstat" && is_ok_status(*n))
+                .filter_map(|propstat| named(propstat, "prop"));
+            for prop in props {
+                for value in prop.children().filter(|n| n.is_element()) {
+                    let text = value.text().unwrap_or_default().trim();
+                    match value.tag_name().name() {
+                        "resourcetype" => metadata.is_dir = named(value, "collection").is_some(),
+                        "getcontentlength" => metadata.len = text.parse().unwrap_or(0),
+                        "getlastmodified" => metadata.mtime = parse_time(text),
+                        "creationdate" => metadata.ctime = parse_time(text),
+                        _ => {}
+                    }
+                }
+            }
+            if metadata.ctime == 0 {
+                metadata.ctime = metadata.mtime;
+            }
+            Some(Resource {
+                path: href_path(href),
+                metadata,
+            })
+        })
+        .collect();
+    Ok(resources)
+}
+
+/// A 207 answer to DELETE/COPY/MOVE lists the members that failed
+fn check_multistatus(response: ureq::Response) -> Result<(), Failure> {
+    if response.status() != 207 {
+        return Ok(());
+    }
+    let body = response.into_string().map_err(|e| Failure::Transport(e.to_string()))?;
+    let Ok(doc) = roxmltree::Document::parse(&body) else {
+        return Ok(());
+    };
+    let failed: Vec<String> = doc
+        .descendants()
+        .filter(|n| n.tag_name().name() == "response" && named(*n, "status").is_some() && !is_ok_status(*n))
+        .filter_map(|n| named(n, "href")?.text().map(href_path))
+        .collect();
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(Failure::Partial(format!("Not done for: {}", failed.join(", "))))
+    }
+}
+
+impl Failure {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Failure::Transport(_) => ErrorKind::NotConnected,
+            Failure::Partial(_) => ErrorKind::Other,
+            Failure::Status(status) => match status {
+                403 => ErrorKind::PermissionDenied,
+                404 => ErrorKind::NotFound,
+                // MKCOL on an existing item; If-None-Match on an existing file
+                405 | 412 => ErrorKind::AlreadyExists,
+                // The parent folder is missing
+                409 => ErrorKind::NotFound,
+                414 => ErrorKind::InvalidFilename,
+                423 => ErrorKind::ResourceBusy,
+                501 => ErrorKind::Unsupported,
+                507 => ErrorKind::StorageFull,
+                _ => ErrorKind::Other,
+            },
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            Failure::Status(status) => format!("HTTP {}", status),
+            Failure::Partial(message) | Failure::Transport(message) => message.clone(),
+        }
+    }
+
+    fn into_io(self) -> io::Error {
+        io::Error::new(self.kind(), self.message())
+    }
+
+    /// As an `FsError` about `path`
+    fn at(self, target: &DavTarget, path: &Path) -> FsError {
+        match self {
+            Failure::Transport(message) => FsError::new(
+                ErrorCode::ConnectionFailed,
+                format!("Can't reach {}: {}", target.authority(), message),
+            )
+            .with_path(target.url(path)),
+            Failure::Status(401) => FsError::new(
+                ErrorCode::AuthFailed,
+                format!("{} rejected the login for {}", target.authority(), target.user),
+            )
+            .with_path(target.url(path)),
+            failure => FsError::io(&failure.into_io(), path),
+        }
+    }
+}
+
+// --- Requests ---
+
+impl Client {
+    fn url(&self, path: &Path, collection: bool) -> String {
+        format!("{}{}", self.target.endpoint(), encode_path(path, collection))
+    }
+
+    fn request(&self, method: &str, path: &Path, collection: bool) -> ureq::Request {
+        let request = self.agent.request(method, &self.url(path, collection));
+        match &self.auth {
+            Some(auth) => request.set("Authorization", auth),
+            None => request,
+        }
+    }
+
+    fn answer(result: Result<ureq::Response, ureq::Error>) -> Result<ureq::Response, Failure> {
+        match result {
+            Ok(response) => Ok(response),
+            Err(ureq::Error::Status(status, _)) => Err(Failure::Status(status)),
+            Err(ureq::Error::Transport(e)) => Err(Failure::Transport(e.to_string())),
+        }
+    }
+
+    /// `path` itself (depth 0) or its members too (depth 1)
+    fn propfind(&self, path: &Path, depth: u8) -> Result<Vec<Resource>, Failure> {
+        let request = self
+            .request("PROPFIND", path, depth > 0)
+            .set("Depth", &depth.to_string())
+            .set("Content-Type", "application/xml; charset=utf-8");
+        let response = Self::answer(request.send_string(PROPFIND_BODY))?;
+        let body = response.into_string().map_err(|e| Failure::Transport(e.to_string()))?;
+        parse_multistatus(&body)
+    }
+
+    fn put(request: ureq::Request, body: impl Read) -> Result<(), Failure> {
+        Self::answer(request.send(body)).map(|_| ())
+    }
+
+    /// MOVE or COPY `from` onto `to`, replacing what's there
+    fn transfer(&self, method: &str, from: &Path, to: &Path) -> Result<(), Failure> {
+        let request = self
+            .request(method, from, false)
+            .set("Destination", &self.url(to, false))
+            .set("Overwrite", "T");
+        check_multistatus(Self::answer(request.call())?)
+    }
+}
+
+// --- Uploads ---
+
+impl Spool {
+    fn len(&mut self) -> io::Result<u64> {
+        match self {
+            Spool::Memory(buffer) => Ok(buffer.len() as u64),
+            Spool::File { file, .. } => file.seek(SeekFrom::End(0)),
+        }
+    }
+
+    /// Move to a temp file once the memory buffer grows past `SPOOL_IN_MEMORY`
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        match self {
+            Spool::Memory(buffer) if buffer.len() + buf.len() > SPOOL_IN_MEMORY => {
+                let path = std::env::temp_dir().join(format!(
+                    "filecanvas-upload-{}-{}",
+                    std::process::id(),
+                    SPOOL_COUNTER.fetch_add(1, Ordering::Relaxed)
+                ));
+                let mut file = fs::File::options().read(true).write(true).create_new(true).open(&path)?;
+                file.write_all(buffer)?;
+                file.write_all(buf)?;
+                *self = Spool::File { file, path };
+                Ok(())
+            }
+            Spool::Memory(buffer) => {
+                buffer.extend_from_slice(buf);
+                Ok(())
+            }
+            Spool::File { file, .. } => file.write_all(buf),
+        }
+    }
+}
+
+impl Upload {
+    fn send(&mut self) -> io::Result<()> {
+        let len = self.spool.len()?;
+        let request = self
+            .client
+            .request("PUT", &self.path, false)
+            .set("Content-Length", &len.to_string());
+        let sent = match &mut self.spool {
+            Spool::Memory(buffer) => Client::put(request, buffer.as_slice()),
+            Spool::File { file, .. } => {
+                file.seek(SeekFrom::Start(0))?;
+                Client::put(request, file)
+            }
+        };
+        sent.map_err(Failure::into_io)
+    }
+}
+
+impl Write for Upload {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.finished {
+            return Err(io::Error::other("The upload was already sent"));
+        }
+        self.spool.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    /// Sends the file; nothing reaches the server before this
+    fn flush(&mut self) -> io::Result<()> {
+        if self.finished {
+            return Ok(());
+        }
+        self.send()?;
+        self.finished = true;
+        Ok(())
+    }
+}
+
+impl Drop for Upload {
+    fn drop(&mut self) {
+        if let Spool::File { path, .. } = &self.spool {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+// --- Connecting ---
+
+impl DavFs {
+    /// Log in and check that `path` is there
+    pub fn connect(target: &DavTarget, password: Option<&str>, path: &Path) -> Result<DavFs, FsError> {
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(CONNECT_TIMEOUT)
+            .timeout_read(IO_TIMEOUT)
+            .timeout_write(IO_TIMEOUT)
+            // A redirect would turn PROPFIND or MOVE into a GET
+            .redirects(0)
+            .build();
+        let auth = (!target.user.is_empty()).then(|| {
+            let login = format!("{}:{}", target.user, password.unwrap_or_default());
+            format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(login))
+        });
+        let client = Client {
+            target: target.clone(),
+            auth,
+            agent,
+        };
+        client.propfind(path, 0).map_err(|f| f.at(target, path))?;
+        Ok(DavFs {
+            client: Arc::new(client),
+        })
+    }
+
+    pub fn target(&self) -> &DavTarget {
+        &self.client.target
+    }
+
+    fn fail(&self, failure: Failure, path: &Path) -> FsError {
+        failure.at(&self.client.target, path)
+    }
+}
+
+impl FileSystem for DavFs {
+    fn metadata(&self, path: &Path) -> Result<Metadata, FsError> {
+        let resources = self.client.propfind(path, 0).map_err(|f| self.fail(f, path))?;
+        resources
+            .into_iter()
+            .next()
+            .map(|r| r.metadata)
+            .ok_or_else(|| FsError::io(&io::Error::from(ErrorKind::NotFound), path))
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> Result<Metadata, FsError> {
+        self.metadata(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> Result<Vec<DirEntry>, FsError> {
+        let resources = self.client.propfind(path, 1).map_err(|f| self.fail(f, path))?;
+        let own_path = href_path(&path.to_string_lossy());
+        let mut entries = Vec::new();
+        for resource in resources {
+            if resource.path == own_path {
+                if !resource.metadata.is_dir {
+                    return Err(FsError::io(&io::Error::from(ErrorKind::NotADirectory), path));
+                }
+                continue;
+            }
+            let Some(name) = resource.path.rsplit('/').next().filter(|n| !n.is_empty()) else {
+                continue;
+            };
+            entries.push(DirEntry {
+                name: name.to_string(),
+                path: path.join(name),
+                metadata: resource.metadata,
+            });
+        }
+        Ok(entries)
+    }
+
+    fn create_dir(&self, path: &Path) -> Result<(), FsError> {
+        let request = self.client.request("MKCOL", path, true);
+        Client::answer(request.call()).map(|_| ()).map_err(|f| self.fail(f, path))
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<(), FsError> {
+        match self.metadata(path) {
+            Ok(metadata) if metadata.is_dir => return Ok(()),
+            Ok(_) => return Err(FsError::io(&io::Error::from(ErrorKind::NotADirectory), path)),
+            Err(_) => {}
+        }
+        if let Some(parent) = path.parent() {
+            self.create_dir_all(parent)?;
+        }
+        self.create_dir(path)
+    }
+
+    fn create_file(&self, path: &Path) -> Result<(), FsError> {
+        // If-None-Match makes the server refuse instead of truncating
+        let request = self
+            .client
+            .request("PUT", path, false)
+            .set("If-None-Match", "*")
+            .set("Content-Length", "0");
+        Client::put(request, io::empty()).map_err(|f| self.fail(f, path))
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), FsError> {
+        let request = self.client.request("DELETE", path, false);
+        Client::answer(request.call())
+            .and_then(check_multistatus)
+            .map_err(|f| self.fail(f, path))
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> Result<(), FsError> {
+        // DELETE on a collection removes everything below it
+        let request = self.client.request("DELETE", path, true);
+        Client::answer(request.call())
+            .and_then(check_multistatus)
+            .map_err(|f| self.fail(f, path))
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), FsError> {
+        self.client.transfer("MOVE", from, to).map_err(|f| self.fail(f, from))
+    }
+
+    fn open_read(&self, path: &Path) -> Result<Box<dyn Read + Send>, FsError> {
+        let response = Client::answer(self.client.request("GET", path, false).call()).map_err(|f| self.fail(f, path))?;
+        Ok(response.into_reader())
+    }
+
+    fn open_write(&self, path: &Path) -> Result<Box<dyn Write + Send>, FsError> {
+        Ok(Box::new(Upload {
+            client: Arc::clone(&self.client),
+            path: path.to_path_buf(),
+            spool: Spool::Memory(Vec::new()),
+            finished: false,
+        }))
+    }
+
+    /// Server-side COPY, so the data never passes through this machine
+    fn copy_file(&self, from: &Path, to: &Path) -> Result<u64, FsError> {
+        let len = self.metadata(from)?.len;
+        self.client.transfer("COPY", from, to).map_err(|f| self.fail(f, to))?;
+        Ok(len)
+    }
+
+    /// Through the `lastmodified` property Nextcloud and ownCloud accept;
+    /// plain WebDAV servers treat it as read-only
+    fn set_mtime(&self, path: &Path, mtime: u64) -> Result<(), FsError> {
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propertyupdate xmlns:d="DAV:"><d:set><d:prop><d:lastmodified>{}</d:lastmodified></d:prop></d:set></d:propertyupdate>"#,
+            mtime / 1000
+        );
+        let request = self
+            .client
+            .request("PROPPATCH", path, false)
+            .set("Content-Type", "application/xml; charset=utf-8");
+        let response = Client::answer(request.send_string(&body)).map_err(|f| self.fail(f, path))?;
+        let answer = response.into_string().unwrap_or_default();
+        let accepted = roxmltree::Document::parse(&answer).is_ok_and(|doc| {
+            doc.descendants()
+                .filter(|n| n.tag_name().name() == "propstat")
+                .all(is_ok_status)
+        });
+        if accepted {
+            Ok(())
+        } else {
+            Err(FsError::new(ErrorCode::Unsupported, "The server keeps its own modification times").with_path(path))
+        }
+    }
+}
+
+impl RemoteFs for DavFs {
+    fn url(&self, path: &Path) -> String {
+        self.client.target.url(path)
+    }
+}
@@ -0,0 +1,160 @@
+// src-tauri/tests/remote_servers.rs
+//
+// The file operations behind the panel commands, run against the servers in
+// dev/. They need the containers up, so they're ignored by default:
+//
+//   docker compose -f dev/sftp/docker-compose.yml up -d   (and dev/s3, dev/webdav)
+//   cargo test --test remote_servers -- --ignored
+//
+// SFTP_URL, S3_URL and WEBDAV_URL point them elsewhere; the passwords and keys
+// default to the ones in the compose files. Each test works in a scratch
+// folder of its own and removes it again.
+
+use filecanvas_lib::s3::{self, AccessKey, S3Fs};
+use filecanvas_lib::sftp::{self, Auth, HostKeys, SftpFs};
+use filecanvas_lib::webdav::{self, DavFs};
+use filecanvas_lib::{ops, ErrorCode, FileSystem, FsError};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+fn env_or(name: &str, default: &str) -> String {
+    std::env::var(name).unwrap_or_else(|_| default.into())
+}
+
+fn write(fs: &dyn FileSystem, path: &Path, contents: &[u8]) -> Result<(), FsError> {
+    let mut writer = fs.open_write(path)?;
+    writer.write_all(contents).map_err(|e| FsError::io(&e, path))?;
+    writer.flush().map_err(|e| FsError::io(&e, path))
+}
+
+fn read(fs: &dyn FileSystem, path: &Path) -> Result<Vec<u8>, FsError> {
+    let mut contents = Vec::new();
+    fs.open_read(path)?
+        .read_to_end(&mut contents)
+        .map_err(|e| FsError::io(&e, path))?;
+    Ok(contents)
+}
+
+/// What the create, copy, move, rename, info and delete commands do, in the
+/// order a user would click through them
+fn exercise(fs: &dyn FileSystem, base: &Path) -> Result<(), FsError> {
+    let docs = base.join("docs");
+    let archive = base.join("archive");
+    ops::create_folder(fs, &docs)?;
+    ops::create_folder(fs, &archive)?;
+    assert!(fs.metadata(&docs)?.is_dir);
+
+    let empty = docs.join("empty.txt");
+    ops::create_file(fs, &empty)?;
+    assert_eq!(fs.metadata(&empty)?.len, 0);
+
+    let note = docs.join("note.txt");
+    write(fs, &note, b"hello from filecanvas\n")?;
+    // Creating a file that's there must not truncate it
+    let _ = ops::create_file(fs, &note);
+    assert_eq!(read(fs, &note)?, b"hello from filecanvas\n");
+
+    let copied = ops::copy_file(fs, &note, &archive)?;
+    assert_eq!(read(fs, &copied)?, b"hello from filecanvas\n");
+    let again = ops::copy_file(fs, &note, &archive).unwrap_err();
+    assert_eq!(again.code, ErrorCode::AlreadyExists);
+
+    let into_itself = ops::copy_folder(fs, &docs, &docs).unwrap_err();
+    assert_eq!(into_itself.code, ErrorCode::InvalidPath);
+    let folder_copy = ops::copy_folder(fs, &docs, &archive)?;
+    assert!(fs.exists(&folder_copy.join("note.txt")));
+
+    let renamed = ops::rename_item(fs, &empty, "blank.txt")?;
+    assert!(fs.exists(&renamed) && !fs.exists(&empty));
+
+    let moved = ops::move_item(fs, &renamed, &archive)?;
+    assert!(fs.exists(&moved) && !fs.exists(&renamed));
+
+    let listed: Vec<String> = ops::read_dir_shallow(fs, &archive).into_iter().map(|item| item.name).collect();
+    for name in ["note.txt", "docs", "blank.txt"] {
+        assert!(listed.iter().any(|n| n == name), "{} missing from {:?}", name, listed);
+    }
+
+    ops::delete_item(fs, &copied)?;
+    assert!(!fs.exists(&copied));
+    ops::delete_item(fs, &docs)?;
+    assert!(!fs.exists(&docs) && fs.exists(&folder_copy.join("note.txt")));
+    ops::delete_item(fs, base)?;
+    assert!(!fs.exists(base));
+    Ok(())
+}
+
+fn scratch(root: &Path, test: &str) -> PathBuf {
+    root.join(format!("filecanvas-{}-{}", test, std::process::id()))
+}
+
+#[test]
+#[ignore = "needs the SFTP container in dev/sftp"]
+fn sftp_file_operations() -> Result<(), FsError> {
+    let url = env_or("SFTP_URL", "sftp://canvas@localhost:2222/config/data");
+    let auth = Auth::Password(env_or("SFTP_PASSWORD", "canvas"));
+    let (target, folder) = sftp::parse_url(&url)?;
+    let known_hosts = std::env::temp_dir().join("filecanvas_sftp_test_known_hosts");
+
+    let untrusted = HostKeys { file: &known_hosts, trust: None };
+    let fs = match SftpFs::connect(&target, &auth, &untrusted) {
+        Err(e) if e.code == ErrorCode::UnknownHost => {
+            let trusted = HostKeys { file: &known_hosts, trust: e.detail.as_deref() };
+            SftpFs::connect(&target, &auth, &trusted)?
+        }
+        other => other?,
+    };
+
+    let base = scratch(&folder, "sftp");
+    fs.create_dir_all(&base)?;
+    exercise(&fs, &base)
+}
+
+#[test]
+#[ignore = "needs the MinIO container in dev/s3"]
+fn s3_file_operations() -> Result<(), FsError> {
+    let url = env_or("S3_URL", "s3+http://localhost:9000");
+    let key = AccessKey::from_env().unwrap_or(AccessKey {
+        id: "canvas".into(),
+        secret: "canvas-secret".into(),
+        region: None,
+    });
+    let (target, _) = s3::parse_url(&url)?;
+    let fs = S3Fs::connect(&target, &key, Path::new("/"))?;
+
+    let bucket = Path::new("/filecanvas-tests");
+    fs.create_dir_all(bucket)?;
+    let base = scratch(bucket, "s3");
+    fs.create_dir_all(&base)?;
+    exercise(&fs, &base)?;
+
+    // Emptying a whole bucket is refused, the bucket stays
+    let refused = ops::delete_item(&fs, bucket).unwrap_err();
+    assert_eq!(refused.code, ErrorCode::ProtectedRoot);
+    assert!(fs.exists(bucket));
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the WebDAV container in dev/webdav"]
+fn webdav_file_operations() -> Result<(), FsError> {
+    let url = env_or("WEBDAV_URL", "dav://canvas@localhost:8080/");
+    let password = env_or("WEBDAV_PASSWORD", "canvas");
+    let (target, root) = webdav::parse_url(&url)?;
+    let fs = DavFs::connect(&target, Some(&password), &root)?;
+
+    let base = scratch(&root, "webdav");
+    fs.create_dir_all(&base)?;
+    exercise(&fs, &base)
+}
+
+#[test]
+fn server_urls_cannot_climb_out() {
+    for result in [
+        sftp::parse_url("sftp://canvas@localhost:2222/config/../etc").map(|_| ()),
+        s3::parse_url("s3+http://localhost:9000/bucket/../other").map(|_| ()),
+        webdav::parse_url("dav://canvas@localhost:8080/share/./../x").map(|_| ()),
+    ] {
+        assert_eq!(result.unwrap_err().code, ErrorCode::InvalidPath);
+    }
+}
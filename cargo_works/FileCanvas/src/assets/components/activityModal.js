@@ -0,0 +1,152 @@
+// public/assets/components/activityModal.js
+// Browse the activity log kept by the backend (every create, copy, move,
+// delete and rename, with user and result) and export a selection as CSV.
+import { fileapi } from '../fileapi.js';
+import { showSnackbar, describeError, formatBytes } from './uiUtils.js';
+
+const OPERATIONS = {
+  copy: 'Copy',
+  move: 'Move',
+  delete: 'Delete',
+  rename: 'Rename',
+  create_folder: 'New folder',
+  create_file: 'New file',
+  command: 'Command',
+  open: 'Open',
+};
+
+const VIA = {
+  panel: 'Panel',
+  sync: 'Sync',
+  filtered_transfer: 'Filtered transfer',
+  organizer: 'Organizer',
+  organizer_undo: 'Organizer undo',
+  hot_folder: 'Hot folder',
+};
+
+// yyyy-mm-dd from a date input as local midnight in ms
+const dayStart = (value) => (value ? new Date(`${value}T00:00`).getTime() : null);
+const dayAfter = (value) => (value ? dayStart(value) + 24 * 60 * 60 * 1000 : null);
+
+export function showActivityModal() {
+  const overlay = document.createElement('div');
+  overlay.className = 'fixed inset-0 bg-black/50 z-[40000] flex items-center justify-center p-4';
+
+  const modal = document.createElement('div');
+  modal.className = 'bg-white rounded-xl shadow-2xl w-full max-w-5xl max-h-[90vh] flex flex-col p-4 overflow-hidden';
+  modal.innerHTML = `
+    <h2 class="font-semibold text-base mb-3">Activity</h2>
+
+    <form class="flex flex-wrap items-center gap-2 text-sm mb-3">
+      <label class="flex items-center gap-1">From <input name="since" type="date" class="px-2 py-1 border border-gray-300 rounded" /></label>
+      <label class="flex items-center gap-1">To <input name="until" type="date" class="px-2 py-1 border border-gray-300 rounded" /></label>
+      <select name="operation" class="px-2 py-1 border border-gray-300 rounded">
+        <option value="">All operations</option>
+        ${Object.entries(OPERATIONS).map(([value, label]) => `<option value="${value}">${label}</option>`).join('')}
+      </select>
+      <input name="pathPrefix" placeholder="In folder, e.g. /home/anna/Projects" class="flex-1 min-w-[12rem] px-2 py-1 border border-gray-300 rounded font-mono" />
+      <label class="flex items-center gap-1"><input type="checkbox" name="failedOnly" /> Failed only</label>
+      <button type="submit" class="px-3 py-1.5 bg-indigo-600 hover:bg-indigo-700 text-white rounded">Search</button>
+    </form>
+
+    <div class="overflow-auto border border-gray-200 rounded flex-1">
+      <table class="w-full text-xs">
+        <thead class="bg-gray-50 text-gray-600 sticky top-0">
+          <tr>
+            <th class="text-left px-2 py-1">Time</th>
+            <th class="text-left px-2 py-1">User</th>
+            <th class="text-left px-2 py-1">Operation</th>
+            <th class="text-left px-2 py-1">Source</th>
+            <th class="text-left px-2 py-1">Destination</th>
+            <th class="text-right px-2 py-1">Size</th>
+          </tr>
+        </thead>
+        <tbody class="divide-y divide-gray-100"></tbody>
+      </table>
+    </div>
+
+    <div class="flex items-center justify-between mt-3 text-sm">
+      <span class="summary text-gray-500"></span>
+      <div class="flex gap-2">
+        <button type="button" data-action="export" class="px-3 py-1.5 bg-emerald-600 hover:bg-emerald-700 text-white rounded flex items-center gap-2">
+          <i class="fas fa-file-csv"></i> Export CSV
+        </button>
+        <button type="button" data-action="close" class="px-3 py-1.5 bg-gray-200 hover:bg-gray-300 rounded">Close</button>
+      </div>
+    </div>
+  `;
+
+  const form = modal.querySelector('form');
+  const tbody = modal.querySelector('tbody');
+  const summaryEl = modal.querySelector('.summary');
+
+  const currentQuery = () => {
+    const data = new FormData(form);
+    const operation = data.get('operation');
+    return {
+      since: dayStart(data.get('since')),
+      until: dayAfter(data.get('until')),
+      pathPrefix: data.get('pathPrefix').trim() || null,
+      operations: operation ? [operation] : [],
+      failedOnly: data.get('failedOnly') === 'on',
+    };
+  };
+
+  const cell = (text, className = '') => {
+    const td = document.createElement('td');
+    td.className = `px-2 py-1 ${className}`;
+    td.textContent = text ?? '';
+    td.title = text ?? '';
+    return td;
+  };
+
+  const render = (entries) => {
+    tbody.innerHTML = '';
+    if (!entries.length) {
+      tbody.innerHTML = '<tr><td colspan="6" class="px-2 py-3 text-gray-500">Nothing logged for this selection.</td></tr>';
+    }
+    for (const e of entries) {
+      const row = document.createElement('tr');
+      if (!e.success) row.className = 'text-red-600';
+      const operation = `${OPERATIONS[e.operation] ?? e.operation} · ${VIA[e.via] ?? e.via}`;
+      row.append(
+        cell(new Date(e.timestamp).toLocaleString(), 'whitespace-nowrap'),
+        cell(e.user),
+        cell(e.success ? operation : `${operation} — ${e.error ?? 'failed'}`),
+        cell(e.source, 'font-mono max-w-[16rem] truncate'),
+        cell(e.destination, 'font-mono max-w-[16rem] truncate'),
+        cell(formatBytes(e.bytes), 'text-right whitespace-nowrap'),
+      );
+      tbody.appendChild(row);
+    }
+    summaryEl.textContent = `${entries.length} entries, newest first`;
+  };
+
+  const search = async () => {
+    try {
+      render(await fileapi.queryActivity(currentQuery()));
+    } catch (err) {
+      showSnackbar(`Could not read the activity log: ${describeError(err)}`, 'error');
+    }
+  };
+
+  form.addEventListener('submit', (e) => {
+    e.preventDefault();
+    search();
+  });
+
+  modal.querySelector('[data-action="export"]').addEventListener('click', async () => {
+    try {
+      const file = await fileapi.exportActivity(currentQuery());
+      if (file) showSnackbar(`Exported to ${file}`, 'success');
+    } catch (err) {
+      showSnackbar(`Export failed: ${describeError(err)}`, 'error');
+    }
+  });
+
+  modal.querySelector('[data-action="close"]').addEventListener('click', () => overlay.remove());
+
+  overlay.appendChild(modal);
+  document.body.appendChild(overlay);
+  search();
+}
@@ -0,0 +1,149 @@
+// public/assets/components/connectionFilters.js
+// Per-connection filter rules (include/exclude globs, extensions, size, age, name regex).
+// The rules are applied by the backend (plan_transfer / transfer_filtered / run_sync).
+
+const splitList = (value) =>
+  value.split(',').map(s => s.trim()).filter(Boolean);
+
+const toNumberOrNull = (value) => {
+  const n = parseInt(value, 10);
+  return Number.isFinite(n) && n >= 0 ? n : null;
+};
+
+const MB = 1024 * 1024;
+
+/**
+ * True if the connection has at least one active filter rule
+ */
+export function hasFilters(filters) {
+  if (!filters) return false;
+  return (
+    filters.include.length > 0 ||
+    filters.exclude.length > 0 ||
+    filters.extensions.length > 0 ||
+    filters.min_size !== null ||
+    filters.max_size !== null ||
+    filters.older_than_days !== null ||
+    filters.newer_than_days !== null ||
+    !!filters.name_regex
+  );
+}
+
+/**
+ * Short human-readable summary for tooltips
+ */
+export function describeFilters(filters) {
+  if (!hasFilters(filters)) return 'No filters';
+  const parts = [];
+  if (filters.include.length) parts.push(`include ${filters.include.join(', ')}`);
+  if (filters.exclude.length) parts.push(`exclude ${filters.exclude.join(', ')}`);
+  if (filters.extensions.length) parts.push(`ext ${filters.extensions.join(', ')}`);
+  if (filters.min_size !== null) parts.push(`≥ ${(filters.min_size / MB).toFixed(1)} MB`);
+  if (filters.max_size !== null) parts.push(`≤ ${(filters.max_size / MB).toFixed(1)} MB`);
+  if (filters.older_than_days !== null) parts.push(`older than ${filters.older_than_days}d`);
+  if (filters.newer_than_days !== null) parts.push(`newer than ${filters.newer_than_days}d`);
+  if (filters.name_regex) parts.push(`name /${filters.name_regex}/`);
+  return parts.join(' · ');
+}
+
+/**
+ * Group a transfer plan's filtered entries by the rule that removed them
+ */
+export function summarizeFiltered(plan) {
+  const byRule = new Map();
+  for (const entry of plan.filtered) {
+    byRule.set(entry.rule, (byRule.get(entry.rule) || 0) + 1);
+  }
+  return Array.from(byRule.entries())
+    .map(([rule, count]) => `${count} × ${rule}`)
+    .join('\n');
+}
+
+/**
+ * Open the filter editor for a connection. Resolves with the new rules,
+ * or null if the user cancelled.
+ */
+export function openFilterModal(connection) {
+  const f = connection.filters || {};
+
+  return new Promise((resolve) => {
+    const overlay = document.createElement('div');
+    overlay.className = 'fixed inset-0 bg-black/50 z-[40000] flex items-center justify-center p-4';
+
+    const field = (label, name, value, placeholder = '') => `
+      <label class="block text-xs font-medium text-gray-700 mb-1">${label}</label>
+      <input name="${name}" value="${value ?? ''}" placeholder="${placeholder}"
+        class="w-full px-2 py-1 mb-3 text-sm border border-gray-300 rounded focus:ring-indigo-500 focus:border-indigo-500" />
+    `;
+
+    const modal = document.createElement('form');
+    modal.className = 'bg-white rounded-xl shadow-2xl w-full max-w-md p-4';
+    modal.innerHTML = `
+      <h2 class="font-semibold text-base mb-3">Filters for connection #${connection.id}</h2>
+      ${field('Include globs (comma separated)', 'include', (f.include || []).join(', '), '*.pdf, invoices/**')}
+      ${field('Exclude globs (comma separated)', 'exclude', (f.exclude || []).join(', '), '*.tmp, node_modules')}
+      ${field('Extensions (comma separated)', 'extensions', (f.extensions || []).join(', '), 'pdf, docx')}
+      <div class="grid grid-cols-2 gap-2">
+        <div>${field('Min size (MB)', 'min_size', f.min_size != null ? f.min_size / MB : '')}</div>
+        <div>${field('Max size (MB)', 'max_size', f.max_size != null ? f.max_size / MB : '')}</div>
+        <div>${field('Older than (days)', 'older_than_days', f.older_than_days)}</div>
+        <div>${field('Newer than (days)', 'newer_than_days', f.newer_than_days)}</div>
+      </div>
+      ${field('Name regex', 'name_regex', f.name_regex || '', '^invoice_\\d+')}
+      <div class="flex justify-between gap-2 mt-2">
+        <button type="button" data-action="clear" class="px-3 py-1.5 text-sm text-red-600 hover:bg-red-50 rounded">Clear</button>
+        <div class="flex gap-2">
+          <button type="button" data-action="cancel" class="px-3 py-1.5 text-sm bg-gray-200 hover:bg-gray-300 rounded">Cancel</button>
+          <button type="submit" class="px-3 py-1.5 text-sm bg-indigo-600 hover:bg-indigo-700 text-white rounded">Save</button>
+        </div>
+      </div>
+    `;
+
+    const close = (result) => {
+      overlay.remove();
+      resolve(result);
+    };
+
+    const sizeInBytes = (value) => {
+      const n = parseFloat(value);
+      return Number.isFinite(n) && n >= 0 ? Math.round(n * MB) : null;
+    };
+
+    modal.addEventListener('submit', (e) => {
+      e.preventDefault();
+      const data = new FormData(modal);
+      close({
+        include: splitList(data.get('include')),
+        exclude: splitList(data.get('exclude')),
+        extensions: splitList(data.get('extensions')),
+        min_size: sizeInBytes(data.get('min_size')),
+        max_size: sizeInBytes(data.get('max_size')),
+        older_than_days: toNumberOrNull(data.get('older_than_days')),
+        newer_than_days: toNumberOrNull(data.get('newer_than_days')),
+        name_regex: data.get('name_regex').trim() || null,
+      });
+    });
+    modal.querySelector('[data-action="cancel"]').addEventListener('click', () => close(null));
+    modal.querySelector('[data-action="clear"]').addEventListener('click', () => close(emptyFilters()));
+    overlay.addEventListener('mousedown', (e) => {
+      if (e.target === overlay) close(null);
+    });
+
+    overlay.appendChild(modal);
+    document.body.appendChild(overlay);
+    modal.querySelector('input')?.focus();
+  });
+}
+
+export function emptyFilters() {
+  return {
+    include: [],
+    exclude: [],
+    extensions: [],
+    min_size: null,
+    max_size: null,
+    older_than_days: null,
+    newer_than_days: null,
+    name_regex: null,
+  };
+}
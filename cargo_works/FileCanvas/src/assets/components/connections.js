@@ -1,5 +1,6 @@
 // public/assets/components/connections.js
 import { fileapi } from "../fileapi.js";
+import { openFilterModal, describeFilters, hasFilters, emptyFilters } from "./connectionFilters.js";
 
 
 export let showAllConnectionsMode = false;
@@ -518,7 +519,8 @@ async function handleMouseUp(e) {
     fromEl: activeArrow.fromEl,
     toEl: targetDot,
     pathEl: activeArrow.path,
-    cancelBtn
+    cancelBtn,
+    filters: emptyFilters()
   };
 
   // Attach labels to both dots
@@ -536,6 +538,8 @@ async function handleMouseUp(e) {
       <div><span class="font-medium">From:</span> ${connection.fromPath}</div>
       <div><span class="font-medium">To:</span> ${connection.toPath}</div>
       <div class="text-gray-500 text-xs mt-1">${fromPanel} → ${toPanel}</div>
+      <div class="text-gray-500 text-xs mt-1"><span class="font-medium">Filters:</span> ${describeFilters(connection.filters)}</div>
+      <div class="text-gray-400 text-xs">Click the line to edit filters</div>
     `;
     showConnectionTooltip(html, e.clientX, e.clientY);
   };
@@ -551,6 +555,13 @@ async function handleMouseUp(e) {
   connection.pathEl.addEventListener('mouseenter', onMouseEnter);
   connection.pathEl.addEventListener('mousemove', onMouseMove);
   connection.pathEl.addEventListener('mouseleave', onMouseLeave);
+  connection.pathEl.addEventListener('click', async () => {
+    hideConnectionTooltip();
+    const filters = await openFilterModal(connection);
+    if (!filters) return;
+    connection.filters = filters;
+    connection.pathEl.setAttribute("stroke", hasFilters(filters) ? "#d97706" : "#4f46e5");
+  });
 
   // Store for cleanup
   connection._tooltipHandlers = { onMouseEnter, onMouseMove, onMouseLeave };
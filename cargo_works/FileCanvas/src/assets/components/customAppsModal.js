@@ -0,0 +1,140 @@
+// public/assets/components/customAppsModal.js
+// User-defined "Open with" commands (scripts without a .desktop file).
+// They show up in the Open With dialog next to the installed applications.
+import { fileapi } from '../fileapi.js';
+import { showSnackbar } from './uiUtils.js';
+
+const splitList = (value) =>
+  value.split(',').map(s => s.trim()).filter(Boolean);
+
+export async function showCustomAppsModal({ onClose } = {}) {
+  let apps = await fileapi.listCustomApps();
+  let editingId = '';
+
+  const overlay = document.createElement('div');
+  overlay.className = 'fixed inset-0 bg-black/50 z-[40000] flex items-center justify-center p-4';
+
+  const modal = document.createElement('div');
+  modal.className = 'bg-gray-800 text-gray-100 rounded-xl shadow-2xl w-full max-w-xl max-h-[90vh] flex flex-col p-4 overflow-hidden';
+  modal.innerHTML = `
+    <h2 class="font-semibold text-base mb-3">Custom open-with commands</h2>
+
+    <div class="apps overflow-auto mb-3 border border-gray-700 rounded divide-y divide-gray-700"></div>
+
+    <form class="grid grid-cols-2 gap-2 text-sm">
+      <input name="name" placeholder="Name, e.g. Stamp PDF" class="px-2 py-1 bg-gray-700 border border-gray-600 rounded" required />
+      <input name="types" placeholder="Types, e.g. pdf, image/* (empty = all)" class="px-2 py-1 bg-gray-700 border border-gray-600 rounded" />
+      <input name="command" placeholder='Command, e.g. "/opt/stamp pdf.sh" {path}' class="col-span-2 px-2 py-1 bg-gray-700 border border-gray-600 rounded font-mono" required />
+      <div class="col-span-2 text-xs text-gray-400">
+        Placeholders: {path} {dir} {name} {stem} {ext}. Without {path} the path is appended. Runs without a shell.
+      </div>
+      <label class="flex items-center gap-2">
+        <input type="checkbox" name="terminal" /> Run in terminal
+      </label>
+      <div class="flex justify-end gap-2">
+        <button type="button" data-action="reset" class="hidden px-3 py-1.5 text-gray-300 hover:bg-gray-700 rounded">Cancel edit</button>
+        <button type="submit" class="px-3 py-1.5 bg-indigo-600 hover:bg-indigo-700 text-white rounded">Add</button>
+      </div>
+    </form>
+
+    <div class="flex justify-end mt-3">
+      <button type="button" data-action="close" class="px-3 py-1.5 text-sm text-gray-400 hover:text-gray-200 hover:bg-gray-700 rounded">Close</button>
+    </div>
+  `;
+
+  const appsEl = modal.querySelector('.apps');
+  const form = modal.querySelector('form');
+  const submitBtn = form.querySelector('[type="submit"]');
+  const resetBtn = form.querySelector('[data-action="reset"]');
+  const field = (name) => form.querySelector(`[name="${name}"]`);
+
+  const renderApps = () => {
+    appsEl.innerHTML = apps.length
+      ? ''
+      : '<div class="p-3 text-sm text-gray-500">No custom commands yet.</div>';
+    apps.forEach(app => {
+      const row = document.createElement('div');
+      row.className = 'flex items-center gap-2 px-3 py-2 text-sm';
+      row.innerHTML = `
+        <i class="fas ${app.terminal ? 'fa-terminal' : 'fa-code'} text-gray-400"></i>
+        <div class="flex-1 min-w-0">
+          <div class="font-medium truncate">${app.name}</div>
+          <div class="text-xs text-gray-400 truncate font-mono">${app.command}</div>
+          <div class="text-xs text-gray-500 truncate">${app.file_types.length ? app.file_types.join(', ') : 'All files'}</div>
+        </div>
+        <button type="button" data-id="${app.id}" data-action="edit" class="text-gray-400 hover:text-indigo-300"><i class="fas fa-pen"></i></button>
+        <button type="button" data-id="${app.id}" data-action="remove" class="text-red-400 hover:text-red-300">×</button>
+      `;
+      appsEl.appendChild(row);
+    });
+  };
+
+  const resetForm = () => {
+    editingId = '';
+    form.reset();
+    submitBtn.textContent = 'Add';
+    resetBtn.classList.add('hidden');
+  };
+
+  appsEl.addEventListener('click', async (e) => {
+    const btn = e.target.closest('[data-action]');
+    if (!btn) return;
+    const app = apps.find(a => a.id === btn.dataset.id);
+    if (!app) return;
+
+    if (btn.dataset.action === 'edit') {
+      editingId = app.id;
+      field('name').value = app.name;
+      field('types').value = app.file_types.join(', ');
+      field('command').value = app.command;
+      field('terminal').checked = app.terminal;
+      submitBtn.textContent = 'Save';
+      resetBtn.classList.remove('hidden');
+      return;
+    }
+
+    try {
+      await fileapi.deleteCustomApp(app.id);
+      apps = apps.filter(a => a.id !== app.id);
+      if (editingId === app.id) resetForm();
+      renderApps();
+    } catch (err) {
+      showSnackbar(`Could not remove command: ${err}`, 'error');
+    }
+  });
+
+  form.addEventListener('submit', async (e) => {
+    e.preventDefault();
+    try {
+      const saved = await fileapi.saveCustomApp({
+        id: editingId,
+        name: field('name').value,
+        command: field('command').value,
+        file_types: splitList(field('types').value),
+        terminal: field('terminal').checked,
+      });
+      const index = apps.findIndex(a => a.id === saved.id);
+      if (index >= 0) apps[index] = saved; else apps.push(saved);
+      resetForm();
+      renderApps();
+    } catch (err) {
+      showSnackbar(`Could not save command: ${err}`, 'error');
+    }
+  });
+
+  resetBtn.addEventListener('click', resetForm);
+
+  const close = () => {
+    overlay.remove();
+    onClose?.();
+  };
+  modal.querySelector('[data-action="close"]').addEventListener('click', close);
+  overlay.addEventListener('click', (e) => {
+    if (e.target === overlay) close();
+  });
+
+  renderApps();
+  overlay.appendChild(modal);
+  document.body.appendChild(overlay);
+  field('name').focus();
+}
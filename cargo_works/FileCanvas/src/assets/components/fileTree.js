@@ -9,6 +9,7 @@ import { showRenameModal } from './uiUtils.js';
 import { showItemContextMenu } from './showItemContextMenu.js';
 const folderCache = new Map();
 import { showPropertiesModal } from './uiUtils.js';
+import { createWithRename, describeError } from './uiUtils.js';
 const panelSortOrder = new Map();
 
 export { folderCache, panelSortOrder };
@@ -188,7 +189,8 @@ export function renderTree(items) {
             
             try {
               console.log(item.path);
-              await fileapi.createFolder(item.path, name.trim());
+              const created = await createWithRename(n => fileapi.createFolder(item.path, n), name.trim(), "New Folder");
+              if (!created) return;
     
               // invalidate cache
               folderCache.delete(item.path);
@@ -197,10 +199,10 @@ export function renderTree(items) {
               await refreshFolderNode(item.path, li, fileapi);
               redrawAllConnections();
     
-              showSnackbar(`Created folder "${name}"`, "success");
+              showSnackbar(`Created folder "${created}"`, "success");
             } catch (err) {
               console.error("Failed to create folder:", err);
-              showSnackbar(`Failed to create folder`, "error");
+              showSnackbar(`Failed to create folder: ${describeError(err)}`, "error");
             }
           });
     
@@ -216,7 +218,8 @@ export function renderTree(items) {
             }
             
             try {
-              await fileapi.createFile(item.path, name.trim());
+              const created = await createWithRename(n => fileapi.createFile(item.path, n), name.trim(), "New File");
+              if (!created) return;
               // invalidate cache
               folderCache.delete(item.path);
     
@@ -225,10 +228,10 @@ export function renderTree(items) {
               redrawAllConnections();
     
     
-              showSnackbar(`Created file "${name}"`, "success");
+              showSnackbar(`Created file "${created}"`, "success");
             } catch (err) {
               console.error("Failed to create file:", err);
-              showSnackbar(`Failed to create file`, "error");
+              showSnackbar(`Failed to create file: ${describeError(err)}`, "error");
             }
           });
     
@@ -258,7 +261,7 @@ export function renderTree(items) {
               showSnackbar(`Deleted "${item.name}"`, "success");
             } catch (err) {
               console.error("Failed to delete item:", err);
-              showSnackbar(`Failed to delete "${item.name}"`, "error");
+              showSnackbar(`Failed to delete "${item.name}": ${describeError(err)}`, "error");
             }
           });
     
@@ -296,7 +299,7 @@ export function renderTree(items) {
             await fileapi.openFile(item.path);
           } catch (err) {
             console.error("Open failed:", err);
-            showSnackbar(`Could not open "${item.name}"`, "error");
+            showSnackbar(`Could not open "${item.name}": ${describeError(err)}`, "error");
           }
         });
 
@@ -327,7 +330,7 @@ export function renderTree(items) {
 
             } catch (err) {
               console.error("Failed to delete item:", err);
-              showSnackbar(`Failed to delete "${item.name}"`, "error");
+              showSnackbar(`Failed to delete "${item.name}": ${describeError(err)}`, "error");
             }
           });
     
@@ -356,6 +359,12 @@ window.addEventListener('file-renamed', async (e) => {
     await refreshPanelByRootPath(parentDir, fileapi);
   }
 });
+// Auto-refresh panels when a folder's contents were rearranged (organizer, hot folders…)
+window.addEventListener('folder-contents-changed', async (e) => {
+  const { path } = e.detail;
+  folderCache.delete(path);
+  await refreshPanelByRootPath(path, fileapi);
+});
 window.addEventListener('open-folder', (e) => {
   const { path } = e.detail;
   const label = document.querySelector(`[data-folder-path="${path}"]`);
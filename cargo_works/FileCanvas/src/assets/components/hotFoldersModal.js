@@ -0,0 +1,207 @@
+// public/assets/components/hotFoldersModal.js
+// Hot folder rules: act automatically when files land in a watched folder.
+// The same rules are used by the headless `filecanvas --hot-folders` process.
+import { fileapi } from '../fileapi.js';
+import { showSnackbar } from './uiUtils.js';
+
+const splitList = (value) =>
+  value.split(',').map(s => s.trim()).filter(Boolean);
+
+function describeAction(action) {
+  switch (action.kind) {
+    case 'move': return `Move to ${action.destination}`;
+    case 'copy': return `Copy to ${action.destination}`;
+    case 'rename': return `Rename to ${action.template}`;
+    case 'command': return `Run ${action.program} ${action.args.join(' ')}`;
+    default: return action.kind;
+  }
+}
+
+function readAction(form) {
+  const kind = form.querySelector('[name="action"]').value;
+  const value = form.querySelector('[name="value"]').value.trim();
+  switch (kind) {
+    case 'move':
+    case 'copy':
+      return { kind, destination: value };
+    case 'rename':
+      return { kind, template: value || '{date}_{name}' };
+    default: {
+      // first word is the program, the rest are arguments (no shell involved)
+      const [program, ...args] = value.split(/\s+/).filter(Boolean);
+      return { kind: 'command', program: program || '', args: args.length ? args : ['{path}'] };
+    }
+  }
+}
+
+export async function showHotFoldersModal() {
+  let rules = await fileapi.listHotFolderRules();
+
+  const overlay = document.createElement('div');
+  overlay.className = 'fixed inset-0 bg-black/50 z-[40000] flex items-center justify-center p-4';
+
+  const modal = document.createElement('div');
+  modal.className = 'bg-white rounded-xl shadow-2xl w-full max-w-3xl max-h-[90vh] flex flex-col p-4 overflow-hidden';
+  modal.innerHTML = `
+    <div class="flex items-center justify-between mb-3">
+      <h2 class="font-semibold text-base">Hot folders</h2>
+      <div class="flex items-center gap-2 text-sm">
+        <span class="status text-gray-500"></span>
+        <button type="button" data-action="toggle" class="px-3 py-1.5 rounded text-white"></button>
+      </div>
+    </div>
+
+    <div class="rules overflow-auto mb-3 border border-gray-200 rounded divide-y divide-gray-100"></div>
+
+    <form class="grid grid-cols-2 gap-2 text-sm mb-3">
+      <input name="name" placeholder="Rule name" class="px-2 py-1 border border-gray-300 rounded" required />
+      <div class="flex gap-1">
+        <input name="folder" placeholder="Watched folder" class="flex-1 px-2 py-1 border border-gray-300 rounded" required />
+        <button type="button" data-action="pick" class="px-2 bg-gray-200 hover:bg-gray-300 rounded"><i class="fas fa-folder-open"></i></button>
+      </div>
+      <input name="extensions" placeholder="Extensions, e.g. pdf, jpg (empty = all)" class="px-2 py-1 border border-gray-300 rounded" />
+      <label class="flex items-center gap-2">
+        <input type="checkbox" name="recursive" /> Include sub folders
+        <span class="ml-auto">Settle</span>
+        <input name="settle" type="number" min="0" value="3" class="w-14 px-1 py-1 border border-gray-300 rounded" />s
+      </label>
+      <select name="action" class="px-2 py-1 border border-gray-300 rounded">
+        <option value="move">Move to folder</option>
+        <option value="copy">Copy to folder</option>
+        <option value="rename">Rename ({stem}, {ext}, {date}, {time})</option>
+        <option value="command">Run command ({path}, {dir}, {name})</option>
+      </select>
+      <input name="value" placeholder="Destination / template / command" class="px-2 py-1 border border-gray-300 rounded" required />
+      <button type="submit" class="col-span-2 px-3 py-1.5 bg-indigo-600 hover:bg-indigo-700 text-white rounded">Add rule</button>
+    </form>
+
+    <div class="text-xs font-semibold text-gray-600 mb-1">Recent activity</div>
+    <ul class="activity text-xs overflow-auto max-h-40 border border-gray-200 rounded p-2 space-y-0.5"></ul>
+
+    <div class="text-xs text-gray-500 mt-2">
+      Run <code>filecanvas --hot-folders</code> to keep watching without the window open.
+    </div>
+    <div class="flex justify-end mt-3">
+      <button type="button" data-action="close" class="px-3 py-1.5 text-sm bg-gray-200 hover:bg-gray-300 rounded">Close</button>
+    </div>
+  `;
+
+  const rulesEl = modal.querySelector('.rules');
+  const activityEl = modal.querySelector('.activity');
+  const statusEl = modal.querySelector('.status');
+  const toggleBtn = modal.querySelector('[data-action="toggle"]');
+  const form = modal.querySelector('form');
+
+  const renderRules = () => {
+    rulesEl.innerHTML = rules.length
+      ? ''
+      : '<div class="p-3 text-sm text-gray-500">No rules yet.</div>';
+    rules.forEach((rule, i) => {
+      const row = document.createElement('div');
+      row.className = 'flex items-center gap-2 px-3 py-2 text-sm';
+      row.innerHTML = `
+        <input type="checkbox" ${rule.enabled ? 'checked' : ''} data-index="${i}" data-action="enable" />
+        <div class="flex-1 min-w-0">
+          <div class="font-medium truncate">${rule.name}</div>
+          <div class="text-xs text-gray-500 truncate">${rule.folder} → ${describeAction(rule.action)}</div>
+        </div>
+        <button type="button" data-index="${i}" data-action="remove" class="text-red-500 hover:text-red-700">×</button>
+      `;
+      rulesEl.appendChild(row);
+    });
+  };
+
+  const renderStatus = async () => {
+    const status = await fileapi.hotFoldersStatus();
+    statusEl.textContent = status.running
+      ? 'Watching'
+      : status.running_elsewhere ? 'Running in background process' : 'Stopped';
+    toggleBtn.textContent = status.running ? 'Stop' : 'Start';
+    toggleBtn.disabled = status.running_elsewhere;
+    toggleBtn.className = `px-3 py-1.5 rounded text-white ${status.running ? 'bg-red-500 hover:bg-red-600' : 'bg-green-600 hover:bg-green-700'}`;
+    return status;
+  };
+
+  const renderActivity = async () => {
+    const entries = await fileapi.hotFolderActivity(50);
+    activityEl.innerHTML = entries.length
+      ? entries.map(a => `
+          <li class="truncate ${a.success ? '' : 'text-red-600'}">
+            ${new Date(a.timestamp).toLocaleString()} · ${a.rule_name} · ${a.action}
+            ${a.path.split('/').pop()}${a.destination ? ` → ${a.destination}` : ''}${a.detail ? ` (${a.detail})` : ''}
+          </li>`).join('')
+      : '<li class="text-gray-500">Nothing yet.</li>';
+  };
+
+  const save = async () => {
+    try {
+      await fileapi.saveHotFolderRules(rules);
+    } catch (err) {
+      showSnackbar(`Could not save rules: ${err}`, 'error');
+      rules = await fileapi.listHotFolderRules();
+    }
+    renderRules();
+  };
+
+  form.addEventListener('submit', async (e) => {
+    e.preventDefault();
+    const data = new FormData(form);
+    rules.push({
+      id: `hot-${Date.now()}`,
+      name: data.get('name').trim(),
+      folder: data.get('folder').trim(),
+      recursive: data.get('recursive') === 'on',
+      filters: { extensions: splitList(data.get('extensions')) },
+      action: readAction(form),
+      enabled: true,
+      settle_secs: parseInt(data.get('settle'), 10) || 0,
+    });
+    await save();
+    form.reset();
+  });
+
+  modal.addEventListener('click', async (e) => {
+    const el = e.target.closest('[data-action]');
+    if (!el) return;
+    const index = Number(el.dataset.index);
+
+    switch (el.dataset.action) {
+      case 'pick': {
+        const paths = await fileapi.pickFolder();
+        if (paths?.length) form.querySelector('[name="folder"]').value = paths[0];
+        break;
+      }
+      case 'enable':
+        rules[index].enabled = el.checked;
+        await save();
+        break;
+      case 'remove':
+        rules.splice(index, 1);
+        await save();
+        break;
+      case 'toggle': {
+        const status = await fileapi.hotFoldersStatus();
+        try {
+          if (status.running) await fileapi.stopHotFolders();
+          else await fileapi.startHotFolders();
+        } catch (err) {
+          showSnackbar(`${err}`, 'error');
+        }
+        await renderStatus();
+        break;
+      }
+      case 'close':
+        clearInterval(refresh);
+        overlay.remove();
+        break;
+    }
+  });
+
+  renderRules();
+  await renderStatus();
+  await renderActivity();
+  const refresh = setInterval(renderActivity, 3000);
+
+  overlay.appendChild(modal);
+  document.body.appendChild(overlay);
+}
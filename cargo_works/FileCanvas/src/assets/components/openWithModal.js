@@ -1,12 +1,13 @@
 import { fileapi } from '../fileapi.js';
-import { showSnackbar } from './uiUtils.js';
+import { describeError, showSnackbar } from './uiUtils.js';
+import { showCustomAppsModal } from './customAppsModal.js';
 
 export async function showOpenWithModal(filePath, fileName) {
   let apps;
   try {
     apps = await fileapi.listOpenWithApps(filePath);
   } catch (e) {
-    showSnackbar("Failed to load applications", "error");
+    showSnackbar(`Failed to load applications: ${describeError(e)}`, "error");
     return;
   }
 
@@ -15,8 +16,12 @@ export async function showOpenWithModal(filePath, fileName) {
     return;
   }
 
-  const recommended = apps.filter(app => app.is_recommended);
-  const others = apps.filter(app => !app.is_recommended);
+  // Apps used before for this file type get their own group, best first
+  const recent = apps
+    .filter(app => app.recent_rank != null)
+    .sort((a, b) => a.recent_rank - b.recent_rank);
+  const recommended = apps.filter(app => app.is_recommended && app.recent_rank == null);
+  const others = apps.filter(app => !app.is_recommended && app.recent_rank == null);
 
   const overlay = document.createElement("div");
   overlay.className =
@@ -31,6 +36,17 @@ export async function showOpenWithModal(filePath, fileName) {
       <h2 class="font-semibold text-base truncate">Open "${fileName}" with</h2>
     </div>
 
+    <!-- Recently used apps -->
+    <div class="recent-section ${recent.length ? '' : 'hidden'}">
+      <div class="px-4 py-2 bg-emerald-900/30 border-b border-emerald-700/30">
+        <div class="text-xs font-semibold text-emerald-300 uppercase tracking-wide flex items-center gap-2">
+          <i class="fas fa-history text-emerald-400"></i>
+          Recent
+        </div>
+      </div>
+      <div class="recent-list divide-y divide-gray-700"></div>
+    </div>
+
     <!-- Recommended apps -->
     <div class="recommended-section ${recommended.length ? '' : 'hidden'}">
       <div class="px-4 py-2 bg-indigo-900/30 border-b border-indigo-700/30">
@@ -62,19 +78,34 @@ export async function showOpenWithModal(filePath, fileName) {
     </div>
 
     <!-- Cancel button -->
-    <div class="px-4 py-2 border-t border-gray-700 bg-gray-900/50 text-right">
+    <div class="px-4 py-2 border-t border-gray-700 bg-gray-900/50 flex items-center justify-between gap-2">
+      <button class="custom-apps-btn px-2 py-1 text-xs text-gray-400 hover:text-gray-200 rounded hover:bg-gray-700 transition" title="Add your own commands and scripts">
+        <i class="fas fa-code mr-1"></i>Custom…
+      </button>
+      <label class="terminal-setting hidden text-xs text-gray-400 items-center gap-2">
+        <i class="fas fa-terminal"></i>
+        <select class="bg-gray-700 border border-gray-600 rounded px-1 py-0.5 text-gray-200"></select>
+      </label>
       <button class="px-3 py-1.5 text-sm text-gray-400 hover:text-gray-200 cancel-btn rounded hover:bg-gray-700 transition">
         Cancel
       </button>
     </div>
   `;
 
+  const recentListEl = modal.querySelector(".recent-list");
+  const recentSection = modal.querySelector(".recent-section");
   const recommendedListEl = modal.querySelector(".recommended-list");
   const otherListEl = modal.querySelector(".other-list");
   const searchInput = modal.querySelector("input");
   const recommendedSection = modal.querySelector(".recommended-section");
   const otherSection = modal.querySelector(".other-section");
 
+  // User-defined commands (customAppsModal.js) can't become the system default
+  const isCustom = (app) => app.id.startsWith("custom:");
+
+  // Icon names resolved to data URLs by the backend; filled in once loaded
+  let iconUrls = {};
+
   function createAppButton(app, isRecommended = false) {
     const btn = document.createElement("button");
     btn.className = `w-full text-left px-4 py-3 hover:bg-gray-700 transition flex items-center gap-3`;
@@ -83,24 +114,63 @@ export async function showOpenWithModal(filePath, fileName) {
       <div class="flex-shrink-0 w-8 h-8 rounded-lg flex items-center justify-center ${
         isRecommended ? 'bg-indigo-900/50 text-indigo-400' : 'bg-gray-700 text-gray-300'
       }">
-        <i class="fas fa-rocket text-sm"></i>
+        ${iconUrls[app.icon]
+          ? `<img src="${iconUrls[app.icon]}" alt="" class="w-6 h-6 object-contain" />`
+          : `<i class="fas ${isCustom(app) ? 'fa-code' : 'fa-rocket'} text-sm"></i>`}
       </div>
       <div class="flex-1 min-w-0">
         <div class="font-medium text-sm truncate group-hover:text-indigo-300 transition">
           ${app.name}
+          ${app.is_default ? '<span class="ml-1 text-xs text-indigo-300">(default)</span>' : ''}
+          ${app.is_always ? '<span class="ml-1 text-xs text-emerald-300">(always)</span>' : ''}
         </div>
       </div>
+      ${app.is_default || app.action || isCustom(app) ? '' : `
+        <span class="set-default text-gray-500 hover:text-indigo-300 text-xs px-1" title="Always open this file type with ${app.name}">
+          <i class="fas fa-thumbtack"></i>
+        </span>`}
+      <span class="always-use ${app.is_always ? 'text-emerald-300' : 'text-gray-500'} hover:text-emerald-300 text-xs px-1"
+        title="${app.is_always ? 'Stop always using' : 'Always use'} ${app.name} for this file type in FileCanvas">
+        <i class="fas fa-check-circle"></i>
+      </span>
       <i class="fas fa-arrow-right text-gray-500 text-sm"></i>
     `;
 
+    btn.querySelector(".always-use").addEventListener("click", async (e) => {
+      e.stopPropagation();
+      const enable = !app.is_always;
+      try {
+        const type = await fileapi.setAlwaysUseApp(filePath, enable ? app.id : null, enable ? app.exec : null);
+        apps.forEach(a => { a.is_always = enable && a.id === app.id; });
+        showSnackbar(enable
+          ? `${type} files will open with ${app.name}`
+          : `${type} files will open with the system default`, "success");
+        searchInput.dispatchEvent(new Event("input"));
+      } catch (error) {
+        showSnackbar(`Could not save choice: ${error}`, "error");
+      }
+    });
+
+    btn.querySelector(".set-default")?.addEventListener("click", async (e) => {
+      e.stopPropagation();
+      try {
+        const mime = await fileapi.setDefaultApp(app.id, filePath);
+        apps.forEach(a => { a.is_default = a.id === app.id; });
+        showSnackbar(`${app.name} is now the default for ${mime}`, "success");
+        searchInput.dispatchEvent(new Event("input"));
+      } catch (error) {
+        showSnackbar(`Could not set default: ${describeError(error)}`, "error");
+      }
+    });
+
     btn.addEventListener("click", async () => {
       try {
         console.log(`Attempting to open ${filePath} with ${app.name} (exec: ${app.exec})`);
-        await fileapi.openWithApp(app.exec, filePath);
+        await fileapi.openWithApp(app.exec, filePath, app.id);
         showSnackbar(`Opening with ${app.name}...`, "success");
       } catch (error) {
         console.error(`Failed to open with ${app.name}:`, error);
-        showSnackbar(`Failed to open with ${app.name}`, "error");
+        showSnackbar(`Failed to open with ${app.name}: ${describeError(error)}`, "error");
       } finally {
         overlay.remove();
       }
@@ -109,7 +179,13 @@ export async function showOpenWithModal(filePath, fileName) {
     return btn;
   }
 
-  function renderLists(filteredRecommended, filteredOthers) {
+  function renderLists(filteredRecent, filteredRecommended, filteredOthers) {
+    recentListEl.innerHTML = "";
+    recentSection.classList.toggle('hidden', !filteredRecent.length);
+    filteredRecent.forEach(app => {
+      recentListEl.appendChild(createAppButton(app, true));
+    });
+
     recommendedListEl.innerHTML = "";
     if (filteredRecommended.length) {
       recommendedSection.classList.remove('hidden');
@@ -126,7 +202,7 @@ export async function showOpenWithModal(filePath, fileName) {
       filteredOthers.forEach(app => {
         otherListEl.appendChild(createAppButton(app, false));
       });
-    } else if (filteredRecommended.length === 0) {
+    } else if (filteredRecommended.length === 0 && filteredRecent.length === 0) {
       otherListEl.innerHTML = `
         <div class="px-4 py-8 text-center text-gray-500 text-sm">
           <i class="fas fa-search text-2xl text-gray-600 mb-2"></i>
@@ -139,17 +215,64 @@ export async function showOpenWithModal(filePath, fileName) {
     }
   }
 
-  renderLists(recommended, others);
+  renderLists(recent, recommended, others);
+
+  // Console apps (Terminal=true) run inside a terminal emulator; let the user pick it
+  if (apps.some(app => app.terminal)) {
+    const label = modal.querySelector(".terminal-setting");
+    const select = label.querySelector("select");
+    fileapi.terminalStatus()
+      .then(status => {
+        select.innerHTML = [
+          `<option value="">Auto${status.configured ? '' : ` (${status.resolved || 'none found'})`}</option>`,
+          ...status.available.map(t => `<option value="${t}">${t}</option>`),
+          ...(status.configured && !status.available.includes(status.configured)
+            ? [`<option value="${status.configured}">${status.configured}</option>`]
+            : []),
+        ].join("");
+        select.value = status.configured || "";
+        label.title = "Terminal used for console apps";
+        label.classList.remove("hidden");
+        label.classList.add("flex");
+      })
+      .catch(err => console.warn("Could not detect terminals:", err));
+
+    select.addEventListener("change", async () => {
+      try {
+        await fileapi.setTerminal(select.value || null);
+      } catch (error) {
+        showSnackbar(`${error}`, "error");
+      }
+    });
+  }
+
+  const iconNames = apps.map(app => app.icon).filter(Boolean);
+  if (iconNames.length) {
+    fileapi.resolveIcons(iconNames, 32)
+      .then(urls => {
+        iconUrls = urls;
+        searchInput.dispatchEvent(new Event("input"));
+      })
+      .catch(err => console.warn("Could not load app icons:", err));
+  }
 
   searchInput.addEventListener("input", () => {
     const q = searchInput.value.trim().toLowerCase();
     if (!q) {
-      renderLists(recommended, others);
+      renderLists(recent, recommended, others);
       return;
     }
+    const filteredRecent = recent.filter(app => app.name.toLowerCase().includes(q));
     const filteredRecommended = recommended.filter(app => app.name.toLowerCase().includes(q));
     const filteredOthers = others.filter(app => app.name.toLowerCase().includes(q));
-    renderLists(filteredRecommended, filteredOthers);
+    renderLists(filteredRecent, filteredRecommended, filteredOthers);
+  });
+
+  // Reopen afterwards so new or edited commands show up in the list
+  modal.querySelector(".custom-apps-btn").addEventListener("click", () => {
+    overlay.remove();
+    showCustomAppsModal({ onClose: () => showOpenWithModal(filePath, fileName) })
+      .catch(err => showSnackbar(`Could not load custom commands: ${err}`, "error"));
   });
 
   modal.querySelector(".cancel-btn").addEventListener("click", () => {
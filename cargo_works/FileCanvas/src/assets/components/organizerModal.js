@@ -0,0 +1,180 @@
+// public/assets/components/organizerModal.js
+// Rule-based auto-organizer: sorts a messy folder (Downloads, scanner inbox…)
+// into sub folders. Preview runs the backend in dry-run mode.
+import { fileapi } from '../fileapi.js';
+import { showSnackbar, showConfirmModal } from './uiUtils.js';
+
+const PRESETS = {
+  category: [{ kind: 'category' }],
+  date: [{ kind: 'date_pattern', pattern: '{year}/{month}' }],
+  category_date: [{ kind: 'date_pattern', pattern: '{category}/{year}/{month}' }],
+};
+
+function ruleRow(rule = { kind: 'category' }) {
+  const row = document.createElement('div');
+  row.className = 'rule-row flex gap-2 items-center mb-2';
+  row.innerHTML = `
+    <select name="kind" class="px-2 py-1 text-sm border border-gray-300 rounded">
+      <option value="category">By type</option>
+      <option value="date_pattern">By date</option>
+      <option value="regex">By name (regex)</option>
+    </select>
+    <input name="pattern" placeholder="" class="flex-1 px-2 py-1 text-sm border border-gray-300 rounded" />
+    <input name="target" placeholder="Target, e.g. Invoices/$1" class="flex-1 px-2 py-1 text-sm border border-gray-300 rounded" />
+    <button type="button" data-action="remove" class="px-2 text-red-500 hover:text-red-700">×</button>
+  `;
+
+  const kind = row.querySelector('[name="kind"]');
+  const pattern = row.querySelector('[name="pattern"]');
+  const target = row.querySelector('[name="target"]');
+
+  const sync = () => {
+    pattern.classList.toggle('hidden', kind.value === 'category');
+    target.classList.toggle('hidden', kind.value !== 'regex');
+    pattern.placeholder = kind.value === 'regex' ? 'Regex, e.g. ^invoice_(\\d+)' : '{year}/{month}';
+  };
+
+  kind.value = rule.kind;
+  pattern.value = rule.pattern || '';
+  target.value = rule.target || '';
+  kind.addEventListener('change', sync);
+  row.querySelector('[data-action="remove"]').addEventListener('click', () => row.remove());
+  sync();
+  return row;
+}
+
+function readRules(container) {
+  return Array.from(container.querySelectorAll('.rule-row')).map(row => {
+    const kind = row.querySelector('[name="kind"]').value;
+    const pattern = row.querySelector('[name="pattern"]').value.trim();
+    const target = row.querySelector('[name="target"]').value.trim();
+    if (kind === 'category') return { kind };
+    if (kind === 'date_pattern') return { kind, pattern: pattern || '{year}/{month}' };
+    return { kind, pattern, target };
+  });
+}
+
+function renderRun(run) {
+  const list = run.moves
+    .slice(0, 200)
+    .map(m => `<li class="truncate"><span class="text-gray-500">${m.from.split('/').pop()}</span> → ${m.to.substring(run.folder.length + 1)}${m.renamed ? ' <span class="text-yellow-600">(renamed)</span>' : ''}${m.replaced ? ' <span class="text-red-600">(replaced an existing file, kept for undo)</span>' : ''}</li>`)
+    .join('');
+  const more = run.moves.length > 200 ? `<li class="text-gray-500">…and ${run.moves.length - 200} more</li>` : '';
+  const errors = run.errors.map(e => `<li class="text-red-600 truncate">${e}</li>`).join('');
+  return `
+    <div class="text-xs font-semibold mb-1">${run.dry_run ? 'Preview' : 'Done'}: ${run.moves.length} move(s), ${run.skipped.length} skipped</div>
+    <ul class="text-xs space-y-0.5">${list}${more}${errors}</ul>
+  `;
+}
+
+export function showOrganizerModal(folderPath, onDone = () => {}) {
+  const overlay = document.createElement('div');
+  overlay.className = 'fixed inset-0 bg-black/50 z-[40000] flex items-center justify-center p-4';
+
+  const modal = document.createElement('div');
+  modal.className = 'bg-white rounded-xl shadow-2xl w-full max-w-2xl max-h-[85vh] flex flex-col p-4';
+  modal.innerHTML = `
+    <h2 class="font-semibold text-base mb-1">Organize folder</h2>
+    <div class="text-xs text-gray-500 mb-3 truncate">${folderPath}</div>
+
+    <div class="flex gap-2 mb-3 text-sm">
+      <span class="text-gray-600">Presets:</span>
+      <button type="button" data-preset="category" class="text-indigo-600 hover:underline">By type</button>
+      <button type="button" data-preset="date" class="text-indigo-600 hover:underline">By year/month</button>
+      <button type="button" data-preset="category_date" class="text-indigo-600 hover:underline">Type + date</button>
+    </div>
+
+    <div class="text-xs text-gray-500 mb-1">Rules are tried in order; the first match wins.</div>
+    <div class="rules mb-2"></div>
+    <button type="button" data-action="add" class="self-start text-sm text-indigo-600 hover:underline mb-3">+ Add rule</button>
+
+    <div class="flex gap-4 text-sm mb-3">
+      <label class="flex items-center gap-1"><input type="checkbox" name="recursive" /> Include sub folders</label>
+      <label class="flex items-center gap-1">On name collision
+        <select name="collision" class="px-1 py-0.5 border border-gray-300 rounded">
+          <option value="rename">Keep both</option>
+          <option value="skip">Skip</option>
+          <option value="overwrite">Overwrite</option>
+        </select>
+      </label>
+    </div>
+
+    <div class="result flex-1 overflow-auto border border-gray-200 rounded p-2 mb-3 min-h-[80px] text-gray-700"></div>
+
+    <div class="flex justify-between gap-2">
+      <button type="button" data-action="undo" class="px-3 py-1.5 text-sm text-gray-700 hover:bg-gray-100 rounded">Undo last run</button>
+      <div class="flex gap-2">
+        <button type="button" data-action="close" class="px-3 py-1.5 text-sm bg-gray-200 hover:bg-gray-300 rounded">Close</button>
+        <button type="button" data-action="preview" class="px-3 py-1.5 text-sm bg-indigo-100 text-indigo-700 hover:bg-indigo-200 rounded">Preview</button>
+        <button type="button" data-action="run" class="px-3 py-1.5 text-sm bg-indigo-600 hover:bg-indigo-700 text-white rounded">Organize</button>
+      </div>
+    </div>
+  `;
+
+  const rules = modal.querySelector('.rules');
+  const result = modal.querySelector('.result');
+  const setRules = (list) => {
+    rules.innerHTML = '';
+    list.forEach(r => rules.appendChild(ruleRow(r)));
+  };
+  setRules(PRESETS.category);
+
+  const options = (dryRun) => ({
+    dry_run: dryRun,
+    recursive: modal.querySelector('[name="recursive"]').checked,
+    collision: modal.querySelector('[name="collision"]').value,
+  });
+
+  const run = async (dryRun) => {
+    try {
+      const res = await fileapi.organizeFolder(folderPath, readRules(rules), options(dryRun));
+      result.innerHTML = renderRun(res);
+      if (!dryRun) onDone();
+    } catch (err) {
+      showSnackbar(`Organize failed: ${err}`, 'error');
+    }
+  };
+
+  modal.addEventListener('click', async (e) => {
+    const btn = e.target.closest('button');
+    if (!btn) return;
+    if (btn.dataset.preset) {
+      setRules(PRESETS[btn.dataset.preset]);
+      return;
+    }
+    switch (btn.dataset.action) {
+      case 'add':
+        rules.appendChild(ruleRow());
+        break;
+      case 'preview':
+        await run(true);
+        break;
+      case 'run':
+        await run(false);
+        break;
+      case 'undo': {
+        const runs = await fileapi.listOrganizeRuns(folderPath);
+        const last = runs.find(r => !r.undone);
+        if (!last) {
+          showSnackbar('Nothing to undo for this folder', 'warning');
+          break;
+        }
+        const ok = await showConfirmModal(
+          `Undo the run from ${new Date(last.started_at).toLocaleString()} (${last.moves.length} file(s))?`
+        );
+        if (!ok) break;
+        const undo = await fileapi.undoOrganizeRun(last.id);
+        showSnackbar(`Restored ${undo.restored} file(s)`, undo.errors.length ? 'warning' : 'success');
+        result.innerHTML = undo.errors.map(e => `<div class="text-xs text-red-600">${e}</div>`).join('');
+        onDone();
+        break;
+      }
+      case 'close':
+        overlay.remove();
+        break;
+    }
+  });
+
+  overlay.appendChild(modal);
+  document.body.appendChild(overlay);
+}
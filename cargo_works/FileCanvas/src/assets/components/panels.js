@@ -1,7 +1,7 @@
 // public/assets/components/panels.js
 
 import { folderCache, panelSortOrder, refreshFolderNode, refreshPanelByRootPath, renderTree } from './fileTree.js';
-import { showSnackbar, showInputModal, createTrashIcon } from './uiUtils.js';
+import { showSnackbar, showInputModal, createTrashIcon, createWithRename, describeError } from './uiUtils.js';
 import { sortItems } from './sorting.js';
 import { fileapi } from '../fileapi.js';
 import { activeArrow } from './connections.js';
@@ -192,7 +192,8 @@ if (addRootBtn) {
     
     try {
       const rootPath = panel.querySelector('input').value.trim();
-      await fileapi.createFolder(rootPath, name.trim());
+      const created = await createWithRename(n => fileapi.createFolder(rootPath, n), name.trim(), "New Folder");
+      if (!created) return;
       folderCache.delete(rootPath); // invalidate cache
       const treeContainer = panel.querySelector(".tree-container");
       treeContainer.textContent = "Loading...";
@@ -201,10 +202,10 @@ if (addRootBtn) {
       treeContainer.textContent = "";
       treeContainer.appendChild(renderTree(tree));
       redrawFn();
-      showSnackbar(`Created folder "${name}"`, "success");
+      showSnackbar(`Created folder "${created}"`, "success");
     } catch (err) {
       console.error("Failed to create folder:", err);
-      showSnackbar("Failed to create folder", "error");
+      showSnackbar(`Failed to create folder: ${describeError(err)}`, "error");
     }
   });
 
@@ -221,7 +222,8 @@ if (addRootBtn) {
     
     try {
       const rootPath = panel.querySelector('input').value.trim();
-      await fileapi.createFile(rootPath, name.trim());
+      const created = await createWithRename(n => fileapi.createFile(rootPath, n), name.trim(), "New File");
+      if (!created) return;
       folderCache.delete(rootPath);
       const treeContainer = panel.querySelector(".tree-container");
       treeContainer.textContent = "Loading...";
@@ -230,10 +232,10 @@ if (addRootBtn) {
       treeContainer.textContent = "";
       treeContainer.appendChild(renderTree(tree));
       redrawFn();
-      showSnackbar(`Created file "${name}"`, "success");
+      showSnackbar(`Created file "${created}"`, "success");
     } catch (err) {
       console.error("Failed to create file:", err);
-      showSnackbar("Failed to create file", "error");
+      showSnackbar(`Failed to create file: ${describeError(err)}`, "error");
     }
   });
 }
@@ -0,0 +1,146 @@
+// public/assets/components/remoteModal.js
+// Connect to an SFTP server, S3 bucket or WebDAV share and open one of its
+// folders as a panel. Leave the login empty to use saved credentials,
+// ssh-agent (SFTP) or the AWS_* environment variables (S3).
+import { fileapi } from '../fileapi.js';
+import { showConfirmModal, describeError } from './uiUtils.js';
+
+const orNull = (value) => value.trim() || null;
+
+// Resolves to { url, type } once connected, or null when cancelled
+export function showRemoteModal() {
+  return new Promise((resolve) => {
+    const overlay = document.createElement('div');
+    // below confirmModal so the host key question shows on top
+    overlay.className = 'fixed inset-0 bg-black/50 z-[20000] flex items-center justify-center p-4';
+
+    const modal = document.createElement('div');
+    modal.className = 'bg-white rounded-xl shadow-2xl w-full max-w-md p-4';
+    modal.innerHTML = `
+      <h2 class="font-semibold text-base mb-3">Remote folder</h2>
+      <form class="grid gap-2 text-sm">
+        <div class="flex gap-2">
+          <select name="protocol" class="px-2 py-1 border border-gray-300 rounded">
+            <option value="sftp">SFTP</option>
+            <option value="s3">S3</option>
+            <option value="webdav">WebDAV</option>
+          </select>
+          <input name="url" class="flex-1 px-2 py-1 border border-gray-300 rounded font-mono" required />
+        </div>
+        <input name="password" type="password" placeholder="Password" class="password-field px-2 py-1 border border-gray-300 rounded" />
+        <div class="sftp-fields grid gap-2">
+          <input name="keyPath" placeholder="Private key file, e.g. ~/.ssh/id_ed25519" class="px-2 py-1 border border-gray-300 rounded font-mono" />
+          <input name="passphrase" type="password" placeholder="Key passphrase" class="px-2 py-1 border border-gray-300 rounded" />
+          <div class="text-xs text-gray-500">Leave both empty to use saved credentials or ssh-agent.</div>
+        </div>
+        <div class="s3-fields hidden grid gap-2">
+          <input name="accessKey" placeholder="Access key ID" class="px-2 py-1 border border-gray-300 rounded font-mono" />
+          <input name="secretKey" type="password" placeholder="Secret access key" class="px-2 py-1 border border-gray-300 rounded" />
+          <input name="region" placeholder="Region (default us-east-1)" class="px-2 py-1 border border-gray-300 rounded" />
+          <div class="text-xs text-gray-500">Use s3+http:// for a server without TLS, e.g. a local MinIO. Leave the key empty to use the saved one or AWS_* variables.</div>
+        </div>
+        <div class="webdav-fields hidden text-xs text-gray-500">Use dav:// for a server without TLS. For Nextcloud, create an app password and use the WebDAV address from Files → Settings.</div>
+        <div class="flex items-center gap-4">
+          <label class="flex items-center gap-2"><input type="checkbox" name="remember" /> Remember in keychain</label>
+          <select name="type" class="ml-auto px-2 py-1 border border-gray-300 rounded">
+            <option value="source">Source</option>
+            <option value="destination">Destination</option>
+          </select>
+        </div>
+        <div class="error hidden text-xs text-red-600"></div>
+        <div class="flex justify-end gap-2 mt-1">
+          <button type="button" data-action="cancel" class="px-3 py-1.5 text-gray-600 hover:bg-gray-100 rounded">Cancel</button>
+          <button type="submit" class="px-3 py-1.5 bg-emerald-600 hover:bg-emerald-700 text-white rounded">Connect</button>
+        </div>
+      </form>
+    `;
+
+    const form = modal.querySelector('form');
+    const errorEl = modal.querySelector('.error');
+    const submitBtn = form.querySelector('[type="submit"]');
+    const urlInput = form.querySelector('[name="url"]');
+    const protocolSelect = form.querySelector('[name="protocol"]');
+
+    const placeholders = {
+      sftp: 'sftp://user@host:22/home/user',
+      s3: 's3://s3.amazonaws.com/bucket/prefix',
+      webdav: 'davs://user@cloud.example.com/remote.php/dav/files/user',
+    };
+    const showProtocol = () => {
+      const protocol = protocolSelect.value;
+      urlInput.placeholder = placeholders[protocol];
+      modal.querySelector('.sftp-fields').classList.toggle('hidden', protocol !== 'sftp');
+      modal.querySelector('.s3-fields').classList.toggle('hidden', protocol !== 's3');
+      modal.querySelector('.webdav-fields').classList.toggle('hidden', protocol !== 'webdav');
+      modal.querySelector('.password-field').classList.toggle('hidden', protocol === 's3');
+    };
+    protocolSelect.addEventListener('change', showProtocol);
+    showProtocol();
+
+    const close = (result) => {
+      overlay.remove();
+      resolve(result);
+    };
+
+    const showError = (message) => {
+      errorEl.textContent = message;
+      errorEl.classList.toggle('hidden', !message);
+    };
+
+    const connect = async (trust = null) => {
+      const data = new FormData(form);
+      const url = data.get('url').trim();
+      const remember = data.get('remember') === 'on';
+      const protocol = data.get('protocol');
+      try {
+        let folder;
+        if (protocol === 's3') {
+          folder = await fileapi.connectS3(url, {
+            accessKey: orNull(data.get('accessKey')),
+            secretKey: data.get('secretKey'),
+            region: orNull(data.get('region')),
+            remember,
+          });
+        } else if (protocol === 'webdav') {
+          folder = await fileapi.connectWebdav(url, {
+            password: orNull(data.get('password')),
+            remember,
+          });
+        } else {
+          folder = await fileapi.connectRemote(url, {
+            password: orNull(data.get('password')),
+            keyPath: orNull(data.get('keyPath')),
+            passphrase: orNull(data.get('passphrase')),
+            remember,
+            trust,
+          });
+        }
+        close({ url: folder, type: data.get('type') });
+      } catch (err) {
+        if (err?.code === 'unknown_host' && !trust) {
+          const ok = await showConfirmModal(`${err.message} Trust this server and connect?`);
+          if (ok) return connect(err.detail);
+          showError('Not connected: the server was not trusted.');
+          return;
+        }
+        showError(describeError(err));
+      }
+    };
+
+    form.addEventListener('submit', async (e) => {
+      e.preventDefault();
+      showError('');
+      submitBtn.disabled = true;
+      submitBtn.textContent = 'Connecting…';
+      await connect();
+      submitBtn.disabled = false;
+      submitBtn.textContent = 'Connect';
+    });
+
+    modal.querySelector('[data-action="cancel"]').addEventListener('click', () => close(null));
+
+    overlay.appendChild(modal);
+    document.body.appendChild(overlay);
+    urlInput.focus();
+  });
+}
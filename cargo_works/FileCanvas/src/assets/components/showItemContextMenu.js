@@ -1,7 +1,9 @@
 import { showSnackbar } from "./uiUtils.js";
 import { showRenameModal } from "./uiUtils.js";
 import { showPropertiesModal } from "./uiUtils.js";
+import { describeError, errorAction } from "./uiUtils.js";
 import { showOpenWithModal } from "./openWithModal.js";
+import { showOrganizerModal } from "./organizerModal.js";
 import { fileapi } from "../fileapi.js";
 export function showItemContextMenu(e, item) {
   e.preventDefault();
@@ -35,6 +37,17 @@ export function showItemContextMenu(e, item) {
         : ''
     }
 
+    ${
+      is_directory
+        ? `
+      <button data-action="organize" class="menu-btn">
+        <i class="fas fa-magic"></i>
+        Organize...
+      </button>
+      `
+        : ''
+    }
+
     <hr class="my-1 border-gray-200">
 
     <button data-action="rename" class="menu-btn">
@@ -76,7 +89,11 @@ export function showItemContextMenu(e, item) {
               new CustomEvent('open-folder', { detail: { path } })
             );
           } else {
-            await fileapi.openFile(path);
+            try {
+              await fileapi.openFile(path);
+            } catch (err) {
+              showSnackbar(`Could not open "${name}": ${describeError(err)}`, 'error');
+            }
           }
           break;
 
@@ -84,18 +101,34 @@ export function showItemContextMenu(e, item) {
           showOpenWithModal(path, name);
           break;
 
-        case 'rename':
-          showRenameModal(name, async newName => {
-            const newPath = await fileapi.renameItem(path, newName);
-            showSnackbar(`Renamed to "${newName}"`, 'success');
-
+        case 'organize':
+          showOrganizerModal(path, () =>
             window.dispatchEvent(
-              new CustomEvent('file-renamed', {
-                detail: { oldPath: path, newPath }
-              })
-            );
-          });
+              new CustomEvent('folder-contents-changed', { detail: { path } })
+            )
+          );
+          break;
+
+        case 'rename': {
+          const rename = async newName => {
+            try {
+              const newPath = await fileapi.renameItem(path, newName);
+              showSnackbar(`Renamed to "${newName}"`, 'success');
+
+              window.dispatchEvent(
+                new CustomEvent('file-renamed', {
+                  detail: { oldPath: path, newPath }
+                })
+              );
+            } catch (err) {
+              showSnackbar(`Could not rename "${name}": ${describeError(err)}`, 'error');
+              // Name taken or invalid: let the user pick another one
+              if (errorAction(err) === 'rename') showRenameModal(newName, rename);
+            }
+          };
+          showRenameModal(name, rename);
           break;
+        }
 
         case 'properties':
           await showItemProperties(path, name);
@@ -103,7 +136,7 @@ export function showItemContextMenu(e, item) {
       }
     } catch (err) {
       console.error(err);
-      showSnackbar(`Action failed on "${name}"`, 'error');
+      showSnackbar(`Action failed on "${name}": ${describeError(err)}`, 'error');
     }
   });
 
@@ -134,9 +167,11 @@ async function showItemProperties(path, name) {
       <div><span class="font-medium">Type:</span> ${
         info.is_directory ? 'Folder' : 'File'
       }</div>
+      ${info.is_directory ? '' : `<div><span class="font-medium">MIME type:</span> ${info.mime_type}</div>`}
       <div>
         <span class="font-medium">Size:</span> ${formatBytes(info.size)}
       </div>
+      ${info.free_space == null ? '' : `<div><span class="font-medium">Free space:</span> ${formatBytes(info.free_space)} of ${formatBytes(info.total_space)}</div>`}
       <div><span class="font-medium">Created:</span> ${new Date(
         info.ctime
       ).toLocaleString()}</div>
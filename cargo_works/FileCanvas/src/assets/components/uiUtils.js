@@ -18,6 +18,76 @@ export function showSnackbar(message, type = 'error') {
   setTimeout(() => snackbar.classList.replace('opacity-100', 'opacity-0'), 3000);
 }
 
+// 1536 → "1.5 KB"; empty for a missing value
+export function formatBytes(bytes) {
+  if (bytes == null) return '';
+  const units = ['B', 'KB', 'MB', 'GB', 'TB'];
+  let i = 0;
+  let value = bytes;
+  while (value >= 1024 && i < units.length - 1) {
+    value /= 1024;
+    i++;
+  }
+  return `${value.toFixed(i ? 1 : 0)} ${units[i]}`;
+}
+
+// File and open-with commands reject with { code, path, errno, message,
+// exit_code?, stderr? }; older commands reject with plain strings
+export function describeError(err) {
+  if (!err || typeof err !== 'object') return `${err}`;
+  const lastLine = err.stderr?.trim().split('\n').pop();
+  return lastLine ? `${err.message}: ${lastLine}` : err.message;
+}
+
+// What the UI can offer for an error: 'rename' (pick another name),
+// 'choose_folder' (pick another destination), 'retry', or null
+export function errorAction(err) {
+  switch (err?.code) {
+    case 'already_exists':
+    case 'invalid_name':
+      return 'rename';
+    case 'permission_denied':
+    case 'read_only':
+    case 'no_space':
+    case 'outside_root':
+    case 'crosses_devices':
+      return 'choose_folder';
+    case 'busy':
+    case 'app_exited':
+      return 'retry';
+    default:
+      return null;
+  }
+}
+
+// "notes.txt" → "notes (2).txt", "notes (2).txt" → "notes (3).txt"
+export function nextFreeName(name) {
+  const dot = name.lastIndexOf('.');
+  const hasExt = dot > 0;
+  const stem = hasExt ? name.slice(0, dot) : name;
+  const ext = hasExt ? name.slice(dot) : '';
+  const match = stem.match(/^(.*) \((\d+)\)$/);
+  return match ? `${match[1]} (${Number(match[2]) + 1})${ext}` : `${stem} (2)${ext}`;
+}
+
+// Run `create(name)`; while the name is taken or invalid, ask for another
+// one (suggesting a free-looking name). Resolves to the name used, or null
+// if the user cancelled.
+export async function createWithRename(create, name, title) {
+  for (;;) {
+    try {
+      await create(name);
+      return name;
+    } catch (err) {
+      if (errorAction(err) !== 'rename') throw err;
+      const suggestion = err.code === 'already_exists' ? nextFreeName(name) : name;
+      const next = await showInputModal(title, `${describeError(err)}. Try another name:`, suggestion);
+      if (!next || !next.trim()) return null;
+      name = next.trim();
+    }
+  }
+}
+
 export function showConfirmModal(message) {
   return new Promise((resolve) => {
     const m = document.getElementById('confirmModal');
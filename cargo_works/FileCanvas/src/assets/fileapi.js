@@ -2,7 +2,10 @@
 const { invoke } = window.__TAURI__.core;
 
 export const fileapi = {
+    // Picked folders become approved roots; every other command is limited to them
     pickFolder: () => invoke('pick_folder'),
+    listApprovedRoots: () => invoke('list_approved_roots'),
+    revokeRoot: (root) => invoke('revoke_root', { root }),
     readFolder: (path) => invoke('read_folder', { folderPath: path }),
      isDir: (path) => invoke('is_dir', { path }), 
     createFolder: (path, folderName) =>
@@ -20,14 +23,48 @@ export const fileapi = {
         return await invoke('open_file', { path });
     },
 
+    // Resolves to [{ path, success, error }] in the same order as paths
+    openFiles: (paths) =>
+        invoke('open_files', { paths }),
+
     async openWith(path) {
         return await invoke('open_with', { path });
     },
     listOpenWithApps: (path) =>
     invoke('list_open_with_apps', { filePath: path }),
 
-    openWithApp: (exec, path) =>
-    invoke('open_with_app', { exec, filePath: path }),
+    openWithApp: (exec, path, appId = null) =>
+    invoke('open_with_app', { exec, filePath: path, appId }),
+
+    // One process for every file when the app takes a list (%F / %U)
+    openFilesWithApp: (exec, paths, appId = null) =>
+    invoke('open_files_with_app', { exec, filePaths: paths, appId }),
+
+    setDefaultApp: (appId, path) =>
+    invoke('set_default_app', { appId, filePath: path }),
+
+    // appId = null goes back to the system default for open_file
+    setAlwaysUseApp: (path, appId, exec = null) =>
+    invoke('set_always_use_app', { filePath: path, appId, exec }),
+
+    // app: { id ('' for new), name, command, file_types, terminal }
+    listCustomApps: () =>
+    invoke('list_custom_apps'),
+
+    saveCustomApp: (app) =>
+    invoke('save_custom_app', { app }),
+
+    deleteCustomApp: (id) =>
+    invoke('delete_custom_app', { id }),
+
+    resolveIcons: (icons, size = 32) =>
+    invoke('resolve_icons', { icons, size }),
+
+    terminalStatus: () =>
+    invoke('terminal_status'),
+
+    setTerminal: (command) =>
+    invoke('set_terminal', { command }),
     moveFile: (src, destFolder) => 
         invoke('move_file', { src, destFolder  }),
     copyFile: (src, destFolder) => 
@@ -41,4 +78,84 @@ export const fileapi = {
         invoke('rename_item', { oldPath, newName }),
     getFileInfo: (path) =>
         invoke('get_file_info', { path }),
+    // mode: 'mirror' | 'two_way', options: { deleteExtras, useHash }
+    // filters: rules from connectionFilters.js (snake_case keys, sent as-is)
+    // mode ('copy' | 'move') decides how much space the plan checks for
+    planTransfer: (src, destFolder, filters, mode = 'copy') =>
+        invoke('plan_transfer', { src, destFolder, filters, mode }),
+    // mode: 'copy' | 'move'
+    transferFiltered: (src, destFolder, mode, filters) =>
+        invoke('transfer_filtered', { src, destFolder, mode, filters }),
+    // connections: [{ id, from, to, filters? }]. Resolves to { errors, warnings, order }
+    // where order lists the connection ids that are safe to run, in order.
+    validateConnections: (connections, mode) =>
+        invoke('validate_connections', { connections, mode }),
+    planSync: (src, destFolder, mode, options = {}) =>
+        invoke('plan_sync', { src, destFolder, mode, options: toSyncOptions(options) }),
+    runSync: (src, destFolder, mode, options = {}) =>
+        invoke('run_sync', { src, destFolder, mode, options: toSyncOptions(options) }),
+    // rules: [{ kind: 'category' | 'date_pattern' | 'regex', pattern?, target?, when? }]
+    organizeFolder: (folder, rules, options = {}) =>
+        invoke('organize_folder', { folder, rules, options }),
+    listOrganizeRuns: (folder = null) =>
+        invoke('list_organize_runs', { folder }),
+    undoOrganizeRun: (runId) =>
+        invoke('undo_organize_run', { runId }),
+
+    listHotFolderRules: () =>
+        invoke('list_hot_folder_rules'),
+
+    saveHotFolderRules: (rules) =>
+        invoke('save_hot_folder_rules', { rules }),
+
+    startHotFolders: () =>
+        invoke('start_hot_folders'),
+
+    stopHotFolders: () =>
+        invoke('stop_hot_folders'),
+
+    hotFoldersStatus: () =>
+        invoke('hot_folders_status'),
+
+    hotFolderActivity: (limit = 100) =>
+        invoke('hot_folder_activity', { limit }),
+
+    // Audit log of file operations, newest first. since/until are
+    // timestamps in ms; operations e.g. ['copy', 'move']; empty = all.
+    queryActivity: (query = {}) =>
+        invoke('query_activity', { query: toActivityQuery(query) }),
+
+    // Asks where to save; resolves to the CSV path, or null when cancelled
+    exportActivity: (query = {}) =>
+        invoke('export_activity', { query: toActivityQuery(query) }),
+
+    // url: sftp://user@host[:port]/path. Resolves to the folder URL to open
+    // as a panel; trust is the fingerprint from an unknown_host error.
+    connectRemote: (url, { password = null, keyPath = null, passphrase = null, remember = false, trust = null } = {}) =>
+        invoke('connect_remote', { url, password, keyPath, passphrase, remember, trust }),
+
+    // url: s3://host[:port]/bucket/prefix, or s3+http:// for a local MinIO.
+    // Without accessKey the saved key or AWS_* environment variables are used.
+    connectS3: (url, { accessKey = null, secretKey = null, region = null, remember = false } = {}) =>
+        invoke('connect_s3', { url, accessKey, secretKey, region, remember }),
+
+    // url: davs://user@host[:port]/path, or dav:// without TLS.
+    // Without password the saved one is used, or no login.
+    connectWebdav: (url, { password = null, remember = false } = {}) =>
+        invoke('connect_webdav', { url, password, remember }),
+
+    disconnectRemote: (url) =>
+        invoke('disconnect_remote', { url }),
+
+    // Also deletes the credentials saved in the keyring
+    forgetRemote: (url) =>
+        invoke('forget_remote', { url }),
 };
+
+function toActivityQuery({ since = null, until = null, pathPrefix = null, operations = [], failedOnly = false, limit = null } = {}) {
+    return { since, until, path_prefix: pathPrefix, operations, failed_only: failedOnly, limit };
+}
+
+function toSyncOptions({ deleteExtras = false, useHash = false, filters = null } = {}) {
+    return { delete_extras: deleteExtras, use_hash: useHash, ...(filters ? { filters } : {}) };
+}
@@ -4,13 +4,17 @@ import { createPanel, makeDraggable } from './assets/components/panels.js';
 import { sortItems } from './assets/components/sorting.js';
 import { initConnectionsLayer, redrawAllConnections, connections, summaryLines } from './assets/components/connections.js';
 import { folderCache, panelSortOrder, refreshPanelByRootPath, renderTree } from './assets/components/fileTree.js';
-import { showSnackbar, showConfirmModal, showInputModal } from './assets/components/uiUtils.js';
+import { showSnackbar, showConfirmModal, showInputModal, describeError, errorAction, formatBytes } from './assets/components/uiUtils.js';
 import { refreshFolderNode } from './assets/components/fileTree.js';
 import { markTransferred } from './assets/components/transferHistory.js';
 import { activeArrow } from './assets/components/connections.js';
 import { toggleShowAllConnections } from './assets/components/connections.js';
 import { toggleBlueprintMode } from './assets/components/blueprint.js';
 import { openFeedbackModal} from './assets/components/feedback.js';
+import { hasFilters, summarizeFiltered } from './assets/components/connectionFilters.js';
+import { showHotFoldersModal } from './assets/components/hotFoldersModal.js';
+import { showRemoteModal } from './assets/components/remoteModal.js';
+import { showActivityModal } from './assets/components/activityModal.js';
 
 // Initialize global state
 initConnectionsLayer();
@@ -18,11 +22,14 @@ window.redrawAllConnections = redrawAllConnections; // For legacy compatibility
 
 // --- Top-level app functions (must stay in main.js) ---
 
-async function createPanelWithTree(type) {
-  const paths = await fileapi.pickFolder();
-  if (!paths || paths.length === 0) return;
+// Without a path the folder picker asks for one
+async function createPanelWithTree(type, path = null) {
+  if (!path) {
+    const paths = await fileapi.pickFolder();
+    if (!paths || paths.length === 0) return;
+    path = paths[0];
+  }
 
-  const path = paths[0];
   const panel = createPanel(type, path, null, null, fileapi, redrawAllConnections);
   const treeContainer = panel.querySelector(".tree-container");
   treeContainer.textContent = "Loading...";
@@ -45,6 +52,28 @@ async function executeTransfers(mode) {
     return;
   }
 
+  // Nested, circular or conflicting connections are caught before anything runs
+  let check;
+  try {
+    check = await fileapi.validateConnections(
+      connections.map(c => ({ id: c.id, from: c.fromPath, to: c.toPath, filters: hasFilters(c.filters) ? c.filters : null })),
+      mode
+    );
+  } catch (err) {
+    showSnackbar(`Could not check the connections: ${describeError(err)}`, 'error');
+    return;
+  }
+  const describeIssues = issues => issues.map(i => `#${i.connections.join(', #')}: ${i.message}`).join('\n');
+  if (check.errors.length > 0) {
+    alert(`Nothing was ${mode === 'move' ? 'moved' : 'copied'}. Fix these connections first:\n${describeIssues(check.errors)}`);
+    return;
+  }
+  if (check.warnings.length > 0) {
+    const proceed = await showConfirmModal(`${describeIssues(check.warnings)}\nContinue anyway?`);
+    if (!proceed) return;
+  }
+  const ordered = check.order.map(id => connections.find(c => c.id === id)).filter(Boolean);
+
   const operation = mode === 'move' ? 'Moving' : 'Copying';
   let successCount = 0;
   let errorCount = 0;
@@ -54,7 +83,7 @@ async function executeTransfers(mode) {
 
   // 🔴 removed: newlyCreatedPaths
 
-  for (const conn of connections) {
+  for (const conn of ordered) {
     const { fromPath, toPath } = conn;
 
     const fromParent =
@@ -74,6 +103,7 @@ async function executeTransfers(mode) {
       if (root) pathsToRefresh.add(root);
     }
 
+    let destination = toPath;
     try {
       const isSrcFolder = await fileapi.isDir(fromPath);
       const isDestFolder = await fileapi.isDir(toPath);
@@ -83,25 +113,43 @@ async function executeTransfers(mode) {
         continue;
       }
 
-      if (mode === 'move') {
-        if (isSrcFolder) {
-          await fileapi.moveFolder(fromPath, toPath);
-        } else {
-          await fileapi.moveFile(fromPath, toPath);
+      if (hasFilters(conn.filters)) {
+        const plan = await fileapi.planTransfer(fromPath, toPath, conn.filters, mode);
+        if (plan.space && !plan.space.enough) {
+          throw new Error(`Not enough space in ${toPath}: ${formatBytes(plan.space.needed)} needed, ${formatBytes(plan.space.free)} free`);
+        }
+        const lowSpace = plan.space?.low
+          ? `\n⚠️ Leaves only ${formatBytes(plan.space.free - plan.space.needed)} free on that drive.`
+          : '';
+        const proceed = await showConfirmModal(
+          `Connection #${conn.id}: ${plan.files.length} file(s) will be ${mode === 'move' ? 'moved' : 'copied'}, ` +
+          `${plan.filtered.length} filtered out.\n${summarizeFiltered(plan)}${lowSpace}\nContinue?`
+        );
+        if (!proceed) continue;
+
+        const result = await fileapi.transferFiltered(fromPath, toPath, mode, conn.filters);
+        if (result.conflicts.length > 0) {
+          result.conflicts.forEach(p => console.warn(`⚠️ ${mode} ${fromPath} → ${toPath}: already exists, skipped`, p));
+          showSnackbar(`${result.conflicts.length} file(s) already existed in ${toPath} and were left alone`, 'warning');
+        }
+        if (result.errors.length > 0) {
+          result.errors.forEach(e => console.error(`❌ ${mode} ${fromPath} → ${toPath}:`, e));
+          throw new Error(`${result.errors.length} file(s) failed`);
         }
       } else {
-        if (isSrcFolder) {
-          await fileapi.copyFolder(fromPath, toPath);
-        } else {
-          await fileapi.copyFile(fromPath, toPath);
+        destination = await transferWithFallback(mode, fromPath, toPath, isSrcFolder);
+        if (!destination) {
+          errorCount++;
+          continue;
         }
+        pathsToRefresh.add(destination);
       }
       // After move/copy succeeds
       const srcParent = fromPath.substring(0, fromPath.lastIndexOf('/')) || '/';
-      const destParent = toPath;
+      const destParent = destination;
        // ✅ Record transferred path globally
       const newItemName = fromPath.split('/').pop();
-      const newItemPath = toPath + '/' + newItemName;
+      const newItemPath = destination + '/' + newItemName;
       markTransferred(newItemPath);
 
       // Invalidate all affected cache entries
@@ -121,7 +169,37 @@ async function executeTransfers(mode) {
     }
   } 
 
-  // Clear connections (unchanged)
+  clearCompletedConnections();
+  alert(`${operation} complete!\n✅ Success: ${successCount}\n❌ Errors: ${errorCount}`);
+}
+
+// Move or copy one item. When the destination can't take it (name taken,
+// no permission, read-only, full, …) offer to pick another folder instead.
+// Resolves to the folder used, or null if the user gave up.
+async function transferWithFallback(mode, fromPath, toPath, isSrcFolder) {
+  const transfer = dest => mode === 'move'
+    ? (isSrcFolder ? fileapi.moveFolder(fromPath, dest) : fileapi.moveFile(fromPath, dest))
+    : (isSrcFolder ? fileapi.copyFolder(fromPath, dest) : fileapi.copyFile(fromPath, dest));
+
+  let dest = toPath;
+  for (;;) {
+    try {
+      await transfer(dest);
+      return dest;
+    } catch (err) {
+      const action = errorAction(err);
+      if (action !== 'choose_folder' && err?.code !== 'already_exists') throw err;
+      const name = fromPath.split('/').pop();
+      const pick = await showConfirmModal(`${describeError(err)}\nChoose another folder for "${name}"?`);
+      if (!pick) return null;
+      const [other] = await fileapi.pickFolder();
+      if (!other) return null;
+      dest = other;
+    }
+  }
+}
+
+function clearCompletedConnections() {
   for (const c of connections) {
     c.pathEl?.remove();
     c.cancelBtn?.remove();
@@ -131,12 +209,71 @@ async function executeTransfers(mode) {
   summaryLines.clear();
 
   redrawAllConnections();
-  alert(`${operation} complete!\n✅ Success: ${successCount}\n❌ Errors: ${errorCount}`);
+}
+
+// mode: 'mirror' (one-way) or 'two_way'
+async function executeSync(mode) {
+  if (connections.length === 0) {
+    alert("No connections to sync!");
+    return;
+  }
+
+  const options = { useHash: false, deleteExtras: false };
+  options.useHash = await showConfirmModal(
+    "Compare file contents when only the modification time differs? (slower, but catches copies made without their dates)"
+  );
+  if (mode === 'mirror') {
+    options.deleteExtras = await showConfirmModal(
+      "Also delete files in the destination that are not in the source?"
+    );
+  }
+
+  let copied = 0;
+  let deleted = 0;
+  let errorCount = 0;
+  const conflicts = [];
+
+  for (const conn of connections) {
+    const { fromPath, toPath } = conn;
+
+    try {
+      const filters = hasFilters(conn.filters) ? conn.filters : null;
+      const report = await fileapi.runSync(fromPath, toPath, mode, { ...options, filters });
+      copied += report.copied;
+      deleted += report.deleted;
+      errorCount += report.errors.length;
+      report.errors.forEach(e => console.error(`❌ Sync ${fromPath} → ${toPath}:`, e));
+      conflicts.push(...report.conflicts.map(c => `${report.destination}/${c.path} (${c.reason})`));
+      markTransferred(report.destination);
+
+      const srcParent = fromPath.substring(0, fromPath.lastIndexOf('/')) || '/';
+      folderCache.delete(fromPath);
+      folderCache.delete(toPath);
+      folderCache.delete(report.destination);
+      await refreshPanelByRootPath(srcParent, fileapi);
+      await refreshPanelByRootPath(toPath, fileapi);
+    } catch (err) {
+      console.error(`❌ Failed to sync ${fromPath} → ${toPath}:`, err);
+      errorCount++;
+    }
+  }
+
+  clearCompletedConnections();
+
+  let message = `Sync complete!\n✅ Copied: ${copied}\n🗑️ Deleted: ${deleted}\n❌ Errors: ${errorCount}`;
+  if (conflicts.length > 0) {
+    message += `\n⚠️ Conflicts (left untouched): ${conflicts.length}\n` + conflicts.slice(0, 10).join('\n');
+  }
+  alert(message);
 }
 
 // --- Button Event Listeners ---
 document.getElementById("addSourceBtn")?.addEventListener("click", () => createPanelWithTree("source"));
 document.getElementById("addDestinationBtn")?.addEventListener("click", () => createPanelWithTree("destination"));
+document.getElementById("addRemoteBtn")?.addEventListener("click", async () => {
+  const remote = await showRemoteModal();
+  if (remote) createPanelWithTree(remote.type, remote.url);
+});
 
 document.getElementById('clearAllBtn')?.addEventListener('click', () => {
   document.querySelectorAll('[id^="panel-"]').forEach(el => el.remove());
@@ -174,6 +311,8 @@ document.getElementById('clearAllarr')?.addEventListener('click', () => {
 
 document.getElementById('copy')?.addEventListener('click', () => executeTransfers('copy'));
 document.querySelector('#move button:first-child')?.addEventListener('click', () => executeTransfers('move'));
+document.getElementById('mirror')?.addEventListener('click', () => executeSync('mirror'));
+document.getElementById('sync')?.addEventListener('click', () => executeSync('two_way'));
 
 // Add button handler (place with your other event listeners)
 document.getElementById('showAllConnectionsBtn')?.addEventListener('click', () => {
@@ -226,3 +365,10 @@ document
   .getElementById("suggestBtn")
   .addEventListener("click", () => openFeedbackModal("suggestion"));
 
+document
+  .getElementById("hotFoldersBtn")
+  .addEventListener("click", () => showHotFoldersModal());
+
+document
+  .getElementById("activityBtn")
+  .addEventListener("click", () => showActivityModal());